@@ -0,0 +1,118 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::response::{Account, Site};
+use crate::SiteStatus;
+
+/// Hierarchy of the accounts built from [Client::accounts_list()](crate::Client::accounts_list) results using
+/// [Account::parent_id]
+#[derive(Clone, Debug)]
+pub struct AccountTree<'a> {
+	accounts: HashMap<u64, &'a Account>,
+	children: HashMap<u64, Vec<&'a Account>>,
+	roots: Vec<&'a Account>,
+}
+
+impl<'a> AccountTree<'a> {
+	/// Accounts without parent or with the parent missing from `accounts` become roots of the tree
+	pub fn new(accounts: &'a [Account]) -> Self {
+		let by_id = accounts
+			.iter()
+			.map(|account| (account.id, account))
+			.collect::<HashMap<_, _>>();
+		let mut children = HashMap::<_, Vec<_>>::new();
+		let mut roots = vec![];
+		for account in accounts {
+			match account.parent_id {
+				Some(parent_id) if parent_id != account.id && by_id.contains_key(&parent_id) => {
+					children.entry(parent_id).or_default().push(account);
+				}
+				_ => roots.push(account),
+			}
+		}
+		Self {
+			accounts: by_id,
+			children,
+			roots,
+		}
+	}
+
+	pub fn get(&self, id: u64) -> Option<&'a Account> {
+		self.accounts.get(&id).copied()
+	}
+
+	pub fn roots(&self) -> &[&'a Account] {
+		&self.roots
+	}
+
+	pub fn parent(&self, id: u64) -> Option<&'a Account> {
+		self.get(id)?.parent_id.and_then(|parent_id| self.get(parent_id))
+	}
+
+	/// Direct sub-accounts of the account `id`
+	pub fn children(&self, id: u64) -> &[&'a Account] {
+		self.children.get(&id).map_or(&[], Vec::as_slice)
+	}
+
+	/// All sub-accounts of the account `id` at any depth in depth-first order, the account itself is not included
+	pub fn descendants(&self, id: u64) -> Vec<&'a Account> {
+		let mut out = vec![];
+		let mut seen = HashSet::from([id]);
+		let mut stack = self.children(id).iter().rev().copied().collect::<Vec<_>>();
+		while let Some(account) = stack.pop() {
+			// guards against the cycles in the malformed data
+			if seen.insert(account.id) {
+				out.push(account);
+				stack.extend(self.children(account.id).iter().rev());
+			}
+		}
+		out
+	}
+}
+
+/// Sites belonging to a single account, see [group_sites_by_account()]
+#[derive(Clone, Debug)]
+pub struct AccountSites<'a> {
+	pub account_id: u64,
+	/// `None` if the account was not found in the passed accounts
+	pub account: Option<&'a Account>,
+	pub sites: Vec<&'a Site>,
+}
+
+impl AccountSites<'_> {
+	/// Sum of the peak power of all sites, kWp
+	pub fn total_peak_power(&self) -> f64 {
+		self.sites.iter().map(|site| site.peak_power).sum()
+	}
+
+	pub fn active_site_count(&self) -> usize {
+		self
+			.sites
+			.iter()
+			.filter(|site| matches!(site.status, SiteStatus::Active))
+			.count()
+	}
+
+	/// Sum of the open alerts of all sites
+	pub fn alert_count(&self) -> u32 {
+		self.sites.iter().filter_map(|site| site.alert_quantity).sum()
+	}
+}
+
+/// Groups the sites by [Site::account_id], the groups are ordered by account id
+///
+/// Pass the result of [Client::accounts_list()](crate::Client::accounts_list) as `accounts` to have the account details
+/// attached to the groups or an empty slice if they are not needed.
+pub fn group_sites_by_account<'a>(sites: &'a [Site], accounts: &'a [Account]) -> Vec<AccountSites<'a>> {
+	let mut groups = BTreeMap::<_, Vec<_>>::new();
+	for site in sites {
+		groups.entry(site.account_id).or_default().push(site);
+	}
+	groups
+		.into_iter()
+		.map(|(account_id, sites)| AccountSites {
+			account_id,
+			account: accounts.iter().find(|account| account.id == account_id),
+			sites,
+		})
+		.collect()
+}
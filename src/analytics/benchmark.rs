@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::fleet::median;
+use crate::api::Date;
+use crate::response::{Site, SiteEnergyBulkList};
+use crate::SiteId;
+
+/// Sites that are close enough to share the weather, see [group_sites_by_city()] and [group_sites_by_distance()]
+#[derive(Clone, Debug)]
+pub struct LocationGroup<'a> {
+	/// Grouping key, e.g. `de/berlin` for [group_sites_by_city()] or `near/1` for [group_sites_by_distance()]
+	pub key: String,
+	/// Sorted by site id
+	pub sites: Vec<&'a Site>,
+}
+
+/// Groups the sites by country code and city, the comparison is case-insensitive and ignores the surrounding spaces
+///
+/// The groups are sorted by key.
+pub fn group_sites_by_city(sites: &[Site]) -> Vec<LocationGroup<'_>> {
+	let mut groups = BTreeMap::<_, Vec<_>>::new();
+	for site in sites {
+		let key = format!(
+			"{}/{}",
+			site.location.country_code.trim().to_lowercase(),
+			site.location.city.trim().to_lowercase()
+		);
+		groups.entry(key).or_default().push(site);
+	}
+	groups
+		.into_iter()
+		.map(|(key, mut sites)| {
+			sites.sort_unstable_by_key(|site| site.id);
+			LocationGroup { key, sites }
+		})
+		.collect()
+}
+
+/// Groups the sites that are within `radius_km` of each other
+///
+/// Grouping is transitive: if site A is close to B and B is close to C then all three end up in the same group even if A
+/// is farther than `radius_km` from C. Sites without coordinates are left out. The group key is `near/<lowest site id>`,
+/// the groups are sorted by that site id.
+pub fn group_sites_by_distance(sites: &[Site], radius_km: f64) -> Vec<LocationGroup<'_>> {
+	let mut sites = sites
+		.iter()
+		.filter(|site| site.location.coordinates().is_some())
+		.collect::<Vec<_>>();
+	sites.sort_unstable_by_key(|site| site.id);
+	// union-find over the site indices, roots are always the lowest index in the set
+	let mut parents = (0..sites.len()).collect::<Vec<_>>();
+	fn root(parents: &mut [usize], mut i: usize) -> usize {
+		while parents[i] != i {
+			parents[i] = parents[parents[i]];
+			i = parents[i];
+		}
+		i
+	}
+	for i in 0..sites.len() {
+		for j in i + 1..sites.len() {
+			if sites[i]
+				.location
+				.distance_km(&sites[j].location)
+				.is_some_and(|distance| distance <= radius_km)
+			{
+				let (a, b) = (root(&mut parents, i), root(&mut parents, j));
+				parents[a.max(b)] = a.min(b);
+			}
+		}
+	}
+	let mut groups = BTreeMap::<_, Vec<_>>::new();
+	for (i, site) in sites.into_iter().enumerate() {
+		groups.entry(root(&mut parents, i)).or_default().push(site);
+	}
+	groups
+		.into_values()
+		.map(|sites| LocationGroup {
+			key: format!("near/{}", sites[0].id),
+			sites,
+		})
+		.collect()
+}
+
+/// Daily production of the site that deviates from its location group, see [benchmark_daily()]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkDeviation {
+	pub site_id: SiteId,
+	pub date: Date,
+	/// Energy per kWp
+	pub specific_yield: f64,
+	/// Median of the specific yields of the group sites for that day
+	pub group_median: f64,
+	/// Relative deviation from the group median, e.g. `-0.2` for 20% below the median
+	pub deviation: f64,
+}
+
+/// Compares the daily specific yield of each site against the median of its location group
+///
+/// Nearby sites receive roughly the same irradiance, so comparing against the group median cancels out the weather and
+/// leaves the site-specific issues. `bulk` should be fetched with [TimeUnit::Day](crate::TimeUnit::Day). Groups with
+/// fewer than `min_group_size` sites reporting on a given day are not checked for that day, sites without the peak
+/// power are skipped. Returns the site-days which deviation exceeds `tolerance` (fraction, e.g. `0.2` for 20%) in
+/// either direction, sorted by date and site id.
+pub fn benchmark_daily(
+	groups: &[LocationGroup],
+	bulk: &SiteEnergyBulkList,
+	min_group_size: usize,
+	tolerance: f64,
+) -> Vec<BenchmarkDeviation> {
+	let site_energies = bulk
+		.site_energy_list
+		.iter()
+		.map(|site| (site.site_id, &site.energy_values.values))
+		.collect::<HashMap<_, _>>();
+	let mut out = vec![];
+	for group in groups {
+		let mut days = BTreeMap::<_, Vec<_>>::new();
+		for site in group.sites.iter().filter(|site| site.peak_power > 0.) {
+			let Some(values) = site_energies.get(&site.id) else {
+				continue;
+			};
+			for value in values.iter() {
+				if let Some(energy) = value.value {
+					days
+						.entry(value.date.date())
+						.or_default()
+						.push((site.id, energy / site.peak_power, &value.date));
+				}
+			}
+		}
+		for yields in days.into_values() {
+			if yields.len() < min_group_size.max(1) {
+				continue;
+			}
+			let Some(group_median) = median(yields.iter().map(|(_, specific_yield, _)| *specific_yield).collect()) else {
+				continue;
+			};
+			if group_median <= 0. {
+				continue;
+			}
+			for (site_id, specific_yield, timestamp) in yields {
+				let deviation = specific_yield / group_median - 1.;
+				if deviation.abs() > tolerance {
+					out.push(BenchmarkDeviation {
+						site_id,
+						date: timestamp.date(),
+						specific_yield,
+						group_median,
+						deviation,
+					});
+				}
+			}
+		}
+	}
+	out.sort_by(|a, b| a.date.cmp(&b.date).then(a.site_id.cmp(&b.site_id)));
+	out
+}
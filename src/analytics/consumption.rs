@@ -0,0 +1,146 @@
+use chrono::{Datelike, Timelike};
+
+use crate::api::DateTime;
+use crate::response::SiteDateValue;
+
+/// Number of the hour-of-week slots in [ConsumptionBaseline]
+pub const HOURS_IN_WEEK: usize = 7 * 24;
+
+/// Index of the hour within the week, 0 is Monday 00:00-00:59, 167 is Sunday 23:00-23:59
+pub fn hour_of_week(date: &DateTime) -> usize {
+	date.weekday().num_days_from_monday() as usize * 24 + date.hour() as usize
+}
+
+/// Statistics of the values observed within a single hour-of-week slot
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BaselineSlot {
+	pub mean: f64,
+	/// Population standard deviation
+	pub std_dev: f64,
+	pub samples: usize,
+}
+
+/// Expected consumption for every hour of the week built from the historical series
+///
+/// The values are used in the units they come in so the baseline must be built from and compared to the series fetched
+/// with the same time unit, e.g. [TimeUnit::Hour](crate::TimeUnit::Hour) energy or the power from
+/// [Client::site_power_details()](crate::Client::site_power_details).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsumptionBaseline {
+	slots: Vec<Option<BaselineSlot>>,
+}
+
+impl ConsumptionBaseline {
+	/// Builds the baseline from `history`, the items without value are skipped
+	pub fn new(history: &[SiteDateValue]) -> Self {
+		let mut sums = vec![(0usize, 0., 0.); HOURS_IN_WEEK];
+		for item in history {
+			if let Some(value) = item.value {
+				let (count, sum, sum_sq) = &mut sums[hour_of_week(&item.date)];
+				*count += 1;
+				*sum += value;
+				*sum_sq += value * value;
+			}
+		}
+		let slots = sums
+			.into_iter()
+			.map(|(count, sum, sum_sq)| {
+				(count > 0).then(|| {
+					let mean = sum / count as f64;
+					BaselineSlot {
+						mean,
+						std_dev: (sum_sq / count as f64 - mean * mean).max(0.).sqrt(),
+						samples: count,
+					}
+				})
+			})
+			.collect();
+		Self { slots }
+	}
+
+	/// Statistics for the hour-of-week slot that `date` falls into, `None` if the history had no values for it
+	pub fn slot(&self, date: &DateTime) -> Option<&BaselineSlot> {
+		self.slots[hour_of_week(date)].as_ref()
+	}
+
+	/// Flags the items of `series` that deviate from the baseline by more than `sigmas` standard deviations
+	///
+	/// Slots with fewer than `min_samples` historical values are not checked. If all historical values in the slot are
+	/// equal any different value is flagged.
+	pub fn detect_anomalies<'a>(
+		&self,
+		series: &'a [SiteDateValue],
+		sigmas: f64,
+		min_samples: usize,
+	) -> Vec<ConsumptionAnomaly<'a>> {
+		series
+			.iter()
+			.filter_map(|item| {
+				let value = item.value?;
+				let slot = self.slot(&item.date).filter(|slot| slot.samples >= min_samples)?;
+				let deviation = value - slot.mean;
+				let is_anomaly = if slot.std_dev > 0. {
+					deviation.abs() > sigmas * slot.std_dev
+				} else {
+					deviation != 0.
+				};
+				is_anomaly.then_some(ConsumptionAnomaly {
+					item,
+					expected: slot.mean,
+					deviation,
+				})
+			})
+			.collect()
+	}
+}
+
+/// Item that deviates from the [ConsumptionBaseline]
+#[derive(Clone, Copy, Debug)]
+pub struct ConsumptionAnomaly<'a> {
+	pub item: &'a SiteDateValue,
+	/// Baseline mean for the item's hour-of-week slot
+	pub expected: f64,
+	/// Difference between the actual and the expected value, positive if the consumption is higher than usual
+	pub deviation: f64,
+}
+
+/// Run of identical consecutive values, see [find_stuck_periods()]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StuckPeriod {
+	pub start: DateTime,
+	pub end: DateTime,
+	pub value: f64,
+	pub samples: usize,
+}
+
+/// Finds the runs of at least `min_samples` consecutive items reporting exactly the same value
+///
+/// Real consumption fluctuates all the time, so long runs of the same non-zero value usually mean that the meter is
+/// stuck. Runs of zeroes are reported too when `include_zero` is `true`. `series` must be sorted by date, the items
+/// without value break the run.
+pub fn find_stuck_periods(series: &[SiteDateValue], min_samples: usize, include_zero: bool) -> Vec<StuckPeriod> {
+	let mut out = vec![];
+	let mut current: Option<StuckPeriod> = None;
+	for item in series {
+		match (&mut current, item.value) {
+			(Some(period), Some(value)) if period.value == value => {
+				period.end = item.date;
+				period.samples += 1;
+				continue;
+			}
+			_ => {}
+		}
+		out.extend(current.take().filter(|period| period.samples >= min_samples));
+		current = item
+			.value
+			.filter(|&value| include_zero || value != 0.)
+			.map(|value| StuckPeriod {
+				start: item.date,
+				end: item.date,
+				value,
+				samples: 1,
+			});
+	}
+	out.extend(current.filter(|period| period.samples >= min_samples));
+	out
+}
@@ -0,0 +1,148 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::response::{Battery, Equipment, Gateway, Inverter, Meter, Sensor, SiteInventory};
+
+/// Kind of the [Device]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeviceKind {
+	Inverter,
+	Meter,
+	Battery,
+	Gateway,
+	Sensor,
+}
+
+impl Display for DeviceKind {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		let s = match self {
+			DeviceKind::Inverter => "Inverter",
+			DeviceKind::Meter => "Meter",
+			DeviceKind::Battery => "Battery",
+			DeviceKind::Gateway => "Gateway",
+			DeviceKind::Sensor => "Sensor",
+		};
+		f.write_str(s)
+	}
+}
+
+/// Device returned by [Client::site_inventory()](crate::Client::site_inventory) or
+/// [Client::equipment_list()](crate::Client::equipment_list) with the accessors common for all kinds
+#[derive(Clone, Copy, Debug)]
+pub enum Device<'a> {
+	Inverter(&'a Inverter),
+	Meter(&'a Meter),
+	Battery(&'a Battery),
+	Gateway(&'a Gateway),
+	Sensor(&'a Sensor),
+	/// Inverter from [Client::equipment_list()](crate::Client::equipment_list)
+	Equipment(&'a Equipment),
+}
+
+impl<'a> Device<'a> {
+	pub fn kind(&self) -> DeviceKind {
+		match self {
+			Device::Inverter(_) | Device::Equipment(_) => DeviceKind::Inverter,
+			Device::Meter(_) => DeviceKind::Meter,
+			Device::Battery(_) => DeviceKind::Battery,
+			Device::Gateway(_) => DeviceKind::Gateway,
+			Device::Sensor(_) => DeviceKind::Sensor,
+		}
+	}
+
+	/// Name of the device, for sensors it's the sensor id
+	pub fn name(&self) -> &'a str {
+		match self {
+			Device::Inverter(d) => &d.name,
+			Device::Meter(d) => &d.name,
+			Device::Battery(d) => &d.name,
+			Device::Gateway(d) => &d.name,
+			Device::Sensor(d) => &d.id,
+			Device::Equipment(d) => &d.name,
+		}
+	}
+
+	/// Serial number, meters and sensors don't report their own serial number
+	pub fn serial(&self) -> Option<&'a str> {
+		match self {
+			Device::Inverter(d) => Some(&d.sn),
+			Device::Battery(d) => Some(&d.sn),
+			Device::Gateway(d) => Some(&d.sn),
+			Device::Equipment(d) => Some(&d.serial_number),
+			Device::Meter(_) | Device::Sensor(_) => None,
+		}
+	}
+
+	pub fn manufacturer(&self) -> Option<&'a str> {
+		match self {
+			Device::Inverter(d) => Some(&d.manufacturer),
+			Device::Meter(d) => d.manufacturer.as_deref(),
+			Device::Battery(d) => Some(&d.manufacturer),
+			Device::Equipment(d) => Some(&d.manufacturer),
+			Device::Gateway(_) | Device::Sensor(_) => None,
+		}
+	}
+
+	pub fn model(&self) -> Option<&'a str> {
+		match self {
+			Device::Inverter(d) => Some(&d.model),
+			Device::Meter(d) => Some(&d.model),
+			Device::Battery(d) => Some(&d.model),
+			Device::Equipment(d) => Some(&d.model),
+			Device::Gateway(_) | Device::Sensor(_) => None,
+		}
+	}
+
+	pub fn firmware_version(&self) -> Option<&'a str> {
+		match self {
+			Device::Meter(d) => d.firmware_version.as_deref(),
+			Device::Battery(d) => Some(&d.firmware_version),
+			Device::Gateway(d) => Some(&d.firmware_version),
+			Device::Inverter(_) | Device::Sensor(_) | Device::Equipment(_) => None,
+		}
+	}
+
+	/// Serial number of the SolarEdge device this one is connected to
+	pub fn connected_to_serial(&self) -> Option<&'a str> {
+		match self {
+			Device::Meter(d) => Some(&d.connected_solaredge_device_sn),
+			Device::Sensor(d) => Some(&d.connected_solaredge_device_sn),
+			Device::Battery(d) => Some(&d.connected_inverter_sn),
+			Device::Inverter(_) | Device::Gateway(_) | Device::Equipment(_) => None,
+		}
+	}
+}
+
+macro_rules! impl_from_device {
+	($($typ: ty => $variant: ident;)+) => {
+		$(
+			impl<'a> From<&'a $typ> for Device<'a> {
+				#[inline]
+				fn from(device: &'a $typ) -> Self {
+					Device::$variant(device)
+				}
+			}
+		)+
+	};
+}
+
+impl_from_device! {
+	Inverter => Inverter;
+	Meter => Meter;
+	Battery => Battery;
+	Gateway => Gateway;
+	Sensor => Sensor;
+	Equipment => Equipment;
+}
+
+/// All devices of the inventory in the order: inverters, meters, batteries, gateways, sensors
+pub fn inventory_devices(inventory: &SiteInventory) -> Vec<Device<'_>> {
+	inventory
+		.inverters
+		.iter()
+		.map(Device::from)
+		.chain(inventory.meters.iter().map(Device::from))
+		.chain(inventory.batteries.iter().map(Device::from))
+		.chain(inventory.gateways.iter().map(Device::from))
+		.chain(inventory.sensors.iter().map(Device::from))
+		.collect()
+}
@@ -0,0 +1,77 @@
+use crate::response::{Equipment, EquipmentChange, EquipmentTelemetry};
+use crate::SerialNumber;
+
+/// Single equipment change with the context of the device it was reported for
+#[derive(Clone, Copy, Debug)]
+pub struct TimelineEntry<'a> {
+	pub device: &'a Equipment,
+	pub change: &'a EquipmentChange,
+}
+
+/// Merges the changelogs of multiple devices into a single timeline ordered by date
+///
+/// Each item of `changelogs` is a device from [Client::equipment_list()](crate::Client::equipment_list) and the result of
+/// [Client::equipment_changelog()](crate::Client::equipment_changelog) for it. The changes that happened on the same day
+/// keep the order in which they were passed.
+pub fn merge_changelogs<'a, L: AsRef<[EquipmentChange]> + 'a>(
+	changelogs: impl IntoIterator<Item = (&'a Equipment, &'a L)>,
+) -> Vec<TimelineEntry<'a>> {
+	let mut out = changelogs
+		.into_iter()
+		.flat_map(|(device, changes)| changes.as_ref().iter().map(move |change| TimelineEntry { device, change }))
+		.collect::<Vec<_>>();
+	out.sort_by_key(|entry| &entry.change.date);
+	out
+}
+
+/// Share of a single inverter in the site production over a period, see [inverter_contributions()]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InverterContribution<'a> {
+	pub serial_number: &'a SerialNumber,
+	/// Wh produced by the inverter in the period according to its lifetime energy counter
+	pub energy: f64,
+	/// Fraction of the energy of all inverters, 0 if none of them produced anything
+	pub share: f64,
+	/// Part of the site production apportioned to the inverter by its share, `None` if the site production is not passed
+	pub site_energy: Option<f64>,
+}
+
+/// Apportions the site production over a period across the inverters by the deltas of their lifetime energy counters
+///
+/// Each item of `telemetries` is the serial number of the inverter and its telemetries for the period, e.g. from
+/// [Client::equipment_data_all()](crate::Client::equipment_data_all). The energy of the inverter is the sum of the
+/// increases of [EquipmentTelemetry::total_energy] between the consecutive samples, so a counter reset after the inverter
+/// replacement doesn't produce a negative value. The inverter counters don't match the site meter exactly, pass the
+/// `site_energy`, e.g. from [Client::site_energy()](crate::Client::site_energy), to split it in the same proportions.
+/// The result is sorted by the serial number.
+pub fn inverter_contributions<'a, T: AsRef<[EquipmentTelemetry]> + 'a>(
+	telemetries: impl IntoIterator<Item = (&'a SerialNumber, &'a T)>,
+	site_energy: Option<f64>,
+) -> Vec<InverterContribution<'a>> {
+	let mut out = telemetries
+		.into_iter()
+		.map(|(serial_number, telemetries)| {
+			let mut samples = telemetries.as_ref().iter().collect::<Vec<_>>();
+			samples.sort_by_key(|telemetry| &telemetry.date);
+			let energy = samples
+				.windows(2)
+				.map(|pair| (pair[1].total_energy - pair[0].total_energy).max(0.))
+				.sum::<f64>();
+			InverterContribution {
+				serial_number,
+				energy,
+				share: 0.,
+				site_energy: None,
+			}
+		})
+		.collect::<Vec<_>>();
+	let total = out.iter().map(|contribution| contribution.energy).sum::<f64>();
+	for contribution in &mut out {
+		if total > 0. {
+			contribution.share = contribution.energy / total;
+		}
+		contribution.site_energy = site_energy.map(|site_energy| site_energy * contribution.share);
+	}
+	out.sort_by(|a, b| a.serial_number.cmp(b.serial_number));
+	out
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use crate::response::{Site, SiteEnergyBulkList, SiteTimeframeEnergyBulk};
+use crate::SiteId;
+
+/// Production of a single site normalized by its peak power
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SiteYield {
+	pub site_id: SiteId,
+	/// Energy in the unit of the source response, usually Wh
+	pub energy: f64,
+	/// kWp
+	pub peak_power: f64,
+	/// Energy per kWp
+	pub specific_yield: f64,
+}
+
+/// Fleet-level production aggregated across the sites weighted by their peak power
+#[derive(Clone, Debug, PartialEq)]
+pub struct FleetYield {
+	/// Sorted by site id
+	pub sites: Vec<SiteYield>,
+	pub total_energy: f64,
+	/// kWp
+	pub total_peak_power: f64,
+	/// Total energy per total kWp, i.e. the peak-power weighted average of the site specific yields
+	pub specific_yield: f64,
+	/// Median of the site specific yields
+	pub median_specific_yield: f64,
+}
+
+impl FleetYield {
+	/// Builds the aggregation from `(site_id, energy)` pairs
+	///
+	/// `sites` provide the peak power, site ids missing from them or sites with no peak power are skipped. Energies for
+	/// the same site id are summed.
+	pub fn new(sites: &[Site], energies: impl IntoIterator<Item = (impl Into<SiteId>, f64)>) -> Self {
+		let peak_powers = sites
+			.iter()
+			.filter(|site| site.peak_power > 0.)
+			.map(|site| (site.id, site.peak_power))
+			.collect::<HashMap<_, _>>();
+		let mut site_energies = HashMap::<_, f64>::new();
+		for (site_id, energy) in energies {
+			let site_id = site_id.into();
+			if peak_powers.contains_key(&site_id) {
+				*site_energies.entry(site_id).or_default() += energy;
+			}
+		}
+		let mut site_yields = site_energies
+			.into_iter()
+			.map(|(site_id, energy)| {
+				let peak_power = peak_powers[&site_id];
+				SiteYield {
+					site_id,
+					energy,
+					peak_power,
+					specific_yield: energy / peak_power,
+				}
+			})
+			.collect::<Vec<_>>();
+		site_yields.sort_unstable_by_key(|site| site.site_id);
+		let total_energy = site_yields.iter().map(|site| site.energy).sum::<f64>();
+		let total_peak_power = site_yields.iter().map(|site| site.peak_power).sum::<f64>();
+		let specific_yield = if total_peak_power > 0. {
+			total_energy / total_peak_power
+		} else {
+			0.
+		};
+		let median_specific_yield = median(site_yields.iter().map(|site| site.specific_yield).collect()).unwrap_or(0.);
+		Self {
+			sites: site_yields,
+			total_energy,
+			total_peak_power,
+			specific_yield,
+			median_specific_yield,
+		}
+	}
+
+	/// Builds the aggregation from the [Client::site_energy_bulk()](crate::Client::site_energy_bulk) result summing all
+	/// the values of each site
+	pub fn from_energy_bulk(sites: &[Site], bulk: &SiteEnergyBulkList) -> Self {
+		let energies = bulk.site_energy_list.iter().flat_map(|site| {
+			site
+				.energy_values
+				.values
+				.iter()
+				.filter_map(move |value| value.value.map(|energy| (site.site_id, energy)))
+		});
+		Self::new(sites, energies)
+	}
+
+	/// Builds the aggregation from the [Client::site_time_frame_energy_bulk()](crate::Client::site_time_frame_energy_bulk)
+	/// result
+	pub fn from_time_frame_energy_bulk(sites: &[Site], bulk: &[SiteTimeframeEnergyBulk]) -> Self {
+		Self::new(sites, bulk.iter().map(|site| (site.site_id, site.timeframe_energy.energy)))
+	}
+
+	/// Sites which specific yield is lower than the fleet median by more than `tolerance` (fraction, e.g. `0.1` for 10%)
+	pub fn underperformers(&self, tolerance: f64) -> Vec<&SiteYield> {
+		let threshold = self.median_specific_yield * (1. - tolerance);
+		self.sites.iter().filter(|site| site.specific_yield < threshold).collect()
+	}
+}
+
+pub(crate) fn median(mut values: Vec<f64>) -> Option<f64> {
+	if values.is_empty() {
+		return None;
+	}
+	values.sort_unstable_by(f64::total_cmp);
+	let mid = values.len() / 2;
+	Some(if values.len() % 2 == 0 {
+		(values[mid - 1] + values[mid]) / 2.
+	} else {
+		values[mid]
+	})
+}
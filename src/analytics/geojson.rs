@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use super::fleet::FleetYield;
+use crate::response::Site;
+
+/// Exports the site locations as a GeoJSON `FeatureCollection` of `Point` features
+///
+/// Every feature carries the site id, name, status, peak power (kWp), installation date and open alert count in its
+/// properties. When `fleet` is passed the sites found there also get `energy` and `specificYield` properties. Sites
+/// without coordinates are skipped.
+pub fn sites_to_geojson(sites: &[Site], fleet: Option<&FleetYield>) -> Value {
+	let yields = fleet
+		.map(|fleet| fleet.sites.iter().map(|site| (site.site_id, site)).collect::<HashMap<_, _>>())
+		.unwrap_or_default();
+	let features = sites
+		.iter()
+		.filter_map(|site| {
+			let (lat, lng) = site.location.coordinates()?;
+			let mut properties = Map::new();
+			properties.insert("id".to_string(), json!(site.id));
+			properties.insert("name".to_string(), json!(site.name));
+			properties.insert("status".to_string(), json!(site.status.to_string()));
+			properties.insert("peakPower".to_string(), json!(site.peak_power));
+			properties.insert("installationDate".to_string(), json!(site.installation_date.to_string()));
+			properties.insert("alertQuantity".to_string(), json!(site.alert_quantity.unwrap_or(0)));
+			if let Some(site_yield) = yields.get(&site.id) {
+				properties.insert("energy".to_string(), json!(site_yield.energy));
+				properties.insert("specificYield".to_string(), json!(site_yield.specific_yield));
+			}
+			Some(json!({
+				"type": "Feature",
+				"geometry": {
+					"type": "Point",
+					"coordinates": [lng, lat],
+				},
+				"properties": properties,
+			}))
+		})
+		.collect::<Vec<_>>();
+	json!({
+		"type": "FeatureCollection",
+		"features": features,
+	})
+}
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::api::DateTime;
+use crate::response::{Meter, SiteDateValue, SiteInventory, SiteMeterValue, SiteMeters, SiteMetersDetails};
+use crate::MeterType;
+
+/// Identity of the meter usable as the key of the joins and in the exports
+///
+/// The physical meters are identified by their serial number. The virtual meters don't have one, so they are identified by
+/// their name and type. The inventory doesn't report the serial numbers of the meters, so the meters from there are
+/// always identified by the name and type, while the series from [Client::site_meters()](crate::Client::site_meters)
+/// use the serial number when it's not empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MeterId<'a> {
+	Serial(&'a str),
+	Named {
+		name: &'a str,
+		/// Meter type as reported by the API, e.g. `Production` or `FeedIn`
+		meter_type: &'a str,
+	},
+	/// Meter known only by its type, e.g. the series of the energy details without a matching inventory meter
+	Type(&'a str),
+}
+
+impl MeterId<'_> {
+	#[inline]
+	pub fn serial(&self) -> Option<&str> {
+		match self {
+			MeterId::Serial(serial) => Some(serial),
+			MeterId::Named { .. } | MeterId::Type(_) => None,
+		}
+	}
+}
+
+impl<'a> From<&'a Meter> for MeterId<'a> {
+	#[inline]
+	fn from(meter: &'a Meter) -> Self {
+		MeterId::Named {
+			name: &meter.name,
+			meter_type: &meter.typ,
+		}
+	}
+}
+
+impl Display for MeterId<'_> {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		match self {
+			MeterId::Serial(serial) => f.write_str(serial),
+			MeterId::Named { name, meter_type } => write!(f, "{name} ({meter_type})"),
+			MeterId::Type(meter_type) => f.write_str(meter_type),
+		}
+	}
+}
+
+/// Meter series joined with the meter metadata from [SiteInventory]
+#[derive(Clone, Debug)]
+pub struct EnrichedMeter<'a> {
+	/// Meter type as reported by the API, e.g. `Production` or `FeedIn`
+	pub meter_type: &'a str,
+	/// Only available for the series from [Client::site_meters()](crate::Client::site_meters)
+	pub serial_number: Option<&'a str>,
+	pub connected_solaredge_device_sn: Option<&'a str>,
+	/// `None` if no matching meter was found in the inventory, e.g. for virtual meters
+	pub metadata: Option<&'a Meter>,
+	pub values: &'a [SiteDateValue],
+}
+
+impl<'a> EnrichedMeter<'a> {
+	/// Identity of the meter, see [MeterId]
+	pub fn id(&self) -> MeterId<'a> {
+		match (self.serial_number.filter(|serial| !serial.is_empty()), self.metadata) {
+			(Some(serial), _) => MeterId::Serial(serial),
+			(None, Some(meter)) => MeterId::Named {
+				name: &meter.name,
+				meter_type: self.meter_type,
+			},
+			(None, None) => MeterId::Type(self.meter_type),
+		}
+	}
+
+	pub fn name(&self) -> Option<&str> {
+		self.metadata.map(|m| m.name.as_str())
+	}
+
+	pub fn manufacturer(&self) -> Option<&str> {
+		self.metadata.and_then(|m| m.manufacturer.as_deref())
+	}
+
+	pub fn model(&self) -> Option<&str> {
+		self.metadata.map(|m| m.model.as_str())
+	}
+
+	pub fn firmware_version(&self) -> Option<&str> {
+		self.metadata.and_then(|m| m.firmware_version.as_deref())
+	}
+
+	/// Meter form, e.g. `physical` or `virtual`
+	pub fn form(&self) -> Option<&str> {
+		self.metadata.map(|m| m.form.as_str())
+	}
+}
+
+/// Joins the series from [Client::site_meters()](crate::Client::site_meters) with the meter metadata
+///
+/// Meters are matched by type and by the serial number of the SolarEdge device they are connected to.
+pub fn enrich_site_meters<'a>(inventory: &'a SiteInventory, meters: &'a SiteMeters) -> Vec<EnrichedMeter<'a>> {
+	meters
+		.meters
+		.iter()
+		.map(|series| {
+			let meter_type = series.meter_type.as_str();
+			let metadata = inventory.meters.iter().find(|meter| {
+				meter.typ.eq_ignore_ascii_case(meter_type)
+					&& meter.connected_solaredge_device_sn == series.connected_solaredge_device_sn
+			});
+			EnrichedMeter {
+				meter_type,
+				serial_number: Some(&series.meter_serial_number),
+				connected_solaredge_device_sn: Some(&series.connected_solaredge_device_sn),
+				metadata,
+				values: &series.values,
+			}
+		})
+		.collect()
+}
+
+/// Joins the series from [Client::site_energy_details()](crate::Client::site_energy_details) or
+/// [Client::site_power_details()](crate::Client::site_power_details) with the meter metadata
+///
+/// Meters are matched by type only, the first inventory meter of the matching type is used.
+pub fn enrich_meter_details<'a>(inventory: &'a SiteInventory, details: &'a SiteMetersDetails) -> Vec<EnrichedMeter<'a>> {
+	details
+		.meters
+		.iter()
+		.map(|series| {
+			let metadata = inventory
+				.meters
+				.iter()
+				.find(|meter| meter.typ.eq_ignore_ascii_case(&series.typ));
+			EnrichedMeter {
+				meter_type: &series.typ,
+				serial_number: None,
+				connected_solaredge_device_sn: metadata.map(|m| m.connected_solaredge_device_sn.as_str()),
+				metadata,
+				values: &series.values,
+			}
+		})
+		.collect()
+}
+
+/// `consumption = production - feed-in + purchased`
+#[inline]
+pub fn consumption(production: f64, feed_in: f64, purchased: f64) -> f64 {
+	production - feed_in + purchased
+}
+
+/// `self-consumption = production - feed-in`
+#[inline]
+pub fn self_consumption(production: f64, feed_in: f64) -> f64 {
+	production - feed_in
+}
+
+/// Relationship between the meter types, see [consumption()] and [self_consumption()]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeterRelation {
+	Consumption,
+	SelfConsumption,
+}
+
+impl MeterRelation {
+	/// Terms of the relationship as `(meter type, coefficient)` pairs that sum up to zero, the dependent term is first
+	fn terms(self) -> &'static [(MeterType, f64)] {
+		match self {
+			MeterRelation::Consumption => &[
+				(MeterType::Consumption, 1.),
+				(MeterType::Production, -1.),
+				(MeterType::FeedIn, 1.),
+				(MeterType::Purchased, -1.),
+			],
+			MeterRelation::SelfConsumption => &[
+				(MeterType::SelfConsumption, 1.),
+				(MeterType::Production, -1.),
+				(MeterType::FeedIn, 1.),
+			],
+		}
+	}
+}
+
+const RELATIONS: [MeterRelation; 2] = [MeterRelation::SelfConsumption, MeterRelation::Consumption];
+
+/// Allowed discrepancy when checking the relationships, the larger of the two limits applies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+	/// In the unit of the series
+	pub absolute: f64,
+	/// Fraction of the expected value, e.g. `0.02` for 2%
+	pub relative: f64,
+}
+
+impl Tolerance {
+	pub fn accepts(&self, expected: f64, actual: f64) -> bool {
+		(expected - actual).abs() <= self.absolute.max(self.relative * expected.abs())
+	}
+}
+
+/// Sample where the meter series disagree with the [MeterRelation], see [validate_meter_balance()]
+#[derive(Clone, Debug)]
+pub struct BalanceViolation<'a> {
+	pub date: &'a DateTime,
+	pub relation: MeterRelation,
+	/// Value of the dependent meter (consumption or self-consumption) computed from the other meters
+	pub expected: f64,
+	/// Value of the dependent meter as reported
+	pub actual: f64,
+}
+
+/// Values of the recognized meter series indexed by timestamp
+struct MeterIndex<'a> {
+	series: HashMap<MeterType, (&'a SiteMeterValue, HashMap<&'a DateTime, f64>)>,
+}
+
+impl<'a> MeterIndex<'a> {
+	fn new(details: &'a SiteMetersDetails) -> Self {
+		let series = details
+			.meters
+			.iter()
+			.filter_map(|series| {
+				let typ = MeterType::from_api(&series.typ)?;
+				let values = series
+					.values
+					.iter()
+					.filter_map(|item| item.value.map(|value| (&item.date, value)))
+					.collect();
+				Some((typ, (series, values)))
+			})
+			.collect();
+		Self { series }
+	}
+
+	fn has(&self, typ: MeterType) -> bool {
+		self.series.contains_key(&typ)
+	}
+
+	fn value(&self, typ: MeterType, date: &DateTime) -> Option<f64> {
+		self.series.get(&typ)?.1.get(date).copied()
+	}
+
+	/// Value of the `target` term solved from the other terms of `relation`
+	fn solve(&self, relation: MeterRelation, target: MeterType, date: &DateTime) -> Option<f64> {
+		let mut sum = 0.;
+		let mut target_coefficient = 0.;
+		for &(typ, coefficient) in relation.terms() {
+			if typ == target {
+				target_coefficient = coefficient;
+			} else {
+				sum += coefficient * self.value(typ, date)?;
+			}
+		}
+		Some(-sum / target_coefficient)
+	}
+}
+
+/// Checks that the reported consumption and self-consumption match the production, feed-in and purchased series
+///
+/// A relationship is only checked when all its meter series are present in `details` and only for the timestamps where
+/// all of them have values. Violations are returned in the order of the dependent series.
+pub fn validate_meter_balance(details: &SiteMetersDetails, tolerance: Tolerance) -> Vec<BalanceViolation<'_>> {
+	let index = MeterIndex::new(details);
+	let mut out = vec![];
+	for relation in RELATIONS {
+		let terms = relation.terms();
+		if !terms.iter().all(|(typ, _)| index.has(*typ)) {
+			continue;
+		}
+		let dependent = terms[0].0;
+		for item in &index.series[&dependent].0.values {
+			let (Some(actual), Some(expected)) = (item.value, index.solve(relation, dependent, &item.date)) else {
+				continue;
+			};
+			if !tolerance.accepts(expected, actual) {
+				out.push(BalanceViolation {
+					date: &item.date,
+					relation,
+					expected,
+					actual,
+				});
+			}
+		}
+	}
+	out
+}
+
+/// Adds the meter series that can be computed from the other series present in `details`
+///
+/// A series is reconstructed when it's the only one missing from a [MeterRelation], e.g. consumption from production,
+/// feed-in and purchased or feed-in from production and self-consumption. Reconstructed series use the timestamps of
+/// the first present series of the relationship, the value is `None` where any of the source values is missing.
+/// Returns the types of the added series.
+pub fn reconstruct_missing_meters(details: &mut SiteMetersDetails) -> Vec<MeterType> {
+	let mut out = vec![];
+	loop {
+		let index = MeterIndex::new(details);
+		let reconstructed = RELATIONS.into_iter().find_map(|relation| {
+			let terms = relation.terms();
+			let mut missing = terms.iter().map(|(typ, _)| *typ).filter(|typ| !index.has(*typ));
+			let target = missing.next()?;
+			if missing.next().is_some() {
+				return None;
+			}
+			let (_, (template, _)) = terms.iter().find_map(|(typ, _)| index.series.get_key_value(typ))?;
+			let values = template
+				.values
+				.iter()
+				.map(|item| SiteDateValue {
+					date: item.date.to_owned(),
+					value: index.solve(relation, target, &item.date),
+				})
+				.collect();
+			let series = SiteMeterValue {
+				typ: target.as_str().to_string(),
+				values,
+			};
+			Some((target, series))
+		});
+		let Some((target, series)) = reconstructed else {
+			break;
+		};
+		out.push(target);
+		details.meters.push(series);
+	}
+	out
+}
@@ -0,0 +1,32 @@
+//! Helpers for combining and analyzing the data returned by the different endpoints
+
+#[cfg(feature = "chrono")]
+use crate::api::DateTime;
+
+pub mod accounts;
+pub mod benchmark;
+#[cfg(feature = "chrono")]
+pub mod consumption;
+pub mod devices;
+pub mod equipment;
+pub mod fleet;
+pub mod geojson;
+pub mod meters;
+#[cfg(feature = "chrono")]
+pub mod power_flow;
+pub mod sensors;
+pub mod telemetry;
+pub mod virtual_plant;
+#[cfg(feature = "chrono")]
+pub mod weather;
+
+/// Time in hours from `cur` to `next` sample, for the last sample the interval from the `prev` one is used
+#[cfg(feature = "chrono")]
+fn interval_hours(prev: Option<&DateTime>, cur: &DateTime, next: Option<&DateTime>) -> f64 {
+	let interval = match (prev, next) {
+		(_, Some(next)) => *next - *cur,
+		(Some(prev), None) => *cur - *prev,
+		(None, None) => return 0.,
+	};
+	interval.num_seconds().max(0) as f64 / 3600.
+}
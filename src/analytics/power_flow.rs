@@ -0,0 +1,99 @@
+use chrono::Duration;
+
+use super::interval_hours;
+use crate::api::DateTime;
+use crate::response::SiteCurrentPowerFlow;
+use crate::{PowerFlowStatus, PowerUnit};
+
+/// Period when the site was disconnected from the grid and running on backup, see [detect_backup_events()]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackupEvent {
+	/// Timestamp of the first sample with the grid disabled
+	pub start: DateTime,
+	/// Timestamp of the first sample after the grid came back or of the last sample if the series ends during the outage
+	pub end: DateTime,
+	pub samples: usize,
+	/// Wh discharged from the storage during the event
+	pub storage_energy: f64,
+	/// Wh consumed by the load during the event
+	pub load_energy: f64,
+	/// Lowest storage charge level during the event, %
+	pub min_charge_level: Option<f64>,
+}
+
+impl BackupEvent {
+	pub fn duration(&self) -> Duration {
+		self.end - self.start
+	}
+}
+
+/// `true` if the power flow reports the grid as disabled
+pub fn is_grid_down(flow: &SiteCurrentPowerFlow) -> bool {
+	flow
+		.grid
+		.as_ref()
+		.is_some_and(|grid| grid.status == PowerFlowStatus::Disabled)
+}
+
+/// Extracts the backup (grid outage) events from the history of the polled
+/// [Client::site_current_power_flow()](crate::Client::site_current_power_flow) results
+///
+/// `samples` are pairs of the polling time and the result, they must be sorted by time. Energies are computed by
+/// assuming that the reported power stays constant until the next sample. Power flow values without the unit are
+/// treated as kW.
+pub fn detect_backup_events(samples: &[(DateTime, SiteCurrentPowerFlow)]) -> Vec<BackupEvent> {
+	let mut out = vec![];
+	let mut current: Option<BackupEvent> = None;
+	for (i, (timestamp, flow)) in samples.iter().enumerate() {
+		if !is_grid_down(flow) {
+			if let Some(mut event) = current.take() {
+				event.end = *timestamp;
+				out.push(event);
+			}
+			continue;
+		}
+		let prev = i.checked_sub(1).map(|prev| &samples[prev].0);
+		let next = samples.get(i + 1).map(|next| &next.0);
+		let hours = interval_hours(prev, timestamp, next);
+		let factor = flow
+			.unit
+			.as_deref()
+			.and_then(PowerUnit::from_api)
+			.unwrap_or(PowerUnit::Kilowatt)
+			.factor_to(PowerUnit::Watt);
+		let event = current.get_or_insert(BackupEvent {
+			start: *timestamp,
+			end: *timestamp,
+			samples: 0,
+			storage_energy: 0.,
+			load_energy: 0.,
+			min_charge_level: None,
+		});
+		event.end = *timestamp;
+		event.samples += 1;
+		event.storage_energy += storage_discharge(flow) * factor * hours;
+		event.load_energy += flow.load.as_ref().map_or(0., |load| load.current_power) * factor * hours;
+		if let Some(charge_level) = flow.storage.as_ref().and_then(|storage| storage.charge_level) {
+			event.min_charge_level = Some(event.min_charge_level.map_or(charge_level, |min| min.min(charge_level)));
+		}
+	}
+	out.extend(current);
+	out
+}
+
+/// Storage power if it's discharging, 0 otherwise
+fn storage_discharge(flow: &SiteCurrentPowerFlow) -> f64 {
+	let Some(storage) = &flow.storage else {
+		return 0.;
+	};
+	let discharging_to_load = flow.connections.as_ref().is_some_and(|connections| {
+		connections
+			.iter()
+			.any(|c| c.from.eq_ignore_ascii_case("storage") && c.to.eq_ignore_ascii_case("load"))
+	});
+	if storage.status == PowerFlowStatus::Discharging || discharging_to_load {
+		storage.current_power
+	} else {
+		0.
+	}
+}
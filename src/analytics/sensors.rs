@@ -0,0 +1,56 @@
+use crate::response::{Gateway, GatewaySensorData, GatewaySensors, Sensor, SensorTelemetry};
+
+/// Gateway with its sensors and their telemetry, see [sensor_topology()]
+#[derive(Clone, Debug)]
+pub struct GatewayTopology<'a> {
+	/// Gateway name as reported in the `connectedTo` fields
+	pub name: &'a str,
+	/// `None` if the gateway was not found in the passed inventory gateways
+	pub gateway: Option<&'a Gateway>,
+	pub sensors: &'a [Sensor],
+	pub telemetries: &'a [SensorTelemetry],
+}
+
+/// Joins the results of [Client::equipment_sensors()](crate::Client::equipment_sensors) and
+/// [Client::site_sensor_data()](crate::Client::site_sensor_data) by the gateway name
+///
+/// `gateways` from [Client::site_inventory()](crate::Client::site_inventory) are optional and only add the gateway
+/// metadata. Names are compared case-insensitively ignoring the surrounding spaces. The result follows the order of
+/// `sensors`, the gateways that only have telemetry come last.
+pub fn sensor_topology<'a>(
+	gateways: &'a [Gateway],
+	sensors: &'a [GatewaySensors],
+	data: &'a [GatewaySensorData],
+) -> Vec<GatewayTopology<'a>> {
+	fn same_name(a: &str, b: &str) -> bool {
+		a.trim().eq_ignore_ascii_case(b.trim())
+	}
+
+	let gateway = |name: &str| gateways.iter().find(|gateway| same_name(&gateway.name, name));
+	let mut out = sensors
+		.iter()
+		.map(|summary| GatewayTopology {
+			name: &summary.connected_to,
+			gateway: gateway(&summary.connected_to),
+			sensors: &summary.sensors,
+			telemetries: data
+				.iter()
+				.find(|data| same_name(&data.connected_to, &summary.connected_to))
+				.map_or(&[], |data| &data.telemetries),
+		})
+		.collect::<Vec<_>>();
+	for data in data {
+		if !sensors
+			.iter()
+			.any(|summary| same_name(&summary.connected_to, &data.connected_to))
+		{
+			out.push(GatewayTopology {
+				name: &data.connected_to,
+				gateway: gateway(&data.connected_to),
+				sensors: &[],
+				telemetries: &data.telemetries,
+			});
+		}
+	}
+	out
+}
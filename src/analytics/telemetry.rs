@@ -0,0 +1,156 @@
+#[cfg(feature = "chrono")]
+use super::interval_hours;
+#[cfg(feature = "chrono")]
+use crate::api::DateTime;
+use crate::response::EquipmentTelemetry;
+use crate::InverterMode;
+
+/// Continuous period during which the inverter output was limited, see [detect_curtailment()]
+#[cfg(feature = "chrono")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurtailmentPeriod {
+	/// Timestamp of the first limited sample
+	pub start: DateTime,
+	/// Timestamp of the first sample after the period or of the last limited sample if the series ends limited
+	pub end: DateTime,
+	/// Lowest power limit within the period, %
+	pub min_power_limit: f64,
+	/// Number of the limited samples
+	pub samples: usize,
+	/// Wh, rough estimate assuming that the inverter was producing at its limit, see [detect_curtailment()]
+	pub estimated_curtailed_energy: f64,
+}
+
+/// `true` if the inverter output is limited in this sample
+pub fn is_curtailed(telemetry: &EquipmentTelemetry) -> bool {
+	telemetry.power_limit < 100. || matches!(telemetry.inverter_mode, InverterMode::ProductionLimit)
+}
+
+/// Finds the periods where the power limit is below 100% or the inverter reports [InverterMode::ProductionLimit]
+///
+/// `telemetries` must be sorted by date, see [crate::series::sort_by_timestamp()]. The curtailed energy is estimated
+/// for each limited sample as the difference between the power the inverter would produce without the limit
+/// (`total_active_power * 100 / power_limit`) and the actual power, multiplied by the time until the next sample. The
+/// samples with a 0% limit or with [InverterMode::ProductionLimit] but no limit percentage contribute no energy
+/// because the unconstrained power can't be estimated for them.
+#[cfg(feature = "chrono")]
+pub fn detect_curtailment(telemetries: &[EquipmentTelemetry]) -> Vec<CurtailmentPeriod> {
+	let mut out = vec![];
+	let mut current: Option<CurtailmentPeriod> = None;
+	for (i, telemetry) in telemetries.iter().enumerate() {
+		if !is_curtailed(telemetry) {
+			if let Some(mut period) = current.take() {
+				period.end = telemetry.date;
+				out.push(period);
+			}
+			continue;
+		}
+		let prev = i.checked_sub(1).map(|prev| &telemetries[prev].date);
+		let next = telemetries.get(i + 1).map(|next| &next.date);
+		let energy = estimated_curtailed_power(telemetry) * interval_hours(prev, &telemetry.date, next);
+		let period = current.get_or_insert(CurtailmentPeriod {
+			start: telemetry.date,
+			end: telemetry.date,
+			min_power_limit: telemetry.power_limit,
+			samples: 0,
+			estimated_curtailed_energy: 0.,
+		});
+		period.end = telemetry.date;
+		period.min_power_limit = period.min_power_limit.min(telemetry.power_limit);
+		period.samples += 1;
+		period.estimated_curtailed_energy += energy;
+	}
+	out.extend(current);
+	out
+}
+
+#[cfg(feature = "chrono")]
+fn estimated_curtailed_power(telemetry: &EquipmentTelemetry) -> f64 {
+	if telemetry.power_limit > 0. && telemetry.power_limit < 100. {
+		telemetry.total_active_power * (100. / telemetry.power_limit - 1.)
+	} else {
+		0.
+	}
+}
+
+/// Limits used by [scan_anomalies()]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnomalyThresholds {
+	/// Ground fault (isolation) resistance below this value is reported, kOhm
+	pub min_ground_fault_resistance: f64,
+	/// Celsius
+	pub max_temperature: f64,
+	/// Celsius
+	pub min_temperature: f64,
+	/// Largest allowed temperature change between the consecutive samples, Celsius
+	pub max_temperature_step: f64,
+}
+
+impl Default for AnomalyThresholds {
+	fn default() -> Self {
+		Self {
+			min_ground_fault_resistance: 350.,
+			max_temperature: 75.,
+			min_temperature: -25.,
+			max_temperature_step: 15.,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnomalyKind {
+	LowGroundFaultResistance {
+		resistance: f64,
+	},
+	HighTemperature {
+		temperature: f64,
+	},
+	LowTemperature {
+		temperature: f64,
+	},
+	/// Temperature changed by more than [AnomalyThresholds::max_temperature_step] since the previous sample
+	TemperatureJump {
+		previous: f64,
+		temperature: f64,
+	},
+}
+
+/// Anomaly found by [scan_anomalies()] along with the sample where it was detected
+#[derive(Clone, Copy, Debug)]
+pub struct TelemetryAnomaly<'a> {
+	pub telemetry: &'a EquipmentTelemetry,
+	pub kind: AnomalyKind,
+}
+
+/// Scans the telemetry for the low ground fault resistance and abnormal temperatures
+///
+/// `telemetries` should be sorted by date for the temperature jumps to be meaningful, one sample can produce multiple
+/// anomalies.
+pub fn scan_anomalies<'a>(telemetries: &'a [EquipmentTelemetry], thresholds: &AnomalyThresholds) -> Vec<TelemetryAnomaly<'a>> {
+	let mut out = vec![];
+	let mut previous: Option<&EquipmentTelemetry> = None;
+	for telemetry in telemetries {
+		let mut push = |kind| out.push(TelemetryAnomaly { telemetry, kind });
+		if let Some(resistance) = telemetry.ground_fault_resistance {
+			if resistance < thresholds.min_ground_fault_resistance {
+				push(AnomalyKind::LowGroundFaultResistance { resistance });
+			}
+		}
+		let temperature = telemetry.temperature;
+		if temperature > thresholds.max_temperature {
+			push(AnomalyKind::HighTemperature { temperature });
+		} else if temperature < thresholds.min_temperature {
+			push(AnomalyKind::LowTemperature { temperature });
+		}
+		if let Some(previous) = previous {
+			if (temperature - previous.temperature).abs() > thresholds.max_temperature_step {
+				push(AnomalyKind::TemperatureJump {
+					previous: previous.temperature,
+					temperature,
+				});
+			}
+		}
+		previous = Some(telemetry);
+	}
+	out
+}
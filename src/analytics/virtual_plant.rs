@@ -0,0 +1,204 @@
+//! Aggregation of several sites into a single virtual plant, e.g. the rooftops of an energy community
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::api::DateTime;
+use crate::response::{SiteCurrentPowerFlow, SiteDateValue, SiteMeterValue, SiteMetersDetails, SiteStorageData};
+use crate::{PowerFlowStatus, PowerUnit, SiteId};
+
+/// Selected sites treated as one plant
+///
+/// The combining functions take the responses of any sites along with their ids and only use the ones of the member sites,
+/// so the results of the bulk or fleet-wide requests can be passed as is. The values are summed by the exact timestamp,
+/// so the member sites should be in the same time zone and requested with the same time unit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VirtualPlant {
+	site_ids: BTreeSet<SiteId>,
+}
+
+impl VirtualPlant {
+	pub fn new(site_ids: impl IntoIterator<Item = impl Into<SiteId>>) -> Self {
+		Self {
+			site_ids: site_ids.into_iter().map(Into::into).collect(),
+		}
+	}
+
+	/// Adds the site `site_id` to the plant, returns `false` if it's already a member
+	pub fn add(&mut self, site_id: impl Into<SiteId>) -> bool {
+		self.site_ids.insert(site_id.into())
+	}
+
+	/// Removes the site `site_id` from the plant, returns `false` if it's not a member
+	pub fn remove(&mut self, site_id: impl Into<SiteId>) -> bool {
+		self.site_ids.remove(&site_id.into())
+	}
+
+	#[inline]
+	pub fn contains(&self, site_id: impl Into<SiteId>) -> bool {
+		self.site_ids.contains(&site_id.into())
+	}
+
+	/// Member sites sorted by id
+	#[inline]
+	pub fn site_ids(&self) -> impl Iterator<Item = SiteId> + '_ {
+		self.site_ids.iter().copied()
+	}
+
+	/// Combines the [Client::site_power_details()](crate::Client::site_power_details) or
+	/// [Client::site_energy_details()](crate::Client::site_energy_details) results of the member sites
+	///
+	/// The values of the same meter type and timestamp are summed, the missing values are skipped and the value is `None`
+	/// only when it's missing for all sites. The time unit and the unit are taken from the first member site, `None` if no
+	/// member site is included in `details`.
+	pub fn combine_meters<'a>(
+		&self,
+		details: impl IntoIterator<Item = (impl Into<SiteId>, &'a SiteMetersDetails)>,
+	) -> Option<SiteMetersDetails> {
+		let mut out: Option<SiteMetersDetails> = None;
+		let mut meters = BTreeMap::<&str, BTreeMap<&DateTime, Option<f64>>>::new();
+		for (site_id, details) in details {
+			if !self.contains(site_id) {
+				continue;
+			}
+			out.get_or_insert_with(|| SiteMetersDetails {
+				time_unit: details.time_unit,
+				unit: details.unit.clone(),
+				meters: vec![],
+			});
+			for meter in &details.meters {
+				let values = meters.entry(&meter.typ).or_default();
+				for item in &meter.values {
+					add_value(values.entry(&item.date).or_default(), item.value);
+				}
+			}
+		}
+		out.map(|mut out| {
+			out.meters = meters
+				.into_iter()
+				.map(|(typ, values)| SiteMeterValue {
+					typ: typ.to_string(),
+					values: to_series(values),
+				})
+				.collect();
+			out
+		})
+	}
+
+	/// Combines the [Client::site_storage_data()](crate::Client::site_storage_data) results of the member sites into the
+	/// series of the total battery power in W
+	pub fn combine_storage_power<'a>(
+		&self,
+		storage: impl IntoIterator<Item = (impl Into<SiteId>, &'a SiteStorageData)>,
+	) -> Vec<SiteDateValue> {
+		let mut values = BTreeMap::<&DateTime, Option<f64>>::new();
+		for (site_id, storage) in storage {
+			if !self.contains(site_id) {
+				continue;
+			}
+			for telemetry in storage.batteries.iter().flat_map(|battery| &battery.telemetries) {
+				add_value(
+					values.entry(&telemetry.timestamp).or_default(),
+					Some(f64::from(telemetry.power)),
+				);
+			}
+		}
+		to_series(values)
+	}
+
+	/// Combines the [Client::site_current_power_flow()](crate::Client::site_current_power_flow) results of the member sites
+	///
+	/// The flows of the same site are not summed, the last one is used.
+	pub fn combine_power_flows<'a>(
+		&self,
+		flows: impl IntoIterator<Item = (impl Into<SiteId>, &'a SiteCurrentPowerFlow)>,
+	) -> PlantPowerFlow {
+		let flows = flows
+			.into_iter()
+			.map(|(site_id, flow)| (site_id.into(), flow))
+			.filter(|(site_id, _)| self.site_ids.contains(site_id))
+			.collect::<HashMap<_, _>>();
+		let mut out = PlantPowerFlow::default();
+		let mut charge_levels = vec![];
+		for flow in flows.values() {
+			let factor = flow
+				.unit
+				.as_deref()
+				.and_then(PowerUnit::from_api)
+				.unwrap_or(PowerUnit::Kilowatt)
+				.factor_to(PowerUnit::Watt);
+			out.sites += 1;
+			out.pv += flow.pv.as_ref().map_or(0., |pv| pv.current_power) * factor;
+			out.load += flow.load.as_ref().map_or(0., |load| load.current_power) * factor;
+			out.grid += flow.grid.as_ref().map_or(0., |grid| grid.current_power) * factor * grid_sign(flow);
+			if let Some(storage) = &flow.storage {
+				out.storage += storage.current_power * factor * storage_sign(flow);
+				charge_levels.extend(storage.charge_level);
+			}
+		}
+		if !charge_levels.is_empty() {
+			out.charge_level = Some(charge_levels.iter().sum::<f64>() / charge_levels.len() as f64);
+		}
+		out
+	}
+}
+
+/// Current power flow of the [VirtualPlant], the powers are in W
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlantPowerFlow {
+	/// Number of the member sites included
+	pub sites: usize,
+	pub pv: f64,
+	pub load: f64,
+	/// Positive when importing from the grid, negative when exporting
+	pub grid: f64,
+	/// Positive when discharging, negative when charging
+	pub storage: f64,
+	/// Mean charge level of the sites reporting the storage, %
+	pub charge_level: Option<f64>,
+}
+
+fn add_value(sum: &mut Option<f64>, value: Option<f64>) {
+	if let Some(value) = value {
+		*sum = Some(sum.unwrap_or(0.) + value);
+	}
+}
+
+fn to_series(values: BTreeMap<&DateTime, Option<f64>>) -> Vec<SiteDateValue> {
+	values
+		.into_iter()
+		.map(|(date, value)| SiteDateValue {
+			date: date.to_owned(),
+			value,
+		})
+		.collect()
+}
+
+fn has_connection(flow: &SiteCurrentPowerFlow, from: &str, to: &str) -> bool {
+	flow.connections.as_ref().is_some_and(|connections| {
+		connections
+			.iter()
+			.any(|c| c.from.eq_ignore_ascii_case(from) && c.to.eq_ignore_ascii_case(to))
+	})
+}
+
+/// `-1` if the site exports to the grid
+fn grid_sign(flow: &SiteCurrentPowerFlow) -> f64 {
+	if has_connection(flow, "load", "grid") || has_connection(flow, "pv", "grid") {
+		-1.
+	} else {
+		1.
+	}
+}
+
+/// `-1` if the storage is charging
+fn storage_sign(flow: &SiteCurrentPowerFlow) -> f64 {
+	let charging = flow
+		.storage
+		.as_ref()
+		.is_some_and(|storage| storage.status == PowerFlowStatus::Charging);
+	if charging || has_connection(flow, "pv", "storage") || has_connection(flow, "grid", "storage") {
+		-1.
+	} else {
+		1.
+	}
+}
@@ -0,0 +1,130 @@
+//! Integration point for the weather data explaining the production changes, e.g. from Open-Meteo
+
+use super::fleet::median;
+use crate::api::DateTime;
+use crate::response::{Location, SiteDateValue};
+use crate::series::Timestamped;
+use crate::DateTimeRange;
+
+/// Error of the [WeatherProvider]
+pub type WeatherError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Weather during the interval from `start` (inclusive) to `end` (exclusive)
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeatherSample {
+	pub start: DateTime,
+	pub end: DateTime,
+	/// Air temperature, °C
+	pub temperature: Option<f64>,
+	/// Mean global horizontal irradiance, W/m²
+	pub irradiance: Option<f64>,
+}
+
+impl WeatherSample {
+	/// `true` if the `timestamp` is inside the interval of the sample
+	#[inline]
+	pub fn contains(&self, timestamp: &DateTime) -> bool {
+		self.start <= *timestamp && *timestamp < self.end
+	}
+}
+
+/// Source of the weather data for the site location, implement it for the weather API of choice
+///
+/// The timestamps of the SolarEdge API are in the site local time, so the provider must return the samples in the time
+/// zone of the `location`, see [Location::time_zone], e.g. with the `timezone` parameter of Open-Meteo.
+///
+/// # Example
+/// ```
+/// use solaredge::analytics::weather::{WeatherError, WeatherProvider, WeatherSample};
+/// use solaredge::response::Location;
+/// use solaredge::DateTimeRange;
+///
+/// struct OpenMeteo;
+///
+/// #[async_trait::async_trait(?Send)]
+/// impl WeatherProvider for OpenMeteo {
+///    async fn weather(&self, location: &Location, range: &DateTimeRange) -> Result<Vec<WeatherSample>, WeatherError> {
+///       let (lat, lng) = location.coordinates().ok_or("Site has no coordinates")?;
+///       // request https://api.open-meteo.com/v1/forecast?latitude={lat}&longitude={lng}&timezone={location.time_zone}
+///       // &hourly=temperature_2m,shortwave_radiation and convert the hourly values to the samples
+///       Ok(vec![])
+///    }
+/// }
+/// ```
+#[async_trait::async_trait(?Send)]
+pub trait WeatherProvider {
+	/// Weather samples at the `location` covering the `range`, sorted by time and not overlapping
+	async fn weather(&self, location: &Location, range: &DateTimeRange) -> Result<Vec<WeatherSample>, WeatherError>;
+}
+
+/// Item of the series along with the weather during its timestamp, see [join_weather()]
+#[derive(Debug)]
+pub struct WithWeather<'a, T> {
+	pub item: &'a T,
+	/// `None` if no weather sample covers the item's timestamp
+	pub weather: Option<&'a WeatherSample>,
+}
+
+impl<T> Clone for WithWeather<'_, T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T> Copy for WithWeather<'_, T> {}
+
+impl WithWeather<'_, SiteDateValue> {
+	/// Production per 1 W/m² of the irradiance, `None` if either of them is missing or the irradiance is below
+	/// `min_irradiance`, e.g. at night
+	pub fn yield_per_irradiance(&self, min_irradiance: f64) -> Option<f64> {
+		let irradiance = self.weather?.irradiance.filter(|&irradiance| irradiance >= min_irradiance)?;
+		Some(self.item.value? / irradiance)
+	}
+}
+
+/// Pairs every item of the `series` with the weather sample covering its timestamp
+///
+/// `weather` must be sorted by time and not overlapping as returned by the [WeatherProvider], `series` can be in any order.
+pub fn join_weather<'a, T: Timestamped>(series: &'a [T], weather: &'a [WeatherSample]) -> Vec<WithWeather<'a, T>> {
+	series
+		.iter()
+		.map(|item| {
+			let timestamp = item.timestamp();
+			let after = weather.partition_point(|sample| sample.start <= *timestamp);
+			let weather = after
+				.checked_sub(1)
+				.map(|i| &weather[i])
+				.filter(|sample| sample.contains(timestamp));
+			WithWeather { item, weather }
+		})
+		.collect()
+}
+
+/// Finds the production dips not explained by the weather
+///
+/// The dip is the item of the joined production series whose [yield per irradiance](WithWeather::yield_per_irradiance)
+/// is below `ratio` of the median over the whole series, e.g. `0.5` for less than a half of the usual yield in the same
+/// light. Cloudy periods reduce both the production and the irradiance, so they are not reported, while the shading,
+/// the soiling or the equipment faults are. Items with the irradiance below `min_irradiance` are skipped.
+pub fn unexplained_dips<'a>(
+	joined: &[WithWeather<'a, SiteDateValue>],
+	min_irradiance: f64,
+	ratio: f64,
+) -> Vec<WithWeather<'a, SiteDateValue>> {
+	let yields = joined
+		.iter()
+		.filter_map(|item| item.yield_per_irradiance(min_irradiance))
+		.collect();
+	let Some(median) = median(yields) else {
+		return vec![];
+	};
+	joined
+		.iter()
+		.filter(|item| {
+			item
+				.yield_per_irradiance(min_irradiance)
+				.is_some_and(|item_yield| item_yield < median * ratio)
+		})
+		.copied()
+		.collect()
+}
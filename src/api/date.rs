@@ -0,0 +1,160 @@
+//! Lightweight date and date-time types used instead of `chrono` when the `chrono` feature is disabled
+//!
+//! They only validate and carry the API formatted value, no date math is available. Because the format
+//! is fixed-width the ordering of the values matches the chronological one.
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+/// Error returned when the string is not a valid date or date-time
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+	input: String,
+}
+
+impl ParseError {
+	fn new(input: &str) -> Self {
+		Self {
+			input: input.to_string(),
+		}
+	}
+}
+
+impl Display for ParseError {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		write!(f, "invalid date or date-time: {}", self.input)
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+/// Date in `YYYY-MM-DD` format
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date(String);
+
+impl Date {
+	/// Validates the `YYYY-MM-DD` string, the day is checked against the length of the month including the leap years
+	pub fn parse(s: &str) -> Result<Self, ParseError> {
+		if is_valid_date(s) {
+			Ok(Self(s.to_string()))
+		} else {
+			Err(ParseError::new(s))
+		}
+	}
+
+	/// Value in `YYYY-MM-DD` format
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl FromStr for Date {
+	type Err = ParseError;
+
+	#[inline]
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::parse(s)
+	}
+}
+
+impl Display for Date {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(&self.0)
+	}
+}
+
+/// Date and time in `YYYY-MM-DD HH:MM:SS` format
+///
+/// Date-only input (`YYYY-MM-DD`) is accepted too and is normalized to midnight.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime(String);
+
+impl DateTime {
+	/// Validates the `YYYY-MM-DD HH:MM:SS` string, the `YYYY-MM-DD` date is accepted too and is normalized to midnight
+	///
+	/// The time is limited to `23:59:59`, the leap seconds and `24:00:00` are rejected.
+	pub fn parse(s: &str) -> Result<Self, ParseError> {
+		if s.len() == 10 && is_valid_date(s) {
+			Ok(Self(format!("{s} 00:00:00")))
+		} else if s.len() == 19
+			&& s.as_bytes()[10] == b' '
+			&& s.get(..10).is_some_and(is_valid_date)
+			&& s.get(11..).is_some_and(is_valid_time)
+		{
+			Ok(Self(s.to_string()))
+		} else {
+			Err(ParseError::new(s))
+		}
+	}
+
+	/// Value in `YYYY-MM-DD HH:MM:SS` format
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+
+	/// Date part of the value
+	pub fn date(&self) -> Date {
+		Date(self.0[..10].to_string())
+	}
+}
+
+impl FromStr for DateTime {
+	type Err = ParseError;
+
+	#[inline]
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::parse(s)
+	}
+}
+
+impl Display for DateTime {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(&self.0)
+	}
+}
+
+/// Parses fixed-width, `sep`-separated groups of digits, e.g. `2021-08-10` or `13:05:00`
+fn parse_groups<const N: usize>(s: &str, widths: [usize; N], sep: u8) -> Option<[u32; N]> {
+	let bytes = s.as_bytes();
+	if bytes.len() != widths.iter().sum::<usize>() + N - 1 {
+		return None;
+	}
+	let mut out = [0; N];
+	let mut pos = 0;
+	for (i, width) in widths.into_iter().enumerate() {
+		if i > 0 {
+			if bytes[pos] != sep {
+				return None;
+			}
+			pos += 1;
+		}
+		let group = &bytes[pos..pos + width];
+		if !group.iter().all(u8::is_ascii_digit) {
+			return None;
+		}
+		out[i] = group.iter().fold(0, |acc, d| acc * 10 + u32::from(d - b'0'));
+		pos += width;
+	}
+	Some(out)
+}
+
+fn is_valid_date(s: &str) -> bool {
+	let Some([year, month, day]) = parse_groups(s, [4, 2, 2], b'-') else {
+		return false;
+	};
+	let is_leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+	let days_in_month = match month {
+		1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+		4 | 6 | 9 | 11 => 30,
+		2 if is_leap => 29,
+		2 => 28,
+		_ => return false,
+	};
+	(1..=days_in_month).contains(&day)
+}
+
+fn is_valid_time(s: &str) -> bool {
+	matches!(parse_groups(s, [2, 2, 2], b':'), Some([hour, minute, second]) if hour < 24 && minute < 60 && second < 60)
+}
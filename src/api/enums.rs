@@ -2,6 +2,10 @@ use std::fmt::{Display, Formatter, Result as FmtResult};
 
 use serde::{Deserialize, Serialize};
 use serde_repr::Deserialize_repr;
+#[cfg(feature = "serialize")]
+use serde_repr::Serialize_repr;
+
+use crate::limits;
 
 #[derive(Copy, Clone, Debug, Serialize)]
 pub enum SortOrder {
@@ -57,7 +61,36 @@ impl Display for SiteSortBy {
 	}
 }
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize)]
+pub enum AccountSortBy {
+	Name,
+	Country,
+	City,
+	Address,
+	Zip,
+	Fax,
+	Phone,
+	Notes,
+}
+
+impl Display for AccountSortBy {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		let s = match self {
+			AccountSortBy::Name => "Name",
+			AccountSortBy::Country => "Country",
+			AccountSortBy::City => "City",
+			AccountSortBy::Address => "Address",
+			AccountSortBy::Zip => "Zip",
+			AccountSortBy::Fax => "Fax",
+			AccountSortBy::Phone => "Phone",
+			AccountSortBy::Notes => "Notes",
+		};
+		f.write_str(s)
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
 pub enum SiteStatus {
 	Active,
 	Pending,
@@ -77,7 +110,7 @@ impl Display for SiteStatus {
 	}
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum TimeUnit {
 	#[serde(rename = "QUARTER_OF_AN_HOUR")]
 	QuarterOfAnHour,
@@ -93,7 +126,19 @@ pub enum TimeUnit {
 	Year,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+impl TimeUnit {
+	/// Longest period in months that the site energy endpoints accept with this time unit, `None` if unlimited
+	pub fn max_energy_period_months(self) -> Option<u32> {
+		match self {
+			TimeUnit::QuarterOfAnHour | TimeUnit::Hour => Some(limits::ENERGY_MAX_MONTHS_INTRADAY),
+			TimeUnit::Day => Some(limits::ENERGY_MAX_MONTHS_DAILY),
+			TimeUnit::Week | TimeUnit::Month | TimeUnit::Year => None,
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
 pub enum MeterType {
 	Production,
 	Consumption,
@@ -102,20 +147,39 @@ pub enum MeterType {
 	Purchased,
 }
 
-impl Display for MeterType {
-	fn fmt(&self, f: &mut Formatter) -> FmtResult {
-		let s = match self {
+impl MeterType {
+	/// Parses the meter type as reported in the `type` field of the API responses, case-insensitive
+	pub fn from_api(s: &str) -> Option<Self> {
+		[
+			MeterType::Production,
+			MeterType::Consumption,
+			MeterType::SelfConsumption,
+			MeterType::FeedIn,
+			MeterType::Purchased,
+		]
+		.into_iter()
+		.find(|typ| typ.as_str().eq_ignore_ascii_case(s))
+	}
+
+	pub fn as_str(self) -> &'static str {
+		match self {
 			MeterType::Production => "Production",
 			MeterType::Consumption => "Consumption",
 			MeterType::SelfConsumption => "SelfConsumption",
 			MeterType::FeedIn => "FeedIn",
 			MeterType::Purchased => "Purchased",
-		};
-		f.write_str(s)
+		}
 	}
 }
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+impl Display for MeterType {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(self.as_str())
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
 pub enum InverterMode {
 	#[serde(rename = "OFF")]
 	Off,
@@ -166,7 +230,20 @@ pub enum InverterMode {
 	Sleeping,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize_repr)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum PowerFlowStatus {
+	Active,
+	Idle,
+	Disabled,
+	/// Storage only
+	Charging,
+	/// Storage only
+	Discharging,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize_repr)]
+#[cfg_attr(feature = "serialize", derive(Serialize_repr))]
 #[repr(u8)]
 pub enum OperationMode {
 	OnGrid = 0,
@@ -179,3 +256,53 @@ pub enum SystemUnits {
 	Metrics,
 	Imperial,
 }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PowerUnit {
+	Watt,
+	Kilowatt,
+	Megawatt,
+	Gigawatt,
+}
+
+impl PowerUnit {
+	/// Parses the unit as reported in the `unit` field of the API responses, case-sensitive so that `mW` is not taken for
+	/// `MW`, the energy units (e.g. `kWh`) are parsed as the power unit of the same scale
+	pub fn from_api(s: &str) -> Option<Self> {
+		match s {
+			"W" | "Wh" => Some(PowerUnit::Watt),
+			"kW" | "kWh" => Some(PowerUnit::Kilowatt),
+			"MW" | "MWh" => Some(PowerUnit::Megawatt),
+			"GW" | "GWh" => Some(PowerUnit::Gigawatt),
+			_ => None,
+		}
+	}
+
+	/// Number of watts in one unit
+	pub fn watts(self) -> f64 {
+		match self {
+			PowerUnit::Watt => 1.,
+			PowerUnit::Kilowatt => 1_000.,
+			PowerUnit::Megawatt => 1_000_000.,
+			PowerUnit::Gigawatt => 1_000_000_000.,
+		}
+	}
+
+	/// Factor to multiply the value in this unit by to get the value in `target` unit
+	#[inline]
+	pub fn factor_to(self, target: PowerUnit) -> f64 {
+		self.watts() / target.watts()
+	}
+}
+
+impl Display for PowerUnit {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		let s = match self {
+			PowerUnit::Watt => "W",
+			PowerUnit::Kilowatt => "kW",
+			PowerUnit::Megawatt => "MW",
+			PowerUnit::Gigawatt => "GW",
+		};
+		f.write_str(s)
+	}
+}
@@ -0,0 +1,159 @@
+//! Identifiers of the sites and the equipment, distinct types prevent mixing them up in the function arguments
+
+use std::borrow::Borrow;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Deref;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::number_or_str;
+
+/// Id of the site
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct SiteId(pub u64);
+
+/// Accepts both the number and the numeric string, e.g. `"12345"`
+impl<'d> Deserialize<'d> for SiteId {
+	fn deserialize<D: Deserializer<'d>>(d: D) -> Result<Self, D::Error> {
+		number_or_str(d).map(Self)
+	}
+}
+
+impl From<u64> for SiteId {
+	#[inline]
+	fn from(id: u64) -> Self {
+		Self(id)
+	}
+}
+
+impl From<SiteId> for u64 {
+	#[inline]
+	fn from(id: SiteId) -> Self {
+		id.0
+	}
+}
+
+impl PartialEq<u64> for SiteId {
+	#[inline]
+	fn eq(&self, other: &u64) -> bool {
+		self.0 == *other
+	}
+}
+
+impl PartialEq<SiteId> for u64 {
+	#[inline]
+	fn eq(&self, other: &SiteId) -> bool {
+		*self == other.0
+	}
+}
+
+impl Display for SiteId {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		Display::fmt(&self.0, f)
+	}
+}
+
+/// Serial number of an inverter, battery, gateway or other equipment
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SerialNumber(pub String);
+
+impl SerialNumber {
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl From<String> for SerialNumber {
+	#[inline]
+	fn from(sn: String) -> Self {
+		Self(sn)
+	}
+}
+
+impl From<&str> for SerialNumber {
+	#[inline]
+	fn from(sn: &str) -> Self {
+		Self(sn.to_string())
+	}
+}
+
+impl From<&String> for SerialNumber {
+	#[inline]
+	fn from(sn: &String) -> Self {
+		Self(sn.clone())
+	}
+}
+
+impl From<&SerialNumber> for SerialNumber {
+	#[inline]
+	fn from(sn: &SerialNumber) -> Self {
+		sn.clone()
+	}
+}
+
+impl From<SerialNumber> for String {
+	#[inline]
+	fn from(sn: SerialNumber) -> Self {
+		sn.0
+	}
+}
+
+impl Deref for SerialNumber {
+	type Target = str;
+
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl AsRef<str> for SerialNumber {
+	#[inline]
+	fn as_ref(&self) -> &str {
+		&self.0
+	}
+}
+
+impl Borrow<str> for SerialNumber {
+	#[inline]
+	fn borrow(&self) -> &str {
+		&self.0
+	}
+}
+
+impl PartialEq<str> for SerialNumber {
+	#[inline]
+	fn eq(&self, other: &str) -> bool {
+		self.0 == other
+	}
+}
+
+impl PartialEq<&str> for SerialNumber {
+	#[inline]
+	fn eq(&self, other: &&str) -> bool {
+		self.0 == *other
+	}
+}
+
+impl PartialEq<SerialNumber> for str {
+	#[inline]
+	fn eq(&self, other: &SerialNumber) -> bool {
+		self == other.0
+	}
+}
+
+impl PartialEq<SerialNumber> for &str {
+	#[inline]
+	fn eq(&self, other: &SerialNumber) -> bool {
+		*self == other.0
+	}
+}
+
+impl Display for SerialNumber {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(&self.0)
+	}
+}
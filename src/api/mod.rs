@@ -1,10 +1,20 @@
-use std::fmt::{Display, Write};
+use std::fmt::{self, Display, Write};
+use std::marker::PhantomData;
+use std::str::FromStr;
 
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime, ParseResult};
-use serde::de::Error;
+#[cfg(feature = "chrono")]
+pub use chrono::{NaiveDate as Date, NaiveDateTime as DateTime};
+#[cfg(feature = "chrono")]
+use chrono::{NaiveTime, ParseResult};
+#[cfg(not(feature = "chrono"))]
+pub use date::{Date, DateTime, ParseError as DateParseError};
+use serde::de::{Error, IntoDeserializer, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+#[cfg(not(feature = "chrono"))]
+pub mod date;
 pub mod enums;
+pub mod ids;
 pub mod request;
 pub mod response;
 
@@ -32,31 +42,64 @@ fn serialize_comma_slice_opt<T: Display, S: Serializer>(slice: &Option<&[T]>, se
 	}
 }
 
-fn str_to_datetime(s: &str) -> ParseResult<NaiveDateTime> {
-	match NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+#[cfg(feature = "chrono")]
+fn str_to_datetime(s: &str) -> ParseResult<DateTime> {
+	match DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
 		Ok(d) => Ok(d),
 		Err(_) => {
-			let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")?;
-			Ok(NaiveDateTime::new(
-				date,
-				NaiveTime::from_hms_opt(0, 0, 0).expect("Static time"),
-			))
+			let date = Date::parse_from_str(s, "%Y-%m-%d")?;
+			Ok(DateTime::new(date, NaiveTime::from_hms_opt(0, 0, 0).expect("Static time")))
 		}
 	}
 }
 
-fn str_to_date(s: &str) -> ParseResult<NaiveDate> {
-	NaiveDate::parse_from_str(s, "%Y-%m-%d")
+#[cfg(not(feature = "chrono"))]
+#[inline]
+fn str_to_datetime(s: &str) -> Result<DateTime, DateParseError> {
+	DateTime::parse(s)
+}
+
+#[cfg(feature = "chrono")]
+fn str_to_date(s: &str) -> ParseResult<Date> {
+	Date::parse_from_str(s, "%Y-%m-%d")
+}
+
+#[cfg(not(feature = "chrono"))]
+#[inline]
+fn str_to_date(s: &str) -> Result<Date, DateParseError> {
+	Date::parse(s)
+}
+
+#[cfg(feature = "chrono")]
+fn datetime_to_string(d: &DateTime) -> String {
+	d.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[cfg(not(feature = "chrono"))]
+#[inline]
+fn datetime_to_string(d: &DateTime) -> String {
+	d.to_string()
+}
+
+#[cfg(feature = "chrono")]
+fn date_to_string(d: &Date) -> String {
+	d.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(not(feature = "chrono"))]
+#[inline]
+fn date_to_string(d: &Date) -> String {
+	d.to_string()
 }
 
 struct DateTimeSerde;
 
 impl DateTimeSerde {
-	fn serialize<S: Serializer>(d: &NaiveDateTime, ser: S) -> Result<S::Ok, S::Error> {
-		d.format("%Y-%m-%d %H:%M:%S").to_string().serialize(ser)
+	fn serialize<S: Serializer>(d: &DateTime, ser: S) -> Result<S::Ok, S::Error> {
+		datetime_to_string(d).serialize(ser)
 	}
 
-	fn deserialize<'d, D: Deserializer<'d>>(d: D) -> Result<NaiveDateTime, D::Error> {
+	fn deserialize<'d, D: Deserializer<'d>>(d: D) -> Result<DateTime, D::Error> {
 		let s = String::deserialize(d)?;
 		str_to_datetime(&s).map_err(|e| Error::custom(format!("DateTime parse error, input: {}, error: {}", s, e)))
 	}
@@ -66,8 +109,8 @@ struct DateTimeSerdeOpt;
 
 impl DateTimeSerdeOpt {
 	#[inline]
-	#[allow(unused)]
-	fn serialize<S: Serializer>(d: &Option<NaiveDateTime>, ser: S) -> Result<S::Ok, S::Error> {
+	#[cfg_attr(not(feature = "serialize"), allow(unused))]
+	fn serialize<S: Serializer>(d: &Option<DateTime>, ser: S) -> Result<S::Ok, S::Error> {
 		if let Some(d) = d {
 			DateTimeSerde::serialize(d, ser)
 		} else {
@@ -76,7 +119,7 @@ impl DateTimeSerdeOpt {
 	}
 
 	#[inline]
-	fn deserialize<'d, D: Deserializer<'d>>(d: D) -> Result<Option<NaiveDateTime>, D::Error> {
+	fn deserialize<'d, D: Deserializer<'d>>(d: D) -> Result<Option<DateTime>, D::Error> {
 		Ok(match Option::<String>::deserialize(d)? {
 			None => None,
 			Some(s) => {
@@ -89,12 +132,100 @@ impl DateTimeSerdeOpt {
 struct DateSerde;
 
 impl DateSerde {
-	fn serialize<S: Serializer>(d: &NaiveDate, ser: S) -> Result<S::Ok, S::Error> {
-		d.format("%Y-%m-%d").to_string().serialize(ser)
+	fn serialize<S: Serializer>(d: &Date, ser: S) -> Result<S::Ok, S::Error> {
+		date_to_string(d).serialize(ser)
 	}
 
-	fn deserialize<'d, D: Deserializer<'d>>(d: D) -> Result<NaiveDate, D::Error> {
+	fn deserialize<'d, D: Deserializer<'d>>(d: D) -> Result<Date, D::Error> {
 		let s = String::deserialize(d)?;
 		str_to_date(&s).map_err(|e| Error::custom(format!("Date parse error, input: {s}, error: {e}")))
 	}
 }
+
+/// Deserializes `null` as the default value, for the fields that the API occasionally returns as `null`
+fn null_as_default<'d, D: Deserializer<'d>, T: Deserialize<'d> + Default>(d: D) -> Result<T, D::Error> {
+	Ok(Option::<T>::deserialize(d)?.unwrap_or_default())
+}
+
+/// Deserializes the empty or whitespace-only string as `None`, use with `#[serde(default)]`
+fn empty_as_none<'d, D: Deserializer<'d>>(d: D) -> Result<Option<String>, D::Error> {
+	Ok(Option::<String>::deserialize(d)?.filter(|s| !s.trim().is_empty()))
+}
+
+/// Deserializes the number that some deployments return as a string, e.g. `"9800"`
+fn number_or_str<'d, D, T>(d: D) -> Result<T, D::Error>
+where
+	D: Deserializer<'d>,
+	T: Deserialize<'d> + FromStr,
+	T::Err: Display,
+{
+	d.deserialize_any(NumberOrStrVisitor(PhantomData))
+}
+
+/// Optional variant of [number_or_str()], use with `#[serde(default)]`
+fn number_or_str_opt<'d, D, T>(d: D) -> Result<Option<T>, D::Error>
+where
+	D: Deserializer<'d>,
+	T: Deserialize<'d> + FromStr,
+	T::Err: Display,
+{
+	struct OptVisitor<T>(PhantomData<T>);
+
+	impl<'d, T> Visitor<'d> for OptVisitor<T>
+	where
+		T: Deserialize<'d> + FromStr,
+		T::Err: Display,
+	{
+		type Value = Option<T>;
+
+		fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+			f.write_str("a number, a string containing a number or null")
+		}
+
+		fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+			Ok(None)
+		}
+
+		fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+			Ok(None)
+		}
+
+		fn visit_some<D: Deserializer<'d>>(self, d: D) -> Result<Self::Value, D::Error> {
+			number_or_str(d).map(Some)
+		}
+	}
+
+	d.deserialize_option(OptVisitor(PhantomData))
+}
+
+struct NumberOrStrVisitor<T>(PhantomData<T>);
+
+impl<'d, T> Visitor<'d> for NumberOrStrVisitor<T>
+where
+	T: Deserialize<'d> + FromStr,
+	T::Err: Display,
+{
+	type Value = T;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("a number or a string containing a number")
+	}
+
+	fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+		T::deserialize(v.into_deserializer())
+	}
+
+	fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+		T::deserialize(v.into_deserializer())
+	}
+
+	fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+		T::deserialize(v.into_deserializer())
+	}
+
+	fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+		v.trim()
+			.parse()
+			.map_err(|e| E::custom(format!("Number parse error, input: {v}, error: {e}")))
+	}
+}
@@ -1,8 +1,11 @@
-use chrono::{NaiveDate, NaiveDateTime};
+use std::fmt;
+
 use serde::Serialize;
 
-use super::enums::{MeterType, SiteSortBy, SiteStatus, SortOrder, SystemUnits, TimeUnit};
-use super::{serialize_comma_slice_opt, DateSerde, DateTimeSerde};
+use super::enums::{AccountSortBy, MeterType, SiteSortBy, SiteStatus, SortOrder, SystemUnits, TimeUnit};
+use super::response::{Account, Site};
+use super::{serialize_comma_slice_opt, Date, DateSerde, DateTime, DateTimeSerde};
+use crate::limits;
 
 #[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,13 +19,196 @@ pub struct SitesList<'r> {
 	pub status: Option<&'r [SiteStatus]>,
 }
 
+impl SitesList<'_> {
+	/// `true` if the site passes the `status` and `search_text` filters the same way as the API applies them
+	///
+	/// When `status` is not set only the active and pending sites pass, [SiteStatus::All] lets every site pass. Search
+	/// text is matched case-insensitively against the name, notes, address, city, zip code and country.
+	pub fn matches(&self, site: &Site) -> bool {
+		let status_matches = match self.status {
+			Some(statuses) => statuses
+				.iter()
+				.any(|status| *status == SiteStatus::All || *status == site.status),
+			None => StatusFilter::default().matches(site.status),
+		};
+		status_matches
+			&& self.search_text.map_or(true, |search_text| {
+				let search_text = search_text.to_lowercase();
+				let location = &site.location;
+				[
+					&site.name,
+					&site.notes,
+					&location.address,
+					&location.address2,
+					&location.city,
+					&location.zip,
+					&location.country,
+				]
+				.into_iter()
+				.any(|field| field.to_lowercase().contains(&search_text))
+			})
+	}
+
+	/// Filters, sorts and pages `sites` locally, e.g. after fetching all the pages of the list
+	///
+	/// Sorting is stable and is only applied when `sort_property` is set, see [Site::cmp_by()], the order defaults to
+	/// ascending. `start_index` and `size` are applied after sorting, all the remaining sites are kept when `size` is
+	/// not set.
+	pub fn apply(&self, sites: &mut Vec<Site>) {
+		sites.retain(|site| self.matches(site));
+		if let Some(sort_by) = self.sort_property {
+			match self.sort_order {
+				Some(SortOrder::Descending) => sites.sort_by(|a, b| b.cmp_by(a, sort_by)),
+				Some(SortOrder::Ascending) | None => sites.sort_by(|a, b| a.cmp_by(b, sort_by)),
+			}
+		}
+		let start = usize::try_from(self.start_index.unwrap_or(0)).map_or(sites.len(), |start| start.min(sites.len()));
+		sites.drain(..start);
+		if let Some(size) = self.size {
+			sites.truncate(usize::try_from(size).unwrap_or(usize::MAX));
+		}
+	}
+}
+
+/// Legal combination of the statuses for [SitesList::status]
+///
+/// The API accepts either [SiteStatus::All] alone or any non-empty combination of the other statuses. The default is the
+/// API default of the active and pending sites.
+///
+/// # Example
+/// ```
+/// use solaredge::{SiteStatus, SitesList, StatusFilter};
+///
+/// let filter = StatusFilter::new(&[SiteStatus::Disabled, SiteStatus::Active]).unwrap();
+/// let params = SitesList {
+///    status: Some(filter.as_slice()),
+///    ..SitesList::default()
+/// };
+/// assert!(StatusFilter::new(&[SiteStatus::All, SiteStatus::Active]).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StatusFilter {
+	statuses: &'static [SiteStatus],
+}
+
+impl StatusFilter {
+	/// Every site regardless of its status
+	pub const ALL: Self = Self {
+		statuses: &[SiteStatus::All],
+	};
+
+	/// All legal combinations of the statuses in the canonical order
+	const COMBINATIONS: [&'static [SiteStatus]; 8] = [
+		&[SiteStatus::All],
+		&[SiteStatus::Active],
+		&[SiteStatus::Pending],
+		&[SiteStatus::Disabled],
+		&[SiteStatus::Active, SiteStatus::Pending],
+		&[SiteStatus::Active, SiteStatus::Disabled],
+		&[SiteStatus::Pending, SiteStatus::Disabled],
+		&[SiteStatus::Active, SiteStatus::Pending, SiteStatus::Disabled],
+	];
+
+	/// Filter passing the sites with any of the `statuses`, the repeated statuses are ignored
+	///
+	/// Fails with [InvalidRequest::InvalidStatusFilter] if `statuses` is empty or combines [SiteStatus::All] with other
+	/// statuses.
+	pub fn new(statuses: &[SiteStatus]) -> Result<Self, InvalidRequest> {
+		Self::COMBINATIONS
+			.into_iter()
+			.find(|combination| {
+				combination.iter().all(|status| statuses.contains(status))
+					&& statuses.iter().all(|status| combination.contains(status))
+			})
+			.map(|statuses| Self { statuses })
+			.ok_or(InvalidRequest::InvalidStatusFilter)
+	}
+
+	/// Statuses to set as [SitesList::status]
+	#[inline]
+	pub fn as_slice(self) -> &'static [SiteStatus] {
+		self.statuses
+	}
+
+	/// `true` if the site with the `status` passes the filter
+	pub fn matches(self, status: SiteStatus) -> bool {
+		self
+			.statuses
+			.iter()
+			.any(|filter| *filter == SiteStatus::All || *filter == status)
+	}
+}
+
+impl Default for StatusFilter {
+	#[inline]
+	fn default() -> Self {
+		Self {
+			statuses: &[SiteStatus::Active, SiteStatus::Pending],
+		}
+	}
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountsList<'r> {
+	pub size: Option<u32>,
+	pub start_index: Option<u32>,
+	pub search_text: Option<&'r str>,
+	pub sort_property: Option<AccountSortBy>,
+	pub sort_order: Option<SortOrder>,
+}
+
+impl AccountsList<'_> {
+	/// `true` if the account passes the `search_text` filter the same way as the API applies it
+	///
+	/// Search text is matched case-insensitively against the name, notes, email, contact person, address, city, zip code
+	/// and country.
+	pub fn matches(&self, account: &Account) -> bool {
+		self.search_text.map_or(true, |search_text| {
+			let search_text = search_text.to_lowercase();
+			let location = &account.location;
+			[
+				&account.name,
+				&account.notes,
+				&account.email,
+				&account.contact_person,
+				&location.address,
+				&location.address2,
+				&location.city,
+				&location.zip,
+				&location.country,
+			]
+			.into_iter()
+			.any(|field| field.to_lowercase().contains(&search_text))
+		})
+	}
+
+	/// Filters, sorts and pages `accounts` locally, e.g. after fetching all the pages of the list
+	///
+	/// Works the same way as [SitesList::apply()], see [Account::cmp_by()] for the sorting.
+	pub fn apply(&self, accounts: &mut Vec<Account>) {
+		accounts.retain(|account| self.matches(account));
+		if let Some(sort_by) = self.sort_property {
+			match self.sort_order {
+				Some(SortOrder::Descending) => accounts.sort_by(|a, b| b.cmp_by(a, sort_by)),
+				Some(SortOrder::Ascending) | None => accounts.sort_by(|a, b| a.cmp_by(b, sort_by)),
+			}
+		}
+		let start = usize::try_from(self.start_index.unwrap_or(0)).map_or(accounts.len(), |start| start.min(accounts.len()));
+		accounts.drain(..start);
+		if let Some(size) = self.size {
+			accounts.truncate(usize::try_from(size).unwrap_or(usize::MAX));
+		}
+	}
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergy {
 	#[serde(with = "DateSerde")]
-	pub start_date: NaiveDate,
+	pub start_date: Date,
 	#[serde(with = "DateSerde")]
-	pub end_date: NaiveDate,
+	pub end_date: Date,
 	pub time_unit: Option<TimeUnit>,
 }
 
@@ -30,27 +216,36 @@ pub struct SiteEnergy {
 #[serde(rename_all = "camelCase")]
 pub struct SiteTotalEnergy {
 	#[serde(with = "DateSerde")]
-	pub start_date: NaiveDate,
+	pub start_date: Date,
 	#[serde(with = "DateSerde")]
-	pub end_date: NaiveDate,
+	pub end_date: Date,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DateTimeRange {
 	#[serde(with = "DateTimeSerde")]
-	pub start_time: NaiveDateTime,
+	pub start_time: DateTime,
 	#[serde(with = "DateTimeSerde")]
-	pub end_time: NaiveDateTime,
+	pub end_time: DateTime,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteSensorData {
+	#[serde(with = "DateTimeSerde")]
+	pub start_date: DateTime,
+	#[serde(with = "DateTimeSerde")]
+	pub end_date: DateTime,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SitePowerDetails<'r> {
 	#[serde(with = "DateTimeSerde")]
-	pub start_time: NaiveDateTime,
+	pub start_time: DateTime,
 	#[serde(with = "DateTimeSerde")]
-	pub end_time: NaiveDateTime,
+	pub end_time: DateTime,
 	#[serde(serialize_with = "serialize_comma_slice_opt")]
 	pub meters: Option<&'r [MeterType]>,
 }
@@ -59,9 +254,9 @@ pub struct SitePowerDetails<'r> {
 #[serde(rename_all = "camelCase")]
 pub struct MetersDateTimeRange<'r> {
 	#[serde(with = "DateTimeSerde")]
-	pub start_time: NaiveDateTime,
+	pub start_time: DateTime,
 	#[serde(with = "DateTimeSerde")]
-	pub end_time: NaiveDateTime,
+	pub end_time: DateTime,
 	pub time_unit: Option<TimeUnit>,
 	#[serde(serialize_with = "serialize_comma_slice_opt")]
 	pub meters: Option<&'r [MeterType]>,
@@ -71,9 +266,9 @@ pub struct MetersDateTimeRange<'r> {
 #[serde(rename_all = "camelCase")]
 pub struct SiteStorageData<'r> {
 	#[serde(with = "DateTimeSerde")]
-	pub start_time: NaiveDateTime,
+	pub start_time: DateTime,
 	#[serde(with = "DateTimeSerde")]
-	pub end_time: NaiveDateTime,
+	pub end_time: DateTime,
 	#[serde(serialize_with = "serialize_comma_slice_opt")]
 	pub serials: Option<&'r [String]>,
 }
@@ -83,3 +278,259 @@ pub struct SiteStorageData<'r> {
 pub struct SiteEnvBenefits {
 	pub system_units: Option<SystemUnits>,
 }
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteImage<'r> {
+	pub max_width: Option<u32>,
+	pub max_height: Option<u32>,
+	/// Hash of the image the caller already has, see [Client::site_image()](crate::Client::site_image)
+	pub hash: Option<&'r str>,
+}
+
+/// Client-side check of the documented API limits, performed by the [Client](crate::Client) before sending the request
+///
+/// Without the `chrono` feature only the order of the dates is checked, not the length of the period.
+pub trait Validate {
+	fn validate(&self) -> Result<(), InvalidRequest>;
+}
+
+/// Query string of the request parameters, e.g. for logging exactly what the [Client](crate::Client) sends
+///
+/// The string is the same as in the request URL except for the `api_key` parameter, which is never included, and the
+/// defaults from [SiteProfile](crate::SiteProfile), which the client adds to the fields left as `None`. The parameters that
+/// are `None` are omitted, so the empty string means the request is sent without the parameters.
+///
+/// # Example
+/// ```
+/// use solaredge::{Params, SitesList, SiteStatus, SortOrder};
+///
+/// let mut params = SitesList::default();
+/// params.size = Some(10);
+/// params.sort_order = Some(SortOrder::Ascending);
+/// params.status = Some(&[SiteStatus::Active, SiteStatus::Pending]);
+/// assert_eq!("size=10&sortOrder=ASC&status=Active%2CPending", params.to_query_string().unwrap());
+/// ```
+pub trait Params: Serialize {
+	fn to_query_string(&self) -> Result<String, serde_urlencoded::ser::Error> {
+		serde_urlencoded::to_string(self)
+	}
+}
+
+impl Params for SitesList<'_> {}
+impl Params for AccountsList<'_> {}
+impl Params for SiteEnergy {}
+impl Params for SiteTotalEnergy {}
+impl Params for DateTimeRange {}
+impl Params for SiteSensorData {}
+impl Params for SitePowerDetails<'_> {}
+impl Params for MetersDateTimeRange<'_> {}
+impl Params for SiteStorageData<'_> {}
+impl Params for SiteEnvBenefits {}
+
+/// Reason for rejecting the request parameters, returned in [Error::InvalidRequest](crate::Error::InvalidRequest)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidRequest {
+	/// Start of the period is after its end
+	StartAfterEnd,
+	/// Period is longer than the endpoint accepts
+	PeriodTooLong { max: MaxPeriod },
+	/// Page size is above the API maximum, see [limits::MAX_PAGE_SIZE]
+	PageSizeTooLarge { size: u32 },
+	/// Site status filter is empty or combines [SiteStatus::All] with other statuses, see [StatusFilter]
+	InvalidStatusFilter,
+}
+
+impl fmt::Display for InvalidRequest {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			InvalidRequest::StartAfterEnd => f.write_str("start of the period is after its end"),
+			InvalidRequest::PeriodTooLong { max } => write!(f, "period is longer than {max}"),
+			InvalidRequest::PageSizeTooLarge { size } => write!(f, "page size {size} is larger than {}", limits::MAX_PAGE_SIZE),
+			InvalidRequest::InvalidStatusFilter => f.write_str("site status filter is empty or combines All with other statuses"),
+		}
+	}
+}
+
+/// Longest period accepted by an endpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaxPeriod {
+	Days(u32),
+	Months(u32),
+}
+
+impl MaxPeriod {
+	/// `None` for the time units that are not limited, see [TimeUnit::max_energy_period_months()]
+	pub fn for_energy(time_unit: TimeUnit) -> Option<Self> {
+		time_unit.max_energy_period_months().map(MaxPeriod::Months)
+	}
+
+	#[cfg(feature = "chrono")]
+	fn add_to(self, date: Date) -> Option<Date> {
+		match self {
+			MaxPeriod::Days(days) => date.checked_add_days(chrono::Days::new(u64::from(days))),
+			MaxPeriod::Months(months) => date.checked_add_months(chrono::Months::new(months)),
+		}
+	}
+}
+
+impl fmt::Display for MaxPeriod {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			MaxPeriod::Days(1) => f.write_str("1 day"),
+			MaxPeriod::Days(days) => write!(f, "{days} days"),
+			MaxPeriod::Months(1) => f.write_str("1 month"),
+			MaxPeriod::Months(months) => write!(f, "{months} months"),
+		}
+	}
+}
+
+/// Checks the inclusive date period, e.g. the month limit allows from 2021-01-15 to 2021-02-14
+#[allow(unused_variables)]
+fn validate_dates(start: &Date, end: &Date, max: Option<MaxPeriod>) -> Result<(), InvalidRequest> {
+	if start > end {
+		return Err(InvalidRequest::StartAfterEnd);
+	}
+	#[cfg(feature = "chrono")]
+	if let Some(max) = max {
+		if max.add_to(*start).is_some_and(|limit| *end >= limit) {
+			return Err(InvalidRequest::PeriodTooLong { max });
+		}
+	}
+	Ok(())
+}
+
+/// Checks the date-time period, the end can be exactly `max` after the start
+#[allow(unused_variables)]
+fn validate_date_times(start: &DateTime, end: &DateTime, max: Option<MaxPeriod>) -> Result<(), InvalidRequest> {
+	if start > end {
+		return Err(InvalidRequest::StartAfterEnd);
+	}
+	#[cfg(feature = "chrono")]
+	if let Some(max) = max {
+		let limit = max.add_to(start.date()).map(|date| date.and_time(start.time()));
+		if limit.is_some_and(|limit| *end > limit) {
+			return Err(InvalidRequest::PeriodTooLong { max });
+		}
+	}
+	Ok(())
+}
+
+fn validate_page_size(size: Option<u32>) -> Result<(), InvalidRequest> {
+	match size {
+		Some(size) if size > limits::MAX_PAGE_SIZE => Err(InvalidRequest::PageSizeTooLarge { size }),
+		_ => Ok(()),
+	}
+}
+
+impl Validate for SitesList<'_> {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_page_size(self.size)?;
+		if let Some(statuses) = self.status {
+			StatusFilter::new(statuses)?;
+		}
+		Ok(())
+	}
+}
+
+impl Validate for AccountsList<'_> {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_page_size(self.size)
+	}
+}
+
+/// The period limit depends on the time unit, `DAY` is assumed when it's not set
+impl Validate for SiteEnergy {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_dates(
+			&self.start_date,
+			&self.end_date,
+			MaxPeriod::for_energy(self.time_unit.unwrap_or(TimeUnit::Day)),
+		)
+	}
+}
+
+impl Validate for SiteTotalEnergy {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_dates(&self.start_date, &self.end_date, None)
+	}
+}
+
+/// Only the order is checked, the period limit depends on the endpoint, see [DateTimeRange::validate_max_period()]
+impl Validate for DateTimeRange {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_date_times(&self.start_time, &self.end_time, None)
+	}
+}
+
+impl DateTimeRange {
+	/// Same as [Validate::validate()], but also checks that the period is not longer than `max`
+	pub fn validate_max_period(&self, max: MaxPeriod) -> Result<(), InvalidRequest> {
+		validate_date_times(&self.start_time, &self.end_time, Some(max))
+	}
+}
+
+impl Validate for SiteSensorData {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_date_times(&self.start_date, &self.end_date, Some(limits::TELEMETRY_MAX_PERIOD))
+	}
+}
+
+impl Validate for SitePowerDetails<'_> {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_date_times(&self.start_time, &self.end_time, Some(limits::POWER_MAX_PERIOD))
+	}
+}
+
+/// The period limit depends on the time unit, `DAY` is assumed when it's not set
+impl Validate for MetersDateTimeRange<'_> {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_date_times(
+			&self.start_time,
+			&self.end_time,
+			MaxPeriod::for_energy(self.time_unit.unwrap_or(TimeUnit::Day)),
+		)
+	}
+}
+
+impl Validate for SiteStorageData<'_> {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		validate_date_times(&self.start_time, &self.end_time, Some(limits::TELEMETRY_MAX_PERIOD))
+	}
+}
+
+/// Request parameters extended with the defaults from [SiteProfile](crate::SiteProfile), the defaults are only set
+/// when the corresponding field of `params` is `None`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WithProfile<'r, P> {
+	#[serde(flatten)]
+	pub params: &'r P,
+	pub time_unit: Option<TimeUnit>,
+	#[serde(serialize_with = "serialize_comma_slice_opt")]
+	pub meters: Option<&'r [MeterType]>,
+}
+
+/// Checks the period against the limit of the time unit that is actually sent, including the one from the profile
+impl Validate for WithProfile<'_, SiteEnergy> {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		let time_unit = self.time_unit.or(self.params.time_unit).unwrap_or(TimeUnit::Day);
+		validate_dates(
+			&self.params.start_date,
+			&self.params.end_date,
+			MaxPeriod::for_energy(time_unit),
+		)
+	}
+}
+
+/// Checks the period against the limit of the time unit that is actually sent, including the one from the profile
+impl Validate for WithProfile<'_, MetersDateTimeRange<'_>> {
+	fn validate(&self) -> Result<(), InvalidRequest> {
+		let time_unit = self.time_unit.or(self.params.time_unit).unwrap_or(TimeUnit::Day);
+		validate_date_times(
+			&self.params.start_time,
+			&self.params.end_time,
+			MaxPeriod::for_energy(time_unit),
+		)
+	}
+}
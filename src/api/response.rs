@@ -1,52 +1,113 @@
-use chrono::{NaiveDate, NaiveDateTime};
-use serde::Deserialize;
+//! Typed API responses
+//!
+//! The parsing tolerates the known inconsistencies between the deployments: the numeric fields accept the numbers sent as
+//! strings and the optional string fields are `None` when the API returns an empty string.
 
-use super::enums::{InverterMode, MeterType, OperationMode, SiteStatus, TimeUnit};
-use super::{DateSerde, DateTimeSerde, DateTimeSerdeOpt};
+use std::cmp::Ordering;
 
-#[derive(Debug, Deserialize)]
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+
+use super::enums::{
+	AccountSortBy, InverterMode, MeterType, OperationMode, PowerFlowStatus, PowerUnit, SiteSortBy, SiteStatus, TimeUnit,
+};
+use super::ids::{SerialNumber, SiteId};
+use super::{
+	empty_as_none, null_as_default, number_or_str, number_or_str_opt, Date, DateSerde, DateTime, DateTimeSerde, DateTimeSerdeOpt,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(bound(deserialize = "T: DeserializeOwned"))]
 pub struct List<T> {
+	#[serde(deserialize_with = "number_or_str")]
 	pub count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub list: Vec<T>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct VersionSpec {
 	pub release: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct VersionCurrentTop {
 	pub version: VersionSpec,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct VersionSupportedTop {
 	pub supported: Vec<VersionSpec>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Location {
 	pub country: String,
+	/// Only returned for the countries with states, e.g. USA
+	#[serde(default, deserialize_with = "empty_as_none")]
+	pub state: Option<String>,
 	pub city: String,
 	pub address: String,
 	pub address2: String,
 	pub zip: String,
 	pub time_zone: String,
 	pub country_code: String,
-}
-
-#[derive(Debug, Deserialize)]
+	/// Latitude in degrees, only returned for some sites
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub lat: Option<f64>,
+	/// Longitude in degrees, only returned for some sites
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub lng: Option<f64>,
+}
+
+impl Location {
+	/// `(lat, lng)` pair if both are present
+	pub fn coordinates(&self) -> Option<(f64, f64)> {
+		self.lat.zip(self.lng)
+	}
+
+	/// Great-circle distance in km to the `other` location, `None` if either of them has no coordinates
+	pub fn distance_km(&self, other: &Location) -> Option<f64> {
+		const EARTH_RADIUS_KM: f64 = 6371.;
+		let (lat1, lng1) = self.coordinates()?;
+		let (lat2, lng2) = other.coordinates()?;
+		let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+		let half_d_lat = (lat2 - lat1) / 2.;
+		let half_d_lng = (lng2 - lng1).to_radians() / 2.;
+		let a = half_d_lat.sin().powi(2) + lat1.cos() * lat2.cos() * half_d_lng.sin().powi(2);
+		Some(2. * EARTH_RADIUS_KM * a.sqrt().min(1.).asin())
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Module {
 	pub manufacturer_name: String,
 	pub model_name: String,
+	#[serde(deserialize_with = "number_or_str")]
 	pub maximum_power: f64,
+	#[serde(deserialize_with = "number_or_str")]
 	pub temperature_coef: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "UPPERCASE")]
 pub struct SiteUris {
 	pub details: String,
@@ -54,99 +115,233 @@ pub struct SiteUris {
 	pub overview: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct PublicSettings {
+	#[serde(default, deserialize_with = "empty_as_none")]
 	pub name: Option<String>,
+	/// `false` when the API returns `null`
+	#[serde(default, deserialize_with = "null_as_default")]
 	pub is_public: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Site {
-	pub id: u64,
+	pub id: SiteId,
 	pub name: String,
+	#[serde(deserialize_with = "number_or_str")]
 	pub account_id: u64,
 	pub status: SiteStatus,
+	#[serde(deserialize_with = "number_or_str")]
 	pub peak_power: f64,
 	#[serde(with = "DateTimeSerde")]
-	pub last_update_time: NaiveDateTime,
+	pub last_update_time: DateTime,
+	#[serde(default, deserialize_with = "empty_as_none")]
 	pub currency: Option<String>,
 	#[serde(with = "DateTimeSerde")]
-	pub installation_date: NaiveDateTime,
+	pub installation_date: DateTime,
 	#[serde(with = "DateTimeSerdeOpt")]
-	pub pto_date: Option<NaiveDateTime>,
+	pub pto_date: Option<DateTime>,
 	pub notes: String,
 	#[serde(rename = "type")]
 	pub typ: String,
 	pub location: Location,
 	pub primary_module: Module,
+	#[serde(default, deserialize_with = "number_or_str_opt")]
 	pub alert_quantity: Option<u32>,
+	#[serde(default, deserialize_with = "empty_as_none")]
 	pub alert_severity: Option<String>,
 	pub uris: SiteUris,
 	pub public_settings: PublicSettings,
-}
-
-#[derive(Debug, Deserialize)]
+	/// Time when the site was created in the monitoring platform, not returned by every API version
+	#[serde(default, with = "DateTimeSerdeOpt")]
+	pub creation_time: Option<DateTime>,
+}
+
+impl Site {
+	/// Compares the sites the same way the API sorts them for [SitesList::sort_property](crate::SitesList::sort_property)
+	///
+	/// Strings are compared case-insensitively, missing values go first. Alert severities are ranked from `NONE` to
+	/// `HIGH`. [SiteSortBy::Amount] is not exposed in the response so the sites always compare as equal for it.
+	pub fn cmp_by(&self, other: &Site, sort_by: SiteSortBy) -> Ordering {
+		fn cmp_str(a: &str, b: &str) -> Ordering {
+			a.to_lowercase().cmp(&b.to_lowercase())
+		}
+
+		fn severity_rank(severity: Option<&str>) -> u8 {
+			match severity.map(|s| s.to_ascii_uppercase()).as_deref() {
+				Some("LOW") => 1,
+				Some("MEDIUM") => 2,
+				Some("HIGH") => 3,
+				_ => 0,
+			}
+		}
+
+		match sort_by {
+			SiteSortBy::Name => cmp_str(&self.name, &other.name),
+			SiteSortBy::Country => cmp_str(&self.location.country, &other.location.country),
+			SiteSortBy::State => cmp_str(
+				self.location.state.as_deref().unwrap_or_default(),
+				other.location.state.as_deref().unwrap_or_default(),
+			),
+			SiteSortBy::City => cmp_str(&self.location.city, &other.location.city),
+			SiteSortBy::Address => cmp_str(&self.location.address, &other.location.address),
+			SiteSortBy::Zip => cmp_str(&self.location.zip, &other.location.zip),
+			SiteSortBy::Status => cmp_str(&self.status.to_string(), &other.status.to_string()),
+			SiteSortBy::PeakPower => self.peak_power.total_cmp(&other.peak_power),
+			SiteSortBy::InstallationDate => self.installation_date.cmp(&other.installation_date),
+			SiteSortBy::Amount => Ordering::Equal,
+			SiteSortBy::MaxSeverity => {
+				severity_rank(self.alert_severity.as_deref()).cmp(&severity_rank(other.alert_severity.as_deref()))
+			}
+			SiteSortBy::CreationTime => self.creation_time.cmp(&other.creation_time),
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SitesListSites {
+	#[serde(deserialize_with = "number_or_str")]
 	pub count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub site: Vec<Site>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SitesListTop {
 	pub sites: SitesListSites,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SiteDetailsTop {
 	pub details: Site,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct DataPeriod {
 	#[serde(with = "DateTimeSerdeOpt")]
-	pub start_date: Option<NaiveDateTime>,
+	pub start_date: Option<DateTime>,
 	#[serde(with = "DateTimeSerdeOpt")]
-	pub end_date: Option<NaiveDateTime>,
-}
-
-#[derive(Debug, Deserialize)]
+	pub end_date: Option<DateTime>,
+}
+
+impl DataPeriod {
+	/// Start and end of the data period, [DataAvailability::NotTransmitting] if the site hasn't reported any data yet
+	pub fn availability(&self) -> DataAvailability<(&DateTime, &DateTime)> {
+		match (&self.start_date, &self.end_date) {
+			(Some(start), Some(end)) => DataAvailability::Available((start, end)),
+			_ => DataAvailability::NotTransmitting,
+		}
+	}
+}
+
+/// Distinguishes the missing data from the zero values in the responses of the sites that haven't reported anything
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataAvailability<T> {
+	/// Site hasn't transmitted any data yet, e.g. it was just commissioned
+	NotTransmitting,
+	/// Site has transmitted data, but there are no values for the requested period, the API returns them as `null`
+	NoData,
+	Available(T),
+}
+
+impl<T> DataAvailability<T> {
+	#[inline]
+	pub fn is_available(&self) -> bool {
+		matches!(self, DataAvailability::Available(_))
+	}
+
+	/// Available value, `None` for both [DataAvailability::NotTransmitting] and [DataAvailability::NoData]
+	#[inline]
+	pub fn available(self) -> Option<T> {
+		match self {
+			DataAvailability::Available(value) => Some(value),
+			DataAvailability::NotTransmitting | DataAvailability::NoData => None,
+		}
+	}
+
+	pub fn map<U>(self, f: impl FnOnce(T) -> U) -> DataAvailability<U> {
+		match self {
+			DataAvailability::NotTransmitting => DataAvailability::NotTransmitting,
+			DataAvailability::NoData => DataAvailability::NoData,
+			DataAvailability::Available(value) => DataAvailability::Available(f(value)),
+		}
+	}
+}
+
+/// [DataAvailability::NoData] if none of the `values` is present
+fn values_availability(values: &[SiteDateValue]) -> DataAvailability<&[SiteDateValue]> {
+	if values.iter().any(|value| value.value.is_some()) {
+		DataAvailability::Available(values)
+	} else {
+		DataAvailability::NoData
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteDataPeriodTop {
 	pub data_period: DataPeriod,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct DataPeriodBulk {
-	pub site_id: u64,
+	pub site_id: SiteId,
 	pub data_period: DataPeriod,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct DataPeriodBulkList {
+	#[serde(deserialize_with = "number_or_str")]
 	pub count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub site_energy_list: Vec<DataPeriodBulk>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteDataPeriodBulkTop {
 	pub date_period_list: DataPeriodBulkList,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteDateValue {
 	#[serde(with = "DateTimeSerde")]
-	pub date: NaiveDateTime,
+	pub date: DateTime,
+	#[serde(default, deserialize_with = "number_or_str_opt")]
 	pub value: Option<f64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergy {
 	pub time_unit: TimeUnit,
@@ -154,53 +349,91 @@ pub struct SiteEnergy {
 	pub values: Vec<SiteDateValue>,
 }
 
-#[derive(Debug, Deserialize)]
+impl SiteEnergy {
+	/// Values of the period, [DataAvailability::NoData] if all of them are missing as opposed to being zero
+	pub fn availability(&self) -> DataAvailability<&[SiteDateValue]> {
+		values_availability(&self.values)
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergyTop {
 	pub energy: SiteEnergy,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergyValues {
 	pub measured_by: String,
 	pub values: Vec<SiteDateValue>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergyBulk {
-	pub site_id: u64,
+	pub site_id: SiteId,
 	pub energy_values: SiteEnergyValues,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergyBulkList {
 	pub time_unit: TimeUnit,
 	pub unit: String,
+	#[serde(deserialize_with = "number_or_str")]
 	pub count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub site_energy_list: Vec<SiteEnergyBulk>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergyBulkTop {
 	pub sites_energy: SiteEnergyBulkList,
 }
 
-#[derive(Debug, Deserialize)]
+impl SiteEnergyBulkList {
+	/// Converts all values of all sites to the `target` unit, e.g. [PowerUnit::Kilowatt] for `kWh`
+	///
+	/// Returns `false` and leaves the values intact if the current unit is not recognized.
+	pub fn normalize_unit(&mut self, target: PowerUnit) -> bool {
+		let values = self
+			.site_energy_list
+			.iter_mut()
+			.flat_map(|site| site.energy_values.values.iter_mut());
+		convert_power_unit(&mut self.unit, target, values)
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteLifetimeEnergy {
 	#[serde(with = "DateSerde")]
-	pub date: NaiveDate,
+	pub date: Date,
+	#[serde(deserialize_with = "number_or_str")]
 	pub energy: f64,
 	pub unit: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteTimeframeEnergy {
+	#[serde(deserialize_with = "number_or_str")]
 	pub energy: f64,
 	pub unit: String,
 	pub measured_by: String,
@@ -208,37 +441,49 @@ pub struct SiteTimeframeEnergy {
 	pub end_lifetime_energy: SiteLifetimeEnergy,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteTimeframeEnergyTop {
 	#[serde(rename = "timeFrameEnergy")]
 	pub timeframe_energy: SiteTimeframeEnergy,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteTimeframeEnergyBulk {
-	pub site_id: u64,
+	pub site_id: SiteId,
 	#[serde(rename = "timeFrameEnergy")]
 	pub timeframe_energy: SiteTimeframeEnergy,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteTimeframeEnergyList {
+	#[serde(deserialize_with = "number_or_str")]
 	pub count: usize,
 	#[serde(rename = "timeFrameEnergyList")]
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub timeframe_energy_list: Vec<SiteTimeframeEnergyBulk>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteTimeframeEnergyBulkTop {
 	#[serde(rename = "timeFrameEnergyList")]
 	pub timeframe_energy_list: SiteTimeframeEnergyList,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SitePower {
 	pub time_unit: TimeUnit,
@@ -246,49 +491,113 @@ pub struct SitePower {
 	pub values: Vec<SiteDateValue>,
 }
 
-#[derive(Debug, Deserialize)]
+impl SitePower {
+	/// Values of the period, [DataAvailability::NoData] if all of them are missing as opposed to being zero
+	pub fn availability(&self) -> DataAvailability<&[SiteDateValue]> {
+		values_availability(&self.values)
+	}
+
+	/// Converts all values to the `target` unit
+	///
+	/// Returns `false` and leaves the values intact if the current unit is not recognized.
+	pub fn normalize_unit(&mut self, target: PowerUnit) -> bool {
+		convert_power_unit(&mut self.unit, target, self.values.iter_mut())
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SitePowerTop {
 	pub power: SitePower,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergyList {
-	pub site_id: u64,
+	pub site_id: SiteId,
 	pub power_data_value_series: SiteEnergyValues,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SitePowerValueList {
 	pub time_unit: TimeUnit,
 	pub unit: String,
+	#[serde(deserialize_with = "number_or_str")]
 	pub count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub site_energy_list: Vec<SiteEnergyList>,
 }
 
-#[derive(Debug, Deserialize)]
+impl SitePowerValueList {
+	/// Converts all values of all sites to the `target` unit
+	///
+	/// Returns `false` and leaves the values intact if the current unit is not recognized.
+	pub fn normalize_unit(&mut self, target: PowerUnit) -> bool {
+		let values = self
+			.site_energy_list
+			.iter_mut()
+			.flat_map(|site| site.power_data_value_series.values.iter_mut());
+		convert_power_unit(&mut self.unit, target, values)
+	}
+}
+
+fn convert_power_unit<'v>(unit: &mut String, target: PowerUnit, values: impl Iterator<Item = &'v mut SiteDateValue>) -> bool {
+	let Some(current) = PowerUnit::from_api(unit) else {
+		return false;
+	};
+	if current != target {
+		let factor = current.factor_to(target);
+		for value in values.filter_map(|v| v.value.as_mut()) {
+			*value *= factor;
+		}
+		*unit = if unit.ends_with('h') {
+			format!("{target}h")
+		} else {
+			target.to_string()
+		};
+	}
+	true
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SitePowerBulkTop {
 	pub power_date_values_list: SitePowerValueList,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SiteEnergyData {
+	#[serde(deserialize_with = "number_or_str")]
 	pub energy: f64,
+	#[serde(default, deserialize_with = "number_or_str_opt")]
 	pub revenue: Option<f64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SitePowerData {
+	#[serde(deserialize_with = "number_or_str")]
 	pub power: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteOverview {
 	#[serde(with = "DateTimeSerde")]
-	pub last_update_time: NaiveDateTime,
+	pub last_update_time: DateTime,
 	#[serde(rename = "lifeTimeData")]
 	pub lifetime_data: SiteEnergyData,
 	pub last_year_data: SiteEnergyData,
@@ -298,12 +607,16 @@ pub struct SiteOverview {
 	pub measured_by: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SiteOverviewTop {
 	pub overview: SiteOverview,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteMeterValue {
 	#[serde(rename = "type")]
@@ -311,125 +624,223 @@ pub struct SiteMeterValue {
 	pub values: Vec<SiteDateValue>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteMetersDetails {
 	pub time_unit: TimeUnit,
 	pub unit: String,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub meters: Vec<SiteMeterValue>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SitePowerDetailsTop {
 	pub power_details: SiteMetersDetails,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnergyDetailsTop {
 	pub energy_details: SiteMetersDetails,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct PowerConnection {
 	pub from: String,
 	pub to: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct PowerFlowElement {
+	pub status: PowerFlowStatus,
+	#[serde(deserialize_with = "number_or_str")]
+	pub current_power: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct StoragePowerFlowElement {
+	pub status: PowerFlowStatus,
+	#[serde(deserialize_with = "number_or_str")]
+	pub current_power: f64,
+	/// %
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub charge_level: Option<f64>,
+	pub critical: Option<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
 pub struct SiteCurrentPowerFlow {
+	/// Seconds
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub update_refresh_rate: Option<u32>,
+	#[serde(default, deserialize_with = "empty_as_none")]
 	pub unit: Option<String>,
 	pub connections: Option<Vec<PowerConnection>>,
-}
-
-#[derive(Debug, Deserialize)]
+	#[serde(rename = "GRID")]
+	pub grid: Option<PowerFlowElement>,
+	#[serde(rename = "LOAD")]
+	pub load: Option<PowerFlowElement>,
+	#[serde(rename = "PV")]
+	pub pv: Option<PowerFlowElement>,
+	#[serde(rename = "STORAGE")]
+	pub storage: Option<StoragePowerFlowElement>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteCurrentPowerFlowTop {
 	pub site_current_power_flow: SiteCurrentPowerFlow,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct BatteryTelemetry {
 	#[serde(rename = "timeStamp", with = "DateTimeSerde")]
-	pub timestamp: NaiveDateTime,
+	pub timestamp: DateTime,
+	#[serde(deserialize_with = "number_or_str")]
 	pub power: u32,
+	#[serde(deserialize_with = "number_or_str")]
 	pub battery_state: u32,
-	#[serde(rename = "lifeTimeEnergyCharged")]
+	#[serde(rename = "lifeTimeEnergyCharged", deserialize_with = "number_or_str")]
 	pub lifetime_energy_charged: u32,
-	#[serde(rename = "lifeTimeEnergyDischarged")]
+	#[serde(rename = "lifeTimeEnergyDischarged", deserialize_with = "number_or_str")]
 	pub lifetime_energy_discharged: u32,
+	#[serde(deserialize_with = "number_or_str")]
 	pub full_pack_energy_available: u32,
+	#[serde(deserialize_with = "number_or_str")]
 	pub internal_temp: u32,
-	#[serde(rename = "ACGridCharging")]
+	#[serde(rename = "ACGridCharging", deserialize_with = "number_or_str")]
 	pub ac_grid_charging: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct StorageBattery {
 	pub nameplate: String,
-	pub serial_number: String,
+	pub serial_number: SerialNumber,
 	pub model_number: String,
+	#[serde(deserialize_with = "number_or_str")]
 	pub telemetry_count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub telemetries: Vec<BatteryTelemetry>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteStorageData {
+	#[serde(deserialize_with = "number_or_str")]
 	pub battery_count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub batteries: Vec<StorageBattery>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteStorageDataTop {
 	pub storage_data: SiteStorageData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct GasEmissionsSaved {
 	pub units: String,
+	#[serde(deserialize_with = "number_or_str")]
 	pub co2: f64,
+	#[serde(deserialize_with = "number_or_str")]
 	pub so2: f64,
+	#[serde(deserialize_with = "number_or_str")]
 	pub nox: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnvBenefits {
 	pub gas_emission_saved: GasEmissionsSaved,
+	#[serde(deserialize_with = "number_or_str")]
 	pub trees_planted: f64,
+	#[serde(deserialize_with = "number_or_str")]
 	pub light_bulbs: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteEnvBenefitsTop {
 	pub env_benefits: SiteEnvBenefits,
 }
 
-#[derive(Debug, Deserialize)]
+/// Result of [Client::site_image()](crate::Client::site_image)
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum ImageResult {
+	/// Image hasn't changed since the one with the requested hash, the API responded with HTTP 304
+	NotModified,
+	/// Image body along with its hash from the `ETag` header, `None` if the API didn't send one
+	Image(Vec<u8>, Option<String>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Meter {
 	pub name: String,
-	pub manufacturer: String,
+	/// `None` when missing or empty in the response, e.g. for the virtual meters
+	#[serde(default, deserialize_with = "empty_as_none")]
+	pub manufacturer: Option<String>,
+	/// Empty when missing in the response, e.g. for the virtual meters
+	#[serde(default)]
 	pub model: String,
-	pub firmware_version: String,
-	#[serde(rename = "connectedSolaredgeDeviceSN")]
-	pub connected_solaredge_device_sn: String,
+	/// `None` when missing or empty in the response, e.g. for the virtual meters
+	#[serde(default, deserialize_with = "empty_as_none")]
+	pub firmware_version: Option<String>,
+	/// Empty when missing in the response, e.g. for the virtual meters
+	#[serde(default, rename = "connectedSolaredgeDeviceSN")]
+	pub connected_solaredge_device_sn: SerialNumber,
 	#[serde(rename = "type")]
 	pub typ: String,
 	pub form: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Sensor {
 	#[serde(rename = "connectedSolaredgeDeviceSN")]
-	pub connected_solaredge_device_sn: String,
+	pub connected_solaredge_device_sn: SerialNumber,
 	pub id: String,
 	pub connected_to: String,
 	pub category: String,
@@ -437,29 +848,113 @@ pub struct Sensor {
 	pub typ: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct GatewaySensors {
+	/// Name of the gateway the sensors are connected to
+	pub connected_to: String,
+	#[serde(deserialize_with = "number_or_str")]
+	pub count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
+	pub sensors: Vec<Sensor>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct EquipmentSensorsTop {
+	#[serde(rename = "SiteSensors")]
+	pub site_sensors: List<GatewaySensors>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct SensorTelemetry {
+	#[serde(with = "DateTimeSerde")]
+	pub date: DateTime,
+	/// °C
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub ambient_temperature: Option<f64>,
+	/// °C
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub module_temperature: Option<f64>,
+	/// m/s
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub wind_speed: Option<f64>,
+	/// W/m²
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub global_horizontal_irradiance: Option<f64>,
+	/// W/m²
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub diffused_horizontal_irradiance: Option<f64>,
+	/// W/m²
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub direct_irradiance: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct GatewaySensorData {
+	/// Name of the gateway the sensors are connected to
+	pub connected_to: String,
+	#[serde(deserialize_with = "number_or_str")]
+	pub count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
+	pub telemetries: Vec<SensorTelemetry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct SiteSensorDataList {
+	#[serde(deserialize_with = "crate::lenient::list")]
+	pub data: Vec<GatewaySensorData>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct SiteSensorDataTop {
+	pub site_sensors: SiteSensorDataList,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Gateway {
 	pub name: String,
 	pub firmware_version: String,
 	#[serde(rename = "SN")]
-	pub sn: String,
+	pub sn: SerialNumber,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Battery {
 	pub name: String,
 	pub manufacturer: String,
 	pub model: String,
 	pub firmware_version: String,
-	pub connected_inverter_sn: String,
+	pub connected_inverter_sn: SerialNumber,
+	#[serde(deserialize_with = "number_or_str")]
 	pub nameplate_capacity: f64,
 	#[serde(rename = "SN")]
-	pub sn: String,
+	pub sn: SerialNumber,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Inverter {
 	pub name: String,
@@ -467,102 +962,140 @@ pub struct Inverter {
 	pub model: String,
 	pub communication_method: String,
 	#[serde(rename = "SN")]
-	pub sn: String,
+	pub sn: SerialNumber,
+	#[serde(deserialize_with = "number_or_str")]
 	pub connected_optimizers: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SiteInventory {
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub meters: Vec<Meter>,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub sensors: Vec<Sensor>,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub gateways: Vec<Gateway>,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub batteries: Vec<Battery>,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub inverters: Vec<Inverter>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct SiteInventoryTop {
 	#[serde(rename = "Inventory")]
 	pub inventory: SiteInventory,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteMeterValueExt {
-	pub meter_serial_number: String,
+	pub meter_serial_number: SerialNumber,
 	#[serde(rename = "connectedSolaredgeDeviceSN")]
-	pub connected_solaredge_device_sn: String,
+	pub connected_solaredge_device_sn: SerialNumber,
 	pub model: String,
 	pub meter_type: MeterType,
 	pub values: Vec<SiteDateValue>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteMeters {
 	pub time_unit: TimeUnit,
 	pub unit: String,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub meters: Vec<SiteMeterValueExt>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct SiteMetersTop {
 	pub meter_energy_details: SiteMeters,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct Equipment {
 	pub name: String,
 	pub manufacturer: String,
 	pub model: String,
-	pub serial_number: String,
-	#[serde(rename = "kWpDC")]
+	pub serial_number: SerialNumber,
+	#[serde(rename = "kWpDC", default, deserialize_with = "number_or_str_opt")]
 	pub kw_p_dc: Option<f64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct EquipmentListTop {
 	pub reporters: List<Equipment>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct LData {
+	#[serde(deserialize_with = "number_or_str")]
 	pub ac_current: f64,
+	#[serde(deserialize_with = "number_or_str")]
 	pub ac_voltage: f64,
+	#[serde(deserialize_with = "number_or_str")]
 	pub ac_frequency: f64,
 	/// VA
+	#[serde(deserialize_with = "number_or_str")]
 	pub apparent_power: f64,
 	/// VA
+	#[serde(deserialize_with = "number_or_str")]
 	pub active_power: f64,
 	/// VAR
+	#[serde(deserialize_with = "number_or_str")]
 	pub reactive_power: f64,
+	#[serde(deserialize_with = "number_or_str")]
 	pub cos_phi: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct EquipmentTelemetry {
 	#[serde(with = "DateTimeSerde")]
-	pub date: NaiveDateTime,
+	pub date: DateTime,
+	#[serde(deserialize_with = "number_or_str")]
 	pub total_active_power: f64,
+	#[serde(default, deserialize_with = "number_or_str_opt")]
 	pub dc_voltage: Option<f64>,
+	#[serde(default, deserialize_with = "number_or_str_opt")]
 	pub ground_fault_resistance: Option<f64>,
+	#[serde(deserialize_with = "number_or_str")]
 	pub power_limit: f64,
+	#[serde(deserialize_with = "number_or_str")]
 	pub total_energy: f64,
 	/// Celsius
+	#[serde(deserialize_with = "number_or_str")]
 	pub temperature: f64,
 	pub inverter_mode: InverterMode,
 	pub operation_mode: OperationMode,
 	#[serde(rename = "L1Data")]
 	pub l1_data: LData,
-	#[serde(rename = "vL1To2")]
+	#[serde(rename = "vL1To2", default, deserialize_with = "number_or_str_opt")]
 	pub v_l1_to_2: Option<f64>,
-	#[serde(rename = "vL2To3")]
+	#[serde(rename = "vL2To3", default, deserialize_with = "number_or_str_opt")]
 	pub v_l2_to_3: Option<f64>,
-	#[serde(rename = "vL3To1")]
+	#[serde(rename = "vL3To1", default, deserialize_with = "number_or_str_opt")]
 	pub v_l3_to_1: Option<f64>,
 	#[serde(rename = "L2Data")]
 	pub l2_data: Option<LData>,
@@ -570,13 +1103,101 @@ pub struct EquipmentTelemetry {
 	pub l3_data: Option<LData>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct EquipmentData {
+	#[serde(deserialize_with = "number_or_str")]
 	pub count: usize,
+	#[serde(deserialize_with = "crate::lenient::list")]
 	pub telemetries: Vec<EquipmentTelemetry>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct EquipmentDataTop {
 	pub data: EquipmentData,
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct EquipmentChange {
+	pub serial_number: SerialNumber,
+	pub part_number: String,
+	#[serde(with = "DateSerde")]
+	pub date: Date,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct EquipmentChangeLogTop {
+	#[serde(rename = "ChangeLog")]
+	pub change_log: List<EquipmentChange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct AccountLocation {
+	pub country: String,
+	#[serde(default, deserialize_with = "empty_as_none")]
+	pub state: Option<String>,
+	pub city: String,
+	pub address: String,
+	pub address2: String,
+	pub zip: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+	#[serde(deserialize_with = "number_or_str")]
+	pub id: u64,
+	pub name: String,
+	pub location: AccountLocation,
+	pub company_web_site: String,
+	pub contact_person: String,
+	pub email: String,
+	pub phone_number: String,
+	pub fax_number: String,
+	pub notes: String,
+	#[serde(default, deserialize_with = "number_or_str_opt")]
+	pub parent_id: Option<u64>,
+}
+
+impl Account {
+	/// Compares the accounts the same way the API sorts them for
+	/// [AccountsList::sort_property](crate::AccountsList::sort_property)
+	///
+	/// Strings are compared case-insensitively.
+	pub fn cmp_by(&self, other: &Account, sort_by: AccountSortBy) -> Ordering {
+		fn cmp_str(a: &str, b: &str) -> Ordering {
+			a.to_lowercase().cmp(&b.to_lowercase())
+		}
+
+		match sort_by {
+			AccountSortBy::Name => cmp_str(&self.name, &other.name),
+			AccountSortBy::Country => cmp_str(&self.location.country, &other.location.country),
+			AccountSortBy::City => cmp_str(&self.location.city, &other.location.city),
+			AccountSortBy::Address => cmp_str(&self.location.address, &other.location.address),
+			AccountSortBy::Zip => cmp_str(&self.location.zip, &other.location.zip),
+			AccountSortBy::Fax => cmp_str(&self.fax_number, &other.fax_number),
+			AccountSortBy::Phone => cmp_str(&self.phone_number, &other.phone_number),
+			AccountSortBy::Notes => cmp_str(&self.notes, &other.notes),
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct AccountsListTop {
+	pub accounts: List<Account>,
+}
@@ -0,0 +1,39 @@
+use std::time::SystemTime;
+
+use crate::RequestKey;
+
+/// Receiver of the [AuditEntry] for every request performed by the client, set with
+/// [Client::with_audit_sink()](crate::Client::with_audit_sink)
+///
+/// The entries contain no secrets, so the sink can compute the HMAC or the chained hash of the request identity and the
+/// response body (e.g. with the `hmac` and `sha2` crates) and append it to the audit log to prove the data lineage. The
+/// sink is called synchronously on the request path, it should buffer the expensive writes. It's implemented for the
+/// closures taking `&AuditEntry`.
+pub trait AuditSink: Send + Sync {
+	fn record(&self, entry: &AuditEntry);
+}
+
+impl<F> AuditSink for F
+where
+	F: Fn(&AuditEntry) + Send + Sync,
+{
+	#[inline]
+	fn record(&self, entry: &AuditEntry) {
+		self(entry)
+	}
+}
+
+/// Single request as seen by the [AuditSink]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuditEntry<'a> {
+	/// Time when the request was finished
+	pub at: SystemTime,
+	/// Client function that performed the request, e.g. `site_details`
+	pub endpoint: &'a str,
+	/// Identity of the request including its parameters, the API key is excluded
+	pub request_key: &'a RequestKey,
+	/// Number of the attempts including the retries
+	pub attempts: u32,
+	/// Raw response body, `None` if the request failed
+	pub response: Option<&'a [u8]>,
+}
@@ -0,0 +1,216 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::Poll;
+
+use crate::{limits, Client, Error};
+
+type BatchFuture<'c> = Pin<Box<dyn Future<Output = Box<dyn Any>> + 'c>>;
+
+/// Set of the heterogeneous requests executed together, created with [Client::batch()]
+///
+/// Every enqueued request returns a typed [BatchHandle] that is used to take its result from the [BatchResults]. At most
+/// [concurrency](Batch::with_concurrency) requests run at the same time, the requests are started in the order they were
+/// enqueued. The daily budgets and the concurrency limit of [Client::with_rate_limits()] still apply to every request
+/// as the batch uses the same client.
+///
+/// # Example
+/// ```
+/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+/// # mod http_adapter_reqwest {
+/// #    #[derive(Default)]
+/// #    pub struct ReqwestAdapter;
+/// #    #[async_trait::async_trait(?Send)]
+/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+/// #       type Error = String;
+/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+/// #    }
+/// # }
+/// async fn run() -> Result<(), solaredge::Error<String>> {
+///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+///    let mut batch = client.batch().with_concurrency(2);
+///    let overview = batch.add(|c| c.site_overview(1));
+///    let flow = batch.add(|c| c.site_current_power_flow(2));
+///    let mut results = batch.execute().await;
+///    let overview = results.take(overview).expect("Taken once")?;
+///    let flow = results.take(flow).expect("Taken once")?;
+///    Ok(())
+/// }
+/// ```
+pub struct Batch<'c, C: 'c> {
+	client: &'c Client<C>,
+	concurrency: usize,
+	requests: Vec<BatchFuture<'c>>,
+}
+
+impl<'c, C> Batch<'c, C>
+where
+	C: http_adapter::HttpClientAdapter,
+	C::Error: 'static,
+{
+	pub(crate) fn new(client: &'c Client<C>) -> Self {
+		Self {
+			client,
+			concurrency: limits::MAX_CONCURRENT_REQUESTS,
+			requests: vec![],
+		}
+	}
+
+	/// Sets the maximum number of the requests running at the same time, 3 by default
+	#[inline]
+	pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+		self.concurrency = concurrency.max(1);
+		self
+	}
+
+	/// Enqueues the `request`, it's not started until [Batch::execute()] is called
+	pub fn add<T, F, Fut>(&mut self, request: F) -> BatchHandle<T>
+	where
+		T: 'static,
+		F: FnOnce(&'c Client<C>) -> Fut,
+		Fut: Future<Output = Result<T, Error<C::Error>>> + 'c,
+	{
+		let future = request(self.client);
+		self
+			.requests
+			.push(Box::pin(async move { Box::new(future.await) as Box<dyn Any> }));
+		BatchHandle {
+			index: self.requests.len() - 1,
+			_result: PhantomData,
+		}
+	}
+
+	/// Number of the enqueued requests
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.requests.len()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.requests.is_empty()
+	}
+
+	/// Runs all enqueued requests to completion, the failure of one request doesn't affect the others
+	pub async fn execute(self) -> BatchResults<C::Error> {
+		let results = join_bounded(self.requests, self.concurrency).await;
+		BatchResults {
+			results: results.into_iter().map(Some).collect(),
+			_error: PhantomData,
+		}
+	}
+}
+
+/// Runs the `futures` to completion with at most `limit` of them polled at the same time, the futures are started in
+/// order and the outputs are returned in the same order
+pub(crate) async fn join_bounded<F: Future>(futures: impl IntoIterator<Item = F>, limit: usize) -> Vec<F::Output> {
+	let limit = limit.max(1);
+	let mut queued = futures.into_iter().map(Box::pin).enumerate().collect::<VecDeque<_>>();
+	let mut results = (0..queued.len()).map(|_| None).collect::<Vec<_>>();
+	let mut running = Vec::with_capacity(limit.min(queued.len()));
+	poll_fn(|cx| loop {
+		while running.len() < limit {
+			let Some(future) = queued.pop_front() else {
+				break;
+			};
+			running.push(future);
+		}
+		let mut completed = false;
+		let mut i = 0;
+		while i < running.len() {
+			let (_, future): &mut (usize, Pin<Box<F>>) = &mut running[i];
+			if let Poll::Ready(result) = future.as_mut().poll(cx) {
+				let (index, _) = running.swap_remove(i);
+				results[index] = Some(result);
+				completed = true;
+			} else {
+				i += 1;
+			}
+		}
+		if running.is_empty() && queued.is_empty() {
+			return Poll::Ready(());
+		}
+		if !completed {
+			return Poll::Pending;
+		}
+	})
+	.await;
+	results
+		.into_iter()
+		.map(|result| result.expect("All futures are completed"))
+		.collect()
+}
+
+impl<C> fmt::Debug for Batch<'_, C> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Batch")
+			.field("concurrency", &self.concurrency)
+			.field("requests", &self.requests.len())
+			.finish_non_exhaustive()
+	}
+}
+
+/// Typed reference to the request enqueued with [Batch::add()]
+pub struct BatchHandle<T> {
+	index: usize,
+	_result: PhantomData<fn() -> T>,
+}
+
+impl<T> BatchHandle<T> {
+	/// Position of the request in the batch
+	#[inline]
+	pub fn index(&self) -> usize {
+		self.index
+	}
+}
+
+impl<T> Clone for BatchHandle<T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T> Copy for BatchHandle<T> {}
+
+impl<T> fmt::Debug for BatchHandle<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_tuple("BatchHandle").field(&self.index).finish()
+	}
+}
+
+/// Results of the executed [Batch]
+pub struct BatchResults<E> {
+	results: Vec<Option<Box<dyn Any>>>,
+	_error: PhantomData<fn() -> E>,
+}
+
+impl<E: 'static> BatchResults<E> {
+	/// Takes the result of the request, `None` if it has already been taken
+	pub fn take<T: 'static>(&mut self, handle: BatchHandle<T>) -> Option<Result<T, Error<E>>> {
+		let result = self.results.get_mut(handle.index)?.take()?;
+		match result.downcast::<Result<T, Error<E>>>() {
+			Ok(result) => Some(*result),
+			Err(result) => {
+				// handle from another batch with a different type at this position
+				self.results[handle.index] = Some(result);
+				None
+			}
+		}
+	}
+
+	/// Number of the results that haven't been taken yet
+	pub fn remaining(&self) -> usize {
+		self.results.iter().filter(|result| result.is_some()).count()
+	}
+}
+
+impl<E> fmt::Debug for BatchResults<E> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("BatchResults")
+			.field("len", &self.results.len())
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use crate::RequestKey;
+
+/// Expiry of the responses whose TTL reaches past the time representable by [SystemTime]
+const NEVER_EXPIRES: Duration = Duration::from_secs(100 * 365 * 24 * 3600);
+
+/// Endpoints grouped by how often their data changes, each class has its own TTL in the [ResponseCache]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EndpointClass {
+	/// Rarely changing metadata: API version, site and account lists, site details and image, data period, inventory,
+	/// equipment and sensor lists, equipment change log
+	Static,
+	/// Current state of the site: overview and current power flow
+	Realtime,
+	/// Time series and the totals over a period, e.g. energy, power or equipment data
+	Historical,
+}
+
+impl EndpointClass {
+	/// Class of the client function `endpoint`, e.g. `site_details`
+	pub fn of(endpoint: &str) -> Self {
+		match endpoint {
+			"version_current"
+			| "version_supported"
+			| "sites_list"
+			| "sites_list_all"
+			| "site_details"
+			| "site_image"
+			| "site_data_period"
+			| "site_data_period_bulk"
+			| "follow_details"
+			| "follow_data_period"
+			| "site_inventory"
+			| "equipment_list"
+			| "equipment_changelog"
+			| "equipment_sensors"
+			| "accounts_list"
+			| "accounts_list_all" => EndpointClass::Static,
+			"site_overview" | "follow_overview" | "site_current_power_flow" => EndpointClass::Realtime,
+			_ => EndpointClass::Historical,
+		}
+	}
+}
+
+/// Per-site endpoints that can be prefetched with [Client::prefetch()](crate::Client::prefetch), named after the
+/// corresponding client functions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EndpointKind {
+	SiteDetails,
+	SiteDataPeriod,
+	SiteOverview,
+	SiteCurrentPowerFlow,
+	SiteEnvBenefits,
+	SiteInventory,
+	EquipmentList,
+	EquipmentSensors,
+}
+
+impl EndpointKind {
+	/// Name of the client function requesting the endpoint, e.g. `site_details`
+	pub fn name(self) -> &'static str {
+		match self {
+			EndpointKind::SiteDetails => "site_details",
+			EndpointKind::SiteDataPeriod => "site_data_period",
+			EndpointKind::SiteOverview => "site_overview",
+			EndpointKind::SiteCurrentPowerFlow => "site_current_power_flow",
+			EndpointKind::SiteEnvBenefits => "site_env_benefits",
+			EndpointKind::SiteInventory => "site_inventory",
+			EndpointKind::EquipmentList => "equipment_list",
+			EndpointKind::EquipmentSensors => "equipment_sensors",
+		}
+	}
+
+	#[inline]
+	pub fn class(self) -> EndpointClass {
+		EndpointClass::of(self.name())
+	}
+}
+
+/// Storage of the [ResponseCache], implement it to share the cache between the processes, e.g. in Redis or sled
+///
+/// The responses are identified by the [RequestKey], use its [canonical form](RequestKey::as_str) or the
+/// [hash](RequestKey::stable_hash) as the key of the external store. The keys passed to the store are prefixed with the
+/// hash of the API key and the base URL, so the clients of the different accounts sharing the store don't see each
+/// other's responses, the API key itself is not included. The expiry is the wall-clock time, so the processes of the
+/// collector see the same expiry. The errors of the external store
+/// should be logged and treated as a miss, the request is then sent to the API.
+pub trait CacheStore: Send + Sync {
+	/// Body stored under the `key` if it hasn't expired yet
+	fn get(&self, key: &RequestKey) -> Option<Vec<u8>>;
+
+	/// Stores the `body` of the response of the `class` under the `key` until `expires_at`, replacing the previous one
+	fn put(&self, key: &RequestKey, class: EndpointClass, body: &[u8], expires_at: SystemTime);
+
+	/// Drops all stored responses of the `class`
+	fn invalidate(&self, class: EndpointClass);
+
+	/// Drops all stored responses
+	fn clear(&self);
+}
+
+impl<S: CacheStore + ?Sized> CacheStore for Arc<S> {
+	#[inline]
+	fn get(&self, key: &RequestKey) -> Option<Vec<u8>> {
+		(**self).get(key)
+	}
+
+	#[inline]
+	fn put(&self, key: &RequestKey, class: EndpointClass, body: &[u8], expires_at: SystemTime) {
+		(**self).put(key, class, body, expires_at)
+	}
+
+	#[inline]
+	fn invalidate(&self, class: EndpointClass) {
+		(**self).invalidate(class)
+	}
+
+	#[inline]
+	fn clear(&self) {
+		(**self).clear()
+	}
+}
+
+/// Default [CacheStore] keeping the responses in the process memory
+#[derive(Default)]
+pub struct MemoryCacheStore {
+	entries: Mutex<HashMap<RequestKey, Entry>>,
+}
+
+struct Entry {
+	class: EndpointClass,
+	expires_at: SystemTime,
+	body: Vec<u8>,
+}
+
+impl MemoryCacheStore {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Number of the stored responses including the expired ones that weren't requested since
+	pub fn len(&self) -> usize {
+		self.lock_entries().len()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	fn lock_entries(&self) -> MutexGuard<'_, HashMap<RequestKey, Entry>> {
+		self.entries.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+
+impl CacheStore for MemoryCacheStore {
+	fn get(&self, key: &RequestKey) -> Option<Vec<u8>> {
+		let mut entries = self.lock_entries();
+		let entry = entries.get(key)?;
+		if SystemTime::now() < entry.expires_at {
+			Some(entry.body.clone())
+		} else {
+			entries.remove(key);
+			None
+		}
+	}
+
+	fn put(&self, key: &RequestKey, class: EndpointClass, body: &[u8], expires_at: SystemTime) {
+		self.lock_entries().insert(
+			key.clone(),
+			Entry {
+				class,
+				expires_at,
+				body: body.to_vec(),
+			},
+		);
+	}
+
+	fn invalidate(&self, class: EndpointClass) {
+		self.lock_entries().retain(|_, entry| entry.class != class);
+	}
+
+	fn clear(&self) {
+		self.lock_entries().clear();
+	}
+}
+
+impl fmt::Debug for MemoryCacheStore {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("MemoryCacheStore").field("entries", &self.len()).finish()
+	}
+}
+
+/// Cache of the response bodies, enabled with [Client::with_cache()](crate::Client::with_cache)
+///
+/// Repeated requests with the same [RequestKey] are served from the cache until the TTL of their [EndpointClass]
+/// expires, they don't reach the API and don't spend the quota. By default only the [EndpointClass::Static] responses
+/// are cached for 1 hour, set the TTLs with [ResponseCache::with_ttl()]. Only the successful responses are cached. The
+/// responses are kept in the [MemoryCacheStore] unless another [CacheStore] is set with [ResponseCache::with_store()].
+/// The cache is shared between the clones of the client, it can be warmed up with
+/// [Client::prefetch()](crate::Client::prefetch).
+///
+/// # Example
+/// ```
+/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+/// # mod http_adapter_reqwest {
+/// #    #[derive(Default)]
+/// #    pub struct ReqwestAdapter;
+/// #    #[async_trait::async_trait(?Send)]
+/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+/// #       type Error = String;
+/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+/// #    }
+/// # }
+/// use std::time::Duration;
+///
+/// use solaredge::{Client, EndpointClass, ResponseCache};
+///
+/// let cache = ResponseCache::new()
+///    .with_ttl(EndpointClass::Static, Duration::from_secs(24 * 3600))
+///    .with_ttl(EndpointClass::Realtime, Duration::from_secs(60));
+/// let client = Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY").with_cache(cache);
+/// ```
+pub struct ResponseCache {
+	ttls: HashMap<EndpointClass, Duration>,
+	store: Box<dyn CacheStore>,
+}
+
+impl ResponseCache {
+	pub fn new() -> Self {
+		Self {
+			ttls: HashMap::from([(EndpointClass::Static, Duration::from_secs(3600))]),
+			store: Box::new(MemoryCacheStore::new()),
+		}
+	}
+
+	/// Sets the TTL of the responses of the `class`, [Duration::ZERO] disables caching them, [Duration::MAX] keeps them for
+	/// 100 years
+	pub fn with_ttl(mut self, class: EndpointClass, ttl: Duration) -> Self {
+		if ttl.is_zero() {
+			self.ttls.remove(&class);
+		} else {
+			self.ttls.insert(class, ttl);
+		}
+		self
+	}
+
+	/// Replaces the [MemoryCacheStore] with the `store`
+	#[inline]
+	pub fn with_store(mut self, store: impl CacheStore + 'static) -> Self {
+		self.store = Box::new(store);
+		self
+	}
+
+	/// TTL of the responses of the `class`, `None` if they are not cached
+	#[inline]
+	pub fn ttl(&self, class: EndpointClass) -> Option<Duration> {
+		self.ttls.get(&class).copied()
+	}
+
+	/// Drops all stored responses of the `class`, e.g. after changing the site configuration
+	#[inline]
+	pub fn invalidate(&self, class: EndpointClass) {
+		self.store.invalidate(class);
+	}
+
+	/// Drops all stored responses
+	#[inline]
+	pub fn clear(&self) {
+		self.store.clear();
+	}
+
+	/// Body of the unexpired response to the request `key` of the client function `endpoint`
+	pub(crate) fn get(&self, endpoint: &str, key: &RequestKey) -> Option<Vec<u8>> {
+		self.ttl(EndpointClass::of(endpoint))?;
+		self.store.get(key)
+	}
+
+	/// Stores the `body` of the response to the request `key` if the class of the `endpoint` is cached
+	pub(crate) fn insert(&self, endpoint: &str, key: &RequestKey, body: &[u8]) {
+		let class = EndpointClass::of(endpoint);
+		if let Some(ttl) = self.ttl(class) {
+			let now = SystemTime::now();
+			if let Some(expires_at) = now.checked_add(ttl).or_else(|| now.checked_add(NEVER_EXPIRES)) {
+				self.store.put(key, class, body, expires_at);
+			}
+		}
+	}
+}
+
+impl Default for ResponseCache {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl fmt::Debug for ResponseCache {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ResponseCache")
+			.field("ttls", &self.ttls)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,119 @@
+//! Calendar periods in the site local time for building the request ranges
+//!
+//! The API expects the dates and the times in the local time of the site, so the periods must be computed from the
+//! current date at the site rather than the UTC or the local date of the caller, otherwise the aggregation is off by one
+//! day around midnight. Get the current date at the site with [site_today()] and build the period with
+//! [DateBounds::week()], [DateBounds::month()] etc.
+//!
+//! # Example
+//! ```
+//! use chrono::{FixedOffset, Weekday};
+//! use solaredge::calendar::{site_today, DateBounds};
+//!
+//! let site_tz = FixedOffset::east_opt(10 * 3600).unwrap();
+//! let this_week = DateBounds::week(site_today(&site_tz), Weekday::Sun);
+//! let params = this_week.site_energy(Some(solaredge::TimeUnit::Day));
+//! ```
+
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+
+use crate::{DateTimeRange, SiteEnergy, SiteTotalEnergy, TimeUnit};
+
+/// Current date at the site in the time zone `tz`, e.g. `chrono_tz::Tz` parsed from the site location
+/// [time_zone](crate::response::Location::time_zone) or [SiteProfile::time_zone](crate::SiteProfile::time_zone)
+pub fn site_today<Tz: TimeZone>(tz: &Tz) -> NaiveDate {
+	Utc::now().with_timezone(tz).date_naive()
+}
+
+/// Period of the whole days in the site local time, both `first` and `last` days are included
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateBounds {
+	pub first: NaiveDate,
+	pub last: NaiveDate,
+}
+
+impl DateBounds {
+	/// Single day
+	pub fn day(date: NaiveDate) -> Self {
+		Self { first: date, last: date }
+	}
+
+	/// Week containing the `date`, weeks start on `week_start`
+	pub fn week(date: NaiveDate, week_start: Weekday) -> Self {
+		let days_since_start = (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+		let first = date - Days::new(u64::from(days_since_start));
+		Self {
+			first,
+			last: first + Days::new(6),
+		}
+	}
+
+	/// Calendar month containing the `date`
+	pub fn month(date: NaiveDate) -> Self {
+		let first = date.with_day(1).expect("First day of the month is always valid");
+		Self {
+			first,
+			last: first + Months::new(1) - Days::new(1),
+		}
+	}
+
+	/// Calendar year containing the `date`
+	pub fn year(date: NaiveDate) -> Self {
+		let first = NaiveDate::from_yo_opt(date.year(), 1).expect("First day of the year is always valid");
+		Self {
+			first,
+			last: first + Months::new(12) - Days::new(1),
+		}
+	}
+
+	/// Period of the same number of days immediately preceding this one, for the previous calendar month use
+	/// [DateBounds::month()] with `self.first.pred_opt()` instead as the months differ in length
+	pub fn previous(&self) -> Self {
+		let days = Days::new(u64::from(self.days()));
+		Self {
+			first: self.first - days,
+			last: self.last - days,
+		}
+	}
+
+	/// Number of the days in the period
+	pub fn days(&self) -> u32 {
+		u32::try_from((self.last - self.first).num_days() + 1).unwrap_or(0)
+	}
+
+	#[inline]
+	pub fn contains(&self, date: NaiveDate) -> bool {
+		(self.first..=self.last).contains(&date)
+	}
+
+	/// Parameters for [Client::site_energy()](crate::Client::site_energy) covering the period
+	pub fn site_energy(&self, time_unit: Option<TimeUnit>) -> SiteEnergy {
+		SiteEnergy {
+			start_date: self.first,
+			end_date: self.last,
+			time_unit,
+		}
+	}
+
+	/// Parameters for [Client::site_time_frame_energy()](crate::Client::site_time_frame_energy) covering the period
+	pub fn site_total_energy(&self) -> SiteTotalEnergy {
+		SiteTotalEnergy {
+			start_date: self.first,
+			end_date: self.last,
+		}
+	}
+
+	/// Time range from the midnight of the first day to the last second of the last day
+	///
+	/// The end is not the midnight of the following day because the API includes the end time, so the first value of the
+	/// next day would be counted in this period.
+	pub fn date_time_range(&self) -> DateTimeRange {
+		DateTimeRange {
+			start_time: self.first.and_time(NaiveTime::MIN),
+			end_time: self
+				.last
+				.and_hms_opt(23, 59, 59)
+				.expect("Last second of the day is always valid"),
+		}
+	}
+}
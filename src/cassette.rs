@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+use http_adapter::http::StatusCode;
+use http_adapter::{HttpClientAdapter, Request, Response};
+use log::trace;
+use url::Url;
+
+use crate::RequestKey;
+
+/// Selects whether the [CassetteAdapter] sends the requests or reads the recorded responses
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CassetteMode {
+	/// Replays the recorded response if there is one, otherwise sends the request and records the response
+	#[default]
+	Auto,
+	/// Never sends the requests, the missing recording is an error, e.g. for CI without the API key
+	Replay,
+	/// Always sends the requests overwriting the recorded responses, e.g. for refreshing the recordings
+	Record,
+}
+
+/// [HttpClientAdapter] wrapper that records the successful responses of the `inner` adapter to the disk and replays them
+/// afterwards, so the integration tests don't spend the daily API quota
+///
+/// The recording is keyed by the [RequestKey] of the URL, so the API key and the order of the query parameters don't
+/// matter and the API key is never written to the disk. The request without the query parameters is recorded at its path
+/// relative to the directory, e.g. `<directory>/site/1/details.json`, the same layout as the fixtures of
+/// [Environment::Replay](crate::Environment::Replay). The request with the query parameters gets the hex
+/// [RequestKey::stable_hash()] inserted before the extension, e.g. `<directory>/site/1/energy.1f2e3d4c5b6a7988.json`. The
+/// error responses are passed through without recording.
+///
+/// # Example
+/// ```
+/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+/// # mod http_adapter_reqwest {
+/// #    #[derive(Default)]
+/// #    pub struct ReqwestAdapter;
+/// #    #[async_trait::async_trait(?Send)]
+/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+/// #       type Error = String;
+/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+/// #    }
+/// # }
+/// use solaredge::{CassetteAdapter, CassetteMode, Client};
+///
+/// let adapter = CassetteAdapter::new(http_adapter_reqwest::ReqwestAdapter::default(), "tests/cassettes", CassetteMode::Auto);
+/// let client = Client::new_with_client(adapter, "API_KEY");
+/// ```
+#[derive(Clone, Debug)]
+pub struct CassetteAdapter<A> {
+	inner: A,
+	dir: PathBuf,
+	mode: CassetteMode,
+}
+
+impl<A> CassetteAdapter<A> {
+	pub fn new(inner: A, dir: impl Into<PathBuf>, mode: CassetteMode) -> Self {
+		Self {
+			inner,
+			dir: dir.into(),
+			mode,
+		}
+	}
+
+	#[inline]
+	pub fn inner(&self) -> &A {
+		&self.inner
+	}
+
+	#[inline]
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	#[inline]
+	pub fn mode(&self) -> CassetteMode {
+		self.mode
+	}
+
+	/// Path of the recorded response to the request `url`
+	pub fn recording_path(&self, url: &Url) -> PathBuf {
+		let path = url.path().trim_start_matches('/');
+		let params = url.query_pairs().collect::<Vec<_>>();
+		let key = RequestKey::new(path, params).expect("Query pairs are always serializable");
+		if key.as_str().contains('?') {
+			let (stem, extension) = path.rsplit_once('.').unwrap_or((path, "json"));
+			self.dir.join(format!("{stem}.{:016x}.{extension}", key.stable_hash()))
+		} else {
+			self.dir.join(path)
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl<A: HttpClientAdapter> HttpClientAdapter for CassetteAdapter<A> {
+	type Error = CassetteError<A::Error>;
+
+	async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, Self::Error> {
+		let url = Url::parse(&request.uri().to_string()).map_err(CassetteError::Url)?;
+		let recording = self.recording_path(&url);
+		if self.mode != CassetteMode::Record {
+			match fs::read(&recording) {
+				Ok(body) => {
+					trace!("Replaying recording: {}", recording.display());
+					return Ok(Response::new(body));
+				}
+				Err(e) if self.mode == CassetteMode::Replay || e.kind() != io::ErrorKind::NotFound => {
+					return Err(CassetteError::Io(recording, e));
+				}
+				Err(_) => {}
+			}
+		}
+		let res = self.inner.execute(request).await.map_err(CassetteError::Http)?;
+		if res.status() == StatusCode::OK {
+			trace!("Recording response: {}", recording.display());
+			if let Some(dir) = recording.parent() {
+				fs::create_dir_all(dir).map_err(|e| CassetteError::Io(dir.to_path_buf(), e))?;
+			}
+			fs::write(&recording, res.body()).map_err(|e| CassetteError::Io(recording, e))?;
+		}
+		Ok(res)
+	}
+}
+
+/// Error of the [CassetteAdapter]
+#[derive(Debug)]
+pub enum CassetteError<E> {
+	/// Inner adapter failed to perform the request
+	Http(E),
+	/// Request URL couldn't be parsed
+	Url(url::ParseError),
+	/// Recording couldn't be read or written, [io::ErrorKind::NotFound] for the missing recording in [CassetteMode::Replay]
+	Io(PathBuf, io::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for CassetteError<E> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			CassetteError::Http(e) => fmt::Display::fmt(e, f),
+			CassetteError::Url(e) => write!(f, "Request URL parse error: {e}"),
+			CassetteError::Io(path, e) => write!(f, "Recording error, path: {}, error: {e}", path.display()),
+		}
+	}
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for CassetteError<E> {}
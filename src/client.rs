@@ -1,14 +1,49 @@
-use std::fmt;
+use std::collections::HashMap;
 use std::fmt::Write;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use std::{fmt, fs, mem};
 
+use http_adapter::http::{header, StatusCode};
 use http_adapter::{HttpClientAdapter, Request, Response};
-use log::trace;
-use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use log::{trace, warn};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use url::Url;
 
-use crate::api::request;
-use crate::{response, Error};
+use crate::api::request::{self, Validate};
+#[cfg(feature = "chrono")]
+use crate::api::{Date, DateTime};
+use crate::audit::{AuditEntry, AuditSink};
+use crate::batch::{join_bounded, Batch};
+use crate::cache::{EndpointKind, ResponseCache};
+use crate::deprecation::Deprecation;
+use crate::diagnostics::Counted;
+use crate::error::parse_retry_after;
+use crate::error_report::ErrorCollector;
+use crate::handle::{EquipmentHandle, SiteHandle};
+#[cfg(feature = "metrics")]
+use crate::instrument;
+use crate::lenient::{Lenient, SkippedRecorder};
+use crate::limits::{self, MAX_PAGE_SIZE};
+use crate::meta::{MetaRecorder, RawRecorder, RawResponse};
+use crate::middleware::ClientMiddleware;
+use crate::pagination::{Page, Paginator};
+use crate::path_encoding::PathEncoding;
+use crate::post_process::PostProcessors;
+use crate::progress::{ProgressSink, ProgressTracker};
+use crate::quota::QuotaTracker;
+use crate::request_key::fnv1a;
+use crate::retry::Retry;
+use crate::secret::{key_string, redact_api_key, wipe_url, ApiKey};
+#[cfg(feature = "chrono")]
+use crate::snapshot::SiteSnapshot;
+use crate::{
+	fixup, json, response, Environment, Error, ErrorReport, MeterType, PowerUnit, RateLimiter, RateLimits, RequestKey,
+	RequestPriority, ResponseMeta, RetryPolicy, SecretProvider, SerialNumber, SiteError, SiteId, SiteProfile, SiteResult,
+	TimeUnit, WithMeta, WithRaw,
+};
 
 /// Client for accessing SolarEdge API
 ///
@@ -29,8 +64,26 @@ use crate::{response, Error};
 /// ```
 pub struct Client<C> {
 	client: C,
+	environment: Environment,
 	base_url: Url,
-	api_key: String,
+	api_key: ApiKey,
+	count_check: bool,
+	profiles: HashMap<SiteId, SiteProfile>,
+	rate_limiter: Option<Arc<RateLimiter>>,
+	retry: Option<Retry>,
+	api_version: Option<String>,
+	meta_recorder: Option<MetaRecorder>,
+	audit_sink: Option<Arc<dyn AuditSink>>,
+	raw_recorder: Option<RawRecorder>,
+	error_collector: Option<Arc<ErrorCollector>>,
+	skipped_recorder: Option<SkippedRecorder>,
+	progress_sink: Option<Arc<dyn ProgressSink>>,
+	middlewares: Vec<Arc<dyn ClientMiddleware>>,
+	path_encoding: PathEncoding,
+	quota: Arc<QuotaTracker>,
+	cache: Option<Arc<ResponseCache>>,
+	priority: RequestPriority,
+	post_processors: PostProcessors,
 }
 
 impl<C: HttpClientAdapter> Client<C> {
@@ -76,28 +129,972 @@ impl<C: HttpClientAdapter> Client<C> {
 	/// ```
 	#[inline]
 	pub fn new_with_client(client: C, api_key: impl Into<String>) -> Self {
+		Self::new_with_environment(client, api_key, Environment::Production)
+	}
+
+	/// Construct a new client using a passed [HttpClientAdapter] implementation that targets the specified [Environment]
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// let environment = std::env::var("SOLAREDGE_ENVIRONMENT").unwrap_or_else(|_| "production".to_string());
+	/// let client = solaredge::Client::new_with_environment(
+	///    http_adapter_reqwest::ReqwestAdapter::default(),
+	///    "API_KEY",
+	///    environment.parse().expect("Invalid environment"),
+	/// );
+	/// ```
+	pub fn new_with_environment(client: C, api_key: impl Into<String>, environment: Environment) -> Self {
+		Self::with_api_key(client, ApiKey::Plain(key_string(api_key.into())), environment)
+	}
+
+	/// Construct a new client that gets the API key from the [SecretProvider] before every request instead of storing it
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// let client = solaredge::Client::new_with_secret_provider(
+	///    http_adapter_reqwest::ReqwestAdapter::default(),
+	///    solaredge::FileSecret::new("/run/secrets/solaredge_api_key"),
+	///    solaredge::Environment::Production,
+	/// );
+	/// ```
+	pub fn new_with_secret_provider(client: C, provider: impl SecretProvider + 'static, environment: Environment) -> Self {
+		Self::with_api_key(client, ApiKey::Provider(Arc::new(provider)), environment)
+	}
+
+	fn with_api_key(client: C, api_key: ApiKey, environment: Environment) -> Self {
 		Self {
 			client,
-			base_url: Url::parse("https://monitoringapi.solaredge.com").expect("Static URL parsing failed"),
-			api_key: api_key.into(),
+			base_url: Self::directory_url(environment.base_url()),
+			environment,
+			api_key,
+			count_check: false,
+			profiles: HashMap::new(),
+			rate_limiter: None,
+			retry: None,
+			api_version: None,
+			meta_recorder: None,
+			audit_sink: None,
+			raw_recorder: None,
+			error_collector: None,
+			skipped_recorder: None,
+			progress_sink: None,
+			middlewares: vec![],
+			path_encoding: PathEncoding::default(),
+			quota: Arc::new(QuotaTracker::default()),
+			cache: None,
+			priority: RequestPriority::default(),
+			post_processors: PostProcessors::default(),
+		}
+	}
+
+	/// Enables logging a warning when the item count reported in the response disagrees with the number of items
+	/// received, see [Counted] for checking it manually
+	#[inline]
+	pub fn with_count_check(mut self, enabled: bool) -> Self {
+		self.count_check = enabled;
+		self
+	}
+
+	/// Registers the defaults for the requests to the site `site_id`, replaces the previously registered profile
+	#[inline]
+	pub fn with_site_profile(mut self, site_id: impl Into<SiteId>, profile: SiteProfile) -> Self {
+		self.profiles.insert(site_id.into(), profile);
+		self
+	}
+
+	/// Profile registered for the site `site_id` with [Client::with_site_profile()]
+	#[inline]
+	pub fn site_profile(&self, site_id: impl Into<SiteId>) -> Option<&SiteProfile> {
+		self.profiles.get(&site_id.into())
+	}
+
+	/// Enables tracking of the request budgets, see [RateLimiter]
+	#[inline]
+	pub fn with_rate_limits(mut self, limits: RateLimits) -> Self {
+		self.rate_limiter = Some(Arc::new(RateLimiter::new(limits)));
+		self
+	}
+
+	/// Sets the [RequestPriority] of the requests made by this client when waiting for the concurrency limit of the
+	/// [RateLimiter]
+	///
+	/// The clones of the client share the rate limiter, so a clone with a different priority can be used for a kind of
+	/// work, e.g. `let backfill = client.clone().with_priority(RequestPriority::Low)`. Has no effect without
+	/// [Client::with_rate_limits()].
+	#[inline]
+	pub fn with_priority(mut self, priority: RequestPriority) -> Self {
+		self.priority = priority;
+		self
+	}
+
+	/// Rate limiter enabled with [Client::with_rate_limits()], can be used to check the remaining budgets
+	#[inline]
+	pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+		self.rate_limiter.as_deref()
+	}
+
+	/// Replaces the [QuotaTracker] counting today's requests, e.g. to set the limits or the UTC offsets of the sites
+	#[inline]
+	pub fn with_quota_tracker(mut self, tracker: QuotaTracker) -> Self {
+		self.quota = Arc::new(tracker);
+		self
+	}
+
+	/// Requests sent today, see [QuotaTracker]
+	#[inline]
+	pub fn quota(&self) -> &QuotaTracker {
+		&self.quota
+	}
+
+	/// Enables the [ResponseCache] serving the repeated requests without reaching the API, the cache is shared between the
+	/// clones of the client
+	#[inline]
+	pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+		self.cache = Some(Arc::new(cache));
+		self
+	}
+
+	/// Cache enabled with [Client::with_cache()], e.g. to invalidate the stored responses
+	#[inline]
+	pub fn cache(&self) -> Option<&ResponseCache> {
+		self.cache.as_deref()
+	}
+
+	/// Enables counting the failed requests over the sliding `window`, see [Client::error_report()]
+	///
+	/// The counts are shared with the clones of the client created after this call.
+	#[inline]
+	pub fn with_error_report(mut self, window: Duration) -> Self {
+		self.error_collector = Some(Arc::new(ErrorCollector::new(window)));
+		self
+	}
+
+	/// Failures of the requests within the window by the function and the [ErrorClass], `None` unless enabled with
+	/// [Client::with_error_report()]
+	///
+	/// The requests rejected by the parameter validation are not counted as they are never sent.
+	pub fn error_report(&self) -> Option<ErrorReport> {
+		self.error_collector.as_ref().map(|collector| collector.report())
+	}
+
+	/// Enables retrying of the transient failures according to the `policy`
+	///
+	/// The client is runtime-agnostic so the `sleep` function that waits for the specified duration must be provided,
+	/// e.g. `tokio::time::sleep`. The daily budgets of [Client::with_rate_limits()] are charged for every attempt.
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY")
+	///    .with_retry(solaredge::RetryPolicy::default(), tokio::time::sleep);
+	/// ```
+	pub fn with_retry<S, F>(mut self, policy: RetryPolicy, sleep: S) -> Self
+	where
+		S: Fn(Duration) -> F + Send + Sync + 'static,
+		F: Future<Output = ()> + 'static,
+	{
+		self.retry = Some(Retry {
+			policy,
+			sleep: Arc::new(move |delay| Box::pin(sleep(delay))),
+		});
+		self
+	}
+
+	/// Sends the requests to the API-compatible server at `base_url` instead of the production API, e.g. a local mock
+	/// server or an internal API gateway, switches the [Environment] to [Environment::Custom]
+	///
+	/// The path of the `base_url` is kept, so `https://gateway.example.com/solaredge` results in the requests to
+	/// `https://gateway.example.com/solaredge/site/{site_id}/details.json` etc.
+	#[inline]
+	pub fn with_base_url(mut self, base_url: Url) -> Self {
+		self.base_url = Self::directory_url(base_url.clone());
+		self.environment = Environment::Custom(base_url);
+		self
+	}
+
+	/// Sets the API version reported in the [ResponseMeta], e.g. the one returned by [Client::version_current()]
+	#[inline]
+	pub fn with_api_version(mut self, version: impl Into<String>) -> Self {
+		self.api_version = Some(version.into());
+		self
+	}
+
+	/// Sets the [AuditSink] receiving every request performed by this client and its response
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY").with_audit_sink(
+	///    |entry: &solaredge::AuditEntry| {
+	///       // replace with the HMAC of `entry.request_key.as_str()` and `entry.response`
+	///       println!("{} {} {:?}", entry.endpoint, entry.request_key, entry.response.map(|body| body.len()));
+	///    },
+	/// );
+	/// ```
+	#[inline]
+	pub fn with_audit_sink(mut self, sink: impl AuditSink + 'static) -> Self {
+		self.audit_sink = Some(Arc::new(sink));
+		self
+	}
+
+	/// Sets the [ProgressSink] receiving the progress of the functions that perform several requests
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY").with_progress(
+	///    |progress: &solaredge::Progress| {
+	///       eprintln!("{}: {}/{}, ETA: {:?}", progress.operation, progress.done, progress.total, progress.eta());
+	///    },
+	/// );
+	/// ```
+	#[inline]
+	pub fn with_progress(mut self, sink: impl ProgressSink + 'static) -> Self {
+		self.progress_sink = Some(Arc::new(sink));
+		self
+	}
+
+	/// Sets the [PathEncoding] of the serial numbers in the endpoint paths, [PathEncoding::Segment] by default
+	#[inline]
+	pub fn with_path_encoding(mut self, encoding: PathEncoding) -> Self {
+		self.path_encoding = encoding;
+		self
+	}
+
+	/// Adds the `processor` run on every successful response of the client function `endpoint`, e.g. `site_energy_details`
+	///
+	/// Use it to keep the data hygiene applied across the application in one place, e.g. sorting the series or normalizing
+	/// the units. `T` is the type returned by the client function, the processor registered with another type is skipped
+	/// with a warning. The processors of the same endpoint run in the order they were added. The functions that combine
+	/// several requests, e.g. [Client::equipment_data_range()], get the processed response of every request, the bulk
+	/// functions run the processors once on the merged result.
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// use solaredge::response::{EquipmentTelemetry, SitePowerValueList};
+	/// use solaredge::series::SortSeries;
+	/// use solaredge::PowerUnit;
+	///
+	/// let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY")
+	///    .with_post_processor("equipment_data", |telemetries: &mut Vec<EquipmentTelemetry>| telemetries.sort_series())
+	///    .with_post_processor("site_power_bulk", |power: &mut SitePowerValueList| {
+	///       power.normalize_unit(PowerUnit::Watt);
+	///    });
+	/// ```
+	pub fn with_post_processor<T: 'static>(
+		mut self,
+		endpoint: &'static str,
+		processor: impl Fn(&mut T) + Send + Sync + 'static,
+	) -> Self {
+		self.post_processors.add(endpoint, processor);
+		self
+	}
+
+	/// Adds the [ClientMiddleware] called around every HTTP request, the middlewares are called in the order they were added
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// use http_adapter::http::HeaderValue;
+	/// use http_adapter::{Request, Response};
+	///
+	/// struct Tracing;
+	///
+	/// impl solaredge::ClientMiddleware for Tracing {
+	///    fn before_request(&self, _endpoint: &str, request: &mut Request<Vec<u8>>) {
+	///       request.headers_mut().insert("x-request-source", HeaderValue::from_static("collector"));
+	///    }
+	///
+	///    fn after_response(&self, endpoint: &str, response: &Response<Vec<u8>>) {
+	///       eprintln!("{endpoint}: {}, {} bytes", response.status(), response.body().len());
+	///    }
+	/// }
+	///
+	/// let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY").with_middleware(Tracing);
+	/// ```
+	#[inline]
+	pub fn with_middleware(mut self, middleware: impl ClientMiddleware + 'static) -> Self {
+		self.middlewares.push(Arc::new(middleware));
+		self
+	}
+
+	/// Runs the `request` on a clone of this client collecting the provenance of every response
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Clone, Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// async fn run() -> Result<(), solaredge::Error<String>> {
+	///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY").with_api_version("1.0.0");
+	///    let details = client.with_meta(|c| async move { c.site_details(1).await }).await?;
+	///    println!("{} fetched at {:?}", details.value.name, details.fetched_at());
+	///    Ok(())
+	/// }
+	/// ```
+	pub async fn with_meta<T, E, F, Fut>(&self, request: F) -> Result<WithMeta<T>, E>
+	where
+		C: Clone,
+		F: FnOnce(Self) -> Fut,
+		Fut: Future<Output = Result<T, E>>,
+	{
+		let recorder = MetaRecorder::default();
+		let mut client = self.clone();
+		client.meta_recorder = Some(Arc::clone(&recorder));
+		let value = request(client).await?;
+		let meta = recorder.lock().unwrap_or_else(|e| e.into_inner()).drain(..).collect();
+		Ok(WithMeta { value, meta })
+	}
+
+	/// Runs the `request` on a clone of this client collecting the original body of every response
+	///
+	/// The bodies are returned even if the `request` fails, e.g. when the response doesn't match the expected schema, so
+	/// this can be used to debug the parsing errors or to archive the exact API payloads.
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Clone, Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// async fn run() {
+	///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+	///    let details = client.with_raw(|c| async move { c.site_details(1).await }).await;
+	///    if let Err(e) = details.value {
+	///       for raw in &details.raw {
+	///          eprintln!("{e}, {}: {}", raw.path, raw.body);
+	///       }
+	///    }
+	/// }
+	/// ```
+	pub async fn with_raw<T, E, F, Fut>(&self, request: F) -> WithRaw<Result<T, E>>
+	where
+		C: Clone,
+		F: FnOnce(Self) -> Fut,
+		Fut: Future<Output = Result<T, E>>,
+	{
+		let recorder = RawRecorder::default();
+		let mut client = self.clone();
+		client.raw_recorder = Some(Arc::clone(&recorder));
+		let value = request(client).await;
+		let raw = recorder.lock().unwrap_or_else(|e| e.into_inner()).drain(..).collect();
+		WithRaw { value, raw }
+	}
+
+	/// Runs the `request` on a clone of this client that skips the list elements failing to deserialize instead of
+	/// failing the whole response
+	///
+	/// Useful when a single malformed entry, e.g. an inverter in [Client::site_inventory()], shouldn't prevent getting the
+	/// rest of the data. The skipped elements are returned along with their errors, and logged as warnings.
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Clone, Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// async fn run() -> Result<(), solaredge::Error<String>> {
+	///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+	///    let inventory = client.with_lenient(|c| async move { c.site_inventory(1).await }).await?;
+	///    for (i, e) in &inventory.skipped {
+	///       eprintln!("Skipped element {i}: {e}");
+	///    }
+	///    println!("{} inverters", inventory.value.inverters.len());
+	///    Ok(())
+	/// }
+	/// ```
+	pub async fn with_lenient<T, E, F, Fut>(&self, request: F) -> Result<Lenient<T>, E>
+	where
+		C: Clone,
+		F: FnOnce(Self) -> Fut,
+		Fut: Future<Output = Result<T, E>>,
+	{
+		let recorder = SkippedRecorder::default();
+		let mut client = self.clone();
+		client.skipped_recorder = Some(Arc::clone(&recorder));
+		let value = request(client).await?;
+		let skipped = recorder.lock().unwrap_or_else(|e| e.into_inner()).drain(..).collect();
+		Ok(Lenient { value, skipped })
+	}
+
+	/// Starts a [Batch] of the requests to this client
+	#[inline]
+	pub fn batch(&self) -> Batch<'_, C>
+	where
+		C::Error: 'static,
+	{
+		Batch::new(self)
+	}
+
+	/// Returns a [SiteHandle] for calling the site-scoped functions without repeating the `site_id`
+	#[inline]
+	pub fn site(&self, site_id: impl Into<SiteId>) -> SiteHandle<'_, C> {
+		SiteHandle::new(self, site_id.into())
+	}
+
+	/// Returns an [EquipmentHandle] for calling the equipment-scoped functions without repeating the `site_id` and the
+	/// `serial_number`
+	#[inline]
+	pub fn equipment(&self, site_id: impl Into<SiteId>, serial_number: impl Into<SerialNumber>) -> EquipmentHandle<'_, C> {
+		EquipmentHandle::new(self, site_id.into(), serial_number.into())
+	}
+
+	/// Returns an owned future running the `request` for the site `site_id` on a clone of this client, the error is
+	/// wrapped in [SiteError] to identify the failed site
+	///
+	/// The future is `'static` so it can be spawned or collected without borrowing the client. It's not `Send` because
+	/// the futures of [HttpClientAdapter] aren't required to be `Send`, so spawn it with `tokio::task::spawn_local()`
+	/// (`JoinSet::spawn_local()`) inside a `LocalSet` or poll it within the same task, e.g. in `FuturesUnordered`.
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Clone, Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// async fn run() {
+	///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+	///    let mut tasks = tokio::task::JoinSet::new();
+	///    for site_id in [1, 2, 3] {
+	///       tasks.spawn_local(client.site_task(site_id, |c, site_id| async move { c.site_overview(site_id).await }));
+	///    }
+	///    while let Some(res) = tasks.join_next().await {
+	///       match res.expect("Task panicked") {
+	///          Ok((site_id, overview)) => println!("{site_id}: {}", overview.current_power.power),
+	///          Err(e) => eprintln!("{e}"),
+	///       }
+	///    }
+	/// }
+	/// ```
+	pub fn site_task<T, F, Fut>(
+		&self,
+		site_id: impl Into<SiteId>,
+		request: F,
+	) -> impl Future<Output = SiteResult<T, C::Error>> + 'static
+	where
+		C: Clone + 'static,
+		F: FnOnce(Self, SiteId) -> Fut + 'static,
+		Fut: Future<Output = Result<T, Error<C::Error>>>,
+	{
+		let site_id = site_id.into();
+		let client = self.clone();
+		async move {
+			request(client, site_id)
+				.await
+				.map(|res| (site_id, res))
+				.map_err(|error| SiteError { site_id, error })
 		}
 	}
 
+	/// [Client::site_task()] for every site in `site_ids`
+	pub fn site_tasks<T, F, Fut>(
+		&self,
+		site_ids: &[impl Into<SiteId> + Copy],
+		request: F,
+	) -> Vec<impl Future<Output = SiteResult<T, C::Error>> + 'static>
+	where
+		C: Clone + 'static,
+		F: Fn(Self, SiteId) -> Fut + Clone + 'static,
+		Fut: Future<Output = Result<T, Error<C::Error>>>,
+	{
+		site_ids
+			.iter()
+			.map(|site_id| self.site_task(*site_id, request.clone()))
+			.collect()
+	}
+
+	/// Runs the `request` for every site in `site_ids` with at most `limit` of them running at the same time, the results
+	/// are returned in the order of `site_ids`
+	///
+	/// The API allows 3 concurrent requests per API key, so `limit` should stay at or below that unless the requests are
+	/// also limited by [Client::with_rate_limits()]. Like [Client::site_tasks()] every request runs on a clone of this
+	/// client, the failure of one site doesn't affect the others.
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Clone, Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// async fn run() {
+	///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+	///    let overviews = client
+	///       .for_each_site_concurrent(&[1, 2, 3, 4], 3, |c, site_id| async move { c.site_overview(site_id).await })
+	///       .await;
+	///    for res in overviews {
+	///       match res {
+	///          Ok((site_id, overview)) => println!("{site_id}: {}", overview.current_power.power),
+	///          Err(e) => eprintln!("{e}"),
+	///       }
+	///    }
+	/// }
+	/// ```
+	pub async fn for_each_site_concurrent<T, F, Fut>(
+		&self,
+		site_ids: &[impl Into<SiteId> + Copy],
+		limit: usize,
+		request: F,
+	) -> Vec<SiteResult<T, C::Error>>
+	where
+		C: Clone + 'static,
+		F: Fn(Self, SiteId) -> Fut + Clone + 'static,
+		Fut: Future<Output = Result<T, Error<C::Error>>>,
+	{
+		join_bounded(self.site_tasks(site_ids, request), limit).await
+	}
+
+	/// Primes the [ResponseCache] with the responses of the `endpoints` for every site in `site_ids`, e.g. ahead of the
+	/// expected navigation of the dashboard
+	///
+	/// Only the endpoints whose [EndpointClass](crate::EndpointClass) is cached are requested, without
+	/// [Client::with_cache()] nothing is sent. At most 3 requests run at the same time, with [RequestPriority::Low] so
+	/// they don't delay the other requests waiting for the concurrency limit of [Client::with_rate_limits()]. The
+	/// responses that are already cached don't reach the API. Returns the failures, the failure of one request doesn't
+	/// affect the others.
+	pub async fn prefetch(&self, site_ids: &[impl Into<SiteId> + Copy], endpoints: &[EndpointKind]) -> Vec<SiteError<C::Error>>
+	where
+		C: Clone,
+	{
+		let Some(cache) = &self.cache else {
+			return vec![];
+		};
+		let endpoints = endpoints
+			.iter()
+			.filter(|endpoint| cache.ttl(endpoint.class()).is_some())
+			.collect::<Vec<_>>();
+		trace!("prefetch, site_ids: {}, endpoints: {:?}", site_ids.len(), endpoints);
+		let client = self.clone().with_priority(RequestPriority::Low);
+		let client = &client;
+		let requests = site_ids.iter().flat_map(|site_id| {
+			let site_id = (*site_id).into();
+			endpoints.iter().map(move |endpoint| async move {
+				client
+					.prefetch_endpoint(site_id, **endpoint)
+					.await
+					.map_err(|error| SiteError { site_id, error })
+			})
+		});
+		join_bounded(requests, limits::MAX_CONCURRENT_REQUESTS)
+			.await
+			.into_iter()
+			.filter_map(Result::err)
+			.collect()
+	}
+
+	async fn prefetch_endpoint(&self, site_id: SiteId, endpoint: EndpointKind) -> Result<(), Error<C::Error>> {
+		match endpoint {
+			EndpointKind::SiteDetails => self.site_details(site_id).await.map(drop),
+			EndpointKind::SiteDataPeriod => self.site_data_period(site_id).await.map(drop),
+			EndpointKind::SiteOverview => self.site_overview(site_id).await.map(drop),
+			EndpointKind::SiteCurrentPowerFlow => self.site_current_power_flow(site_id).await.map(drop),
+			EndpointKind::SiteEnvBenefits => self
+				.site_env_benefits(site_id, &request::SiteEnvBenefits { system_units: None })
+				.await
+				.map(drop),
+			EndpointKind::SiteInventory => self.site_inventory(site_id).await.map(drop),
+			EndpointKind::EquipmentList => self.equipment_list(site_id).await.map(drop),
+			EndpointKind::EquipmentSensors => self.equipment_sensors(site_id).await.map(drop),
+		}
+	}
+
+	/// [Environment] that this client is targeting
+	#[inline]
+	pub fn environment(&self) -> &Environment {
+		&self.environment
+	}
+
+	/// Appends the trailing slash to the path of the base URL so that the endpoint paths are joined to it instead of
+	/// replacing its last segment
+	fn directory_url(mut url: Url) -> Url {
+		if !url.path().ends_with('/') {
+			let path = format!("{}/", url.path());
+			url.set_path(&path);
+		}
+		url
+	}
+
 	fn prepare_url<E>(&self, path: &str, params: impl Serialize) -> Result<Url, Error<E>> {
-		let mut out = self.base_url.join(path).expect("Static URL parsing failed");
+		// relative path keeps the path prefix of the base URL, e.g. of the API gateway
+		let mut out = self.base_url.join(path.trim_start_matches('/'))?;
 		let query = serde_urlencoded::to_string(params)?;
 		if !query.is_empty() {
 			out.set_query(Some(&query));
 		}
-		out.query_pairs_mut().append_pair("api_key", &self.api_key);
+		let api_key = self.api_key.get().map_err(Error::Secret)?;
+		out.query_pairs_mut().append_pair("api_key", &api_key);
 		Ok(out)
 	}
 
+	/// Hash of the API key and the base URL scoping the [ResponseCache] keys, the key itself must not reach the store
+	fn account_hash<E>(&self) -> Result<u64, Error<E>> {
+		let api_key = self.api_key.get().map_err(Error::Secret)?;
+		let mut account = key_string(String::with_capacity(self.base_url.as_str().len() + 1 + api_key.len()));
+		account.push_str(self.base_url.as_str());
+		account.push('\n');
+		account.push_str(&api_key);
+		Ok(fnv1a(account.as_bytes()))
+	}
+
 	fn request_get(url: Url) -> Request<Vec<u8>> {
 		Request::get(url.to_string()).body(vec![]).unwrap()
 	}
 
-	fn join_site_ids(ids: &[u64]) -> String {
+	async fn perform_request<T: DeserializeOwned>(
+		&self,
+		name: &str,
+		path: &str,
+		params: impl Serialize,
+	) -> Result<T, Error<C::Error>> {
+		let body = self.perform_request_raw(name, path, params).await?;
+		let body = fixup::apply(name, &body);
+		let (res, skipped) = json::parse(&body, self.skipped_recorder.is_some());
+		if let Some(recorder) = &self.skipped_recorder {
+			for (i, e) in &skipped {
+				warn!("{}, skipped list element {}: {}", name, i, e);
+			}
+			recorder.lock().unwrap_or_else(|e| e.into_inner()).extend(skipped);
+		}
+		res.map_err(|e| self.record_error(name, Error::Json(e)))
+	}
+
+	/// Performs the request with retries returning the response body
+	pub(crate) async fn perform_request_raw(
+		&self,
+		name: &str,
+		path: &str,
+		params: impl Serialize,
+	) -> Result<Vec<u8>, Error<C::Error>> {
+		self
+			.send_request(name, path, params)
+			.await
+			.map(|fetched| fetched.body)
+			.map_err(|e| self.record_error(name, e))
+	}
+
+	/// Runs the post-processors registered for the `endpoint` on the response `value`
+	fn post_process<T: 'static>(&self, endpoint: &str, mut value: T) -> T {
+		self.post_processors.apply(endpoint, &mut value);
+		value
+	}
+
+	fn progress(&self, operation: &'static str, total: usize) -> ProgressTracker<'_> {
+		ProgressTracker::new(self.progress_sink.as_deref(), operation, total)
+	}
+
+	/// Counts the failure in the [ErrorReport] if enabled, returns the passed error
+	fn record_error(&self, name: &str, e: Error<C::Error>) -> Error<C::Error> {
+		if let Some(collector) = &self.error_collector {
+			collector.record(name, e.class());
+		}
+		e
+	}
+
+	async fn send_request(&self, name: &str, path: &str, params: impl Serialize) -> Result<Fetched, Error<C::Error>> {
+		let request_key = if self.meta_recorder.is_some() || self.audit_sink.is_some() || self.cache.is_some() {
+			Some(RequestKey::new(path, &params)?)
+		} else {
+			None
+		};
+		let cache_key = match (&self.cache, &request_key) {
+			(Some(_), Some(request_key)) => Some(request_key.scoped(self.account_hash()?)),
+			_ => None,
+		};
+		let cached = self
+			.cache
+			.as_ref()
+			.zip(cache_key.as_ref())
+			.and_then(|(cache, cache_key)| cache.get(name, cache_key));
+		let from_cache = cached.is_some();
+		let (mut fetched, attempt) = if let Some(body) = cached {
+			trace!("{}, served from the cache", name);
+			(
+				Fetched {
+					body,
+					deprecation: None,
+					etag: None,
+				},
+				0,
+			)
+		} else {
+			let mut url = self.prepare_url(path, &params)?;
+			trace!("{}, url: {}", name, redact_api_key(url.as_str()));
+			let mut attempt = 1;
+			let mut key_refreshed = false;
+			let fetched = loop {
+				match self.fetch(name, path, &url).await {
+					Ok(res) => break res,
+					Err(Error::Api(StatusCode::FORBIDDEN, _)) if !key_refreshed && self.api_key.refresh() => {
+						warn!("{}, API key rejected, retrying with the refreshed key", name);
+						key_refreshed = true;
+						wipe_url(mem::replace(&mut url, self.prepare_url(path, &params)?));
+					}
+					Err(e) => {
+						let Some((retry, delay)) = self
+							.retry
+							.as_ref()
+							.and_then(|retry| retry.policy.delay_for(attempt, &e).map(|delay| (retry, delay)))
+						else {
+							wipe_url(url);
+							self.audit(name, request_key.as_ref(), attempt, None);
+							return Err(e);
+						};
+						warn!("{}, attempt {} failed, retrying in {:?}", name, attempt, delay);
+						(retry.sleep)(delay).await;
+						attempt += 1;
+					}
+				}
+			};
+			wipe_url(url);
+			self.audit(name, request_key.as_ref(), attempt, Some(&fetched.body));
+			if let (Some(cache), Some(cache_key)) = (&self.cache, &cache_key) {
+				cache.insert(name, cache_key, &fetched.body);
+			}
+			(fetched, attempt)
+		};
+		if let Some(recorder) = &self.raw_recorder {
+			recorder.lock().unwrap_or_else(|e| e.into_inner()).push(RawResponse {
+				endpoint: name.to_string(),
+				path: path.to_string(),
+				body: String::from_utf8_lossy(&fetched.body).into_owned(),
+			});
+		}
+		if let (Some(recorder), Some(request_key)) = (&self.meta_recorder, request_key) {
+			recorder.lock().unwrap_or_else(|e| e.into_inner()).push(ResponseMeta {
+				fetched_at: SystemTime::now(),
+				endpoint: name.to_string(),
+				path: path.to_string(),
+				request_key,
+				api_version: self.api_version.clone(),
+				from_cache: from_cache || matches!(self.environment, Environment::Replay(_)),
+				attempts: attempt,
+				deprecation: fetched.deprecation.take(),
+			});
+		}
+		Ok(fetched)
+	}
+
+	fn audit(&self, name: &str, request_key: Option<&RequestKey>, attempts: u32, response: Option<&[u8]>) {
+		if let (Some(sink), Some(request_key)) = (&self.audit_sink, request_key) {
+			sink.record(&AuditEntry {
+				at: SystemTime::now(),
+				endpoint: name,
+				request_key,
+				attempts,
+				response,
+			});
+		}
+	}
+
+	/// Performs a single attempt of the request
+	async fn fetch(&self, name: &str, path: &str, url: &Url) -> Result<Fetched, Error<C::Error>> {
+		#[cfg(feature = "metrics")]
+		let started = std::time::Instant::now();
+		let res = self.fetch_unrecorded(name, path, url).await;
+		#[cfg(feature = "metrics")]
+		instrument::record(name, &res, started.elapsed());
+		res
+	}
+
+	async fn fetch_unrecorded(&self, name: &str, path: &str, url: &Url) -> Result<Fetched, Error<C::Error>> {
+		let _permit = if let Some(rate_limiter) = &self.rate_limiter {
+			rate_limiter.charge(path).map_err(Error::BudgetExhausted)?;
+			Some(rate_limiter.acquire(self.priority).await)
+		} else {
+			None
+		};
+		self.quota.record(path);
+		if let Environment::Replay(dir) = &self.environment {
+			let fixture = dir.join(path.trim_start_matches('/'));
+			trace!("{}, replaying fixture: {}", name, fixture.display());
+			let body = fs::read(&fixture).map_err(|e| Error::Replay(fixture, e))?;
+			Ok(Fetched {
+				body,
+				deprecation: None,
+				etag: None,
+			})
+		} else {
+			let mut request = Self::request_get(url.clone());
+			for middleware in &self.middlewares {
+				middleware.before_request(name, &mut request);
+			}
+			let res = self.client.execute(request).await.map_err(Error::HttpRequest)?;
+			for middleware in &self.middlewares {
+				middleware.after_response(name, &res);
+			}
+			let deprecation = Deprecation::from_response(&res);
+			if let Some(deprecation) = &deprecation {
+				warn!("{}, {}", name, deprecation);
+			}
+			let etag = res
+				.headers()
+				.get(header::ETAG)
+				.and_then(|value| value.to_str().ok())
+				.map(|value| value.trim_start_matches("W/").trim_matches('"').to_string());
+			let res = res.error_for_status()?;
+			trace!("{}, response: {:?}", name, res);
+			Ok(Fetched {
+				body: res.into_body(),
+				deprecation,
+				etag,
+			})
+		}
+	}
+
+	/// Time unit from the site profile if the request doesn't specify one
+	fn profile_time_unit(&self, site_id: SiteId, requested: Option<TimeUnit>) -> Option<TimeUnit> {
+		if requested.is_some() {
+			return None;
+		}
+		self.profiles.get(&site_id).and_then(|profile| profile.time_unit)
+	}
+
+	/// Meters from the site profile if the request doesn't specify them
+	fn profile_meters(&self, site_id: SiteId, requested: Option<&[MeterType]>) -> Option<&[MeterType]> {
+		if requested.is_some() {
+			return None;
+		}
+		self.profiles.get(&site_id).and_then(|profile| profile.meters.as_deref())
+	}
+
+	fn check_count(&self, name: &str, counted: &impl Counted) {
+		if self.count_check {
+			if let Some(mismatch) = counted.count_mismatch() {
+				warn!("{}, count mismatch, {}", name, mismatch);
+			}
+		}
+	}
+
+	/// [Client::check_count()] for the page of the list reporting the total count, the API returns 100 items by default
+	fn check_page_count(&self, name: &str, counted: &impl Counted, start_index: Option<u32>, size: Option<u32>) {
+		if self.count_check {
+			let start_index = start_index.unwrap_or(0) as usize;
+			let size = size.unwrap_or(MAX_PAGE_SIZE) as usize;
+			if let Some(mismatch) = counted.page_count_mismatch(start_index, size) {
+				warn!("{}, count mismatch, {}", name, mismatch);
+			}
+		}
+	}
+
+	/// Fails if the unit of the bulk response chunk still differs from the `expected` one of the previous chunks after
+	/// the conversion
+	fn check_unit(expected: &str, actual: String) -> Result<(), Error<C::Error>> {
+		if expected == actual {
+			Ok(())
+		} else {
+			Err(Error::UnitMismatch {
+				expected: expected.to_string(),
+				actual,
+			})
+		}
+	}
+
+	/// Converts the URI returned by the API (e.g. `/site/1/details`) to the endpoint path requesting JSON format
+	fn uri_path(uri: &str) -> String {
+		if uri.ends_with(".json") {
+			uri.to_string()
+		} else {
+			format!("{}.json", uri.trim_end_matches('/'))
+		}
+	}
+
+	fn join_site_ids(ids: &[SiteId]) -> String {
 		let mut out = String::with_capacity(ids.len() * 10);
 		let mut first = true;
 		for id in ids {
@@ -113,228 +1110,364 @@ impl<C: HttpClientAdapter> Client<C> {
 
 	/// Return the most updated version number in <major.minor.revision> format.
 	pub async fn version_current(&self) -> Result<String, Error<C::Error>> {
-		let url = self.prepare_url("/version/current.json", ())?;
-		trace!("version_current, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("version_current, response: {:?}", res);
-		let res = serde_json::from_slice::<response::VersionCurrentTop>(res.body())?;
-		Ok(res.version.release)
+		let res: response::VersionCurrentTop = self.perform_request("version_current", "/version/current.json", ()).await?;
+		Ok(self.post_process("version_current", res.version.release))
 	}
 
 	/// Return a list of supported version numbers in <major.minor.revision> format.
 	pub async fn version_supported(&self) -> Result<Vec<response::VersionSpec>, Error<C::Error>> {
-		let url = self.prepare_url("/version/supported.json", ())?;
-		trace!("version_supported, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("version_supported, response: {:?}", res);
-		let res = serde_json::from_slice::<response::VersionSupportedTop>(res.body())?;
-		Ok(res.supported)
+		let res: response::VersionSupportedTop = self
+			.perform_request("version_supported", "/version/supported.json", ())
+			.await?;
+		Ok(self.post_process("version_supported", res.supported))
+	}
+
+	/// Performs the request to an arbitrary endpoint `path` (e.g. `/site/1/details.json`) with `params` serialized into
+	/// the query, returning the raw response body
+	///
+	/// Useful for the endpoints not yet supported by this crate. The API key, the rate limits, the retries and the response
+	/// metadata are handled the same way as for the other functions.
+	///
+	/// # Example
+	/// ```
+	/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+	/// # mod http_adapter_reqwest {
+	/// #    #[derive(Default)]
+	/// #    pub struct ReqwestAdapter;
+	/// #    #[async_trait::async_trait(?Send)]
+	/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+	/// #       type Error = String;
+	/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+	/// #    }
+	/// # }
+	/// async fn run() -> Result<(), solaredge::Error<String>> {
+	///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+	///    let body = client.fetch_raw("/site/1/overview.json", [("systemUnits", "Metrics")]).await?;
+	///    let json: serde_json::Value = serde_json::from_slice(&body).map_err(solaredge::Error::Json)?;
+	///    Ok(())
+	/// }
+	/// ```
+	pub async fn fetch_raw(&self, path: &str, params: impl Serialize) -> Result<Vec<u8>, Error<C::Error>> {
+		let path = format!("/{}", path.trim_start_matches('/'));
+		self.perform_request_raw("fetch_raw", &path, params).await
 	}
 
 	/// Returns a list of sites related to the given token, which is the account api_key
 	pub async fn sites_list(&self, params: &request::SitesList<'_>) -> Result<Vec<response::Site>, Error<C::Error>> {
 		trace!("sites_list, params: {:?}", params);
-		let url = self.prepare_url("/sites/list.json", params)?;
-		trace!("sites_list, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("sites_list, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SitesListTop>(res.body())?;
-		Ok(res.sites.site)
+		params.validate()?;
+		let res: response::SitesListTop = self.perform_request("sites_list", "/sites/list.json", params).await?;
+		self.check_page_count("sites_list", &res.sites, params.start_index, params.size);
+		Ok(self.post_process("sites_list", res.sites.site))
+	}
+
+	/// Returns all sites related to the given token walking the pages of [Client::sites_list()]
+	///
+	/// `params.size` sets the page size, 100 (the API maximum) if not set, `params.start_index` sets the index of the
+	/// first returned site.
+	pub fn sites_list_all<'a>(&'a self, params: &'a request::SitesList<'a>) -> Paginator<'a, response::Site, Error<C::Error>> {
+		trace!("sites_list_all, params: {:?}", params);
+		let page_size = params.size.unwrap_or(MAX_PAGE_SIZE);
+		Paginator::new(params.start_index.unwrap_or(0), page_size, move |start_index, size| {
+			Box::pin(async move {
+				let params = request::SitesList {
+					size: Some(size),
+					start_index: Some(start_index),
+					..*params
+				};
+				let res: response::SitesListTop = self.perform_request("sites_list_all", "/sites/list.json", &params).await?;
+				Ok(Page {
+					items: res.sites.site,
+					total: res.sites.count,
+				})
+			})
+		})
 	}
 
 	/// Displays the site details, such as name, location, status, etc.
-	pub async fn site_details(&self, site_id: u64) -> Result<response::Site, Error<C::Error>> {
+	pub async fn site_details(&self, site_id: impl Into<SiteId>) -> Result<response::Site, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_details, site_id: {}", site_id);
-		let url = self.prepare_url(&format!("/site/{}/details.json", site_id), ())?;
-		trace!("site_details, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_details, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteDetailsTop>(res.body())?;
-		Ok(res.details)
+		let res: response::SiteDetailsTop = self
+			.perform_request("site_details", &format!("/site/{}/details.json", site_id), ())
+			.await?;
+		Ok(self.post_process("site_details", res.details))
 	}
 
 	/// Return the energy production start and end dates of the site.
-	pub async fn site_data_period(&self, site_id: u64) -> Result<response::DataPeriod, Error<C::Error>> {
+	pub async fn site_data_period(&self, site_id: impl Into<SiteId>) -> Result<response::DataPeriod, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_data_period, site_id: {}", site_id);
-		let url = self.prepare_url(&format!("/site/{}/dataPeriod.json", site_id), ())?;
-		trace!("site_data_period, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_data_period, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteDataPeriodTop>(res.body())?;
-		Ok(res.data_period)
+		let res: response::SiteDataPeriodTop = self
+			.perform_request("site_data_period", &format!("/site/{}/dataPeriod.json", site_id), ())
+			.await?;
+		Ok(self.post_process("site_data_period", res.data_period))
 	}
 
 	/// Return the energy production start and end dates of the multiple sites.
-	pub async fn site_data_period_bulk(&self, site_ids: &[u64]) -> Result<Vec<response::DataPeriodBulk>, Error<C::Error>> {
+	///
+	/// More than 100 sites are requested in multiple chunks.
+	pub async fn site_data_period_bulk(
+		&self,
+		site_ids: &[impl Into<SiteId> + Copy],
+	) -> Result<Vec<response::DataPeriodBulk>, Error<C::Error>> {
+		let site_ids = site_ids.iter().map(|site_id| (*site_id).into()).collect::<Vec<SiteId>>();
 		trace!("site_data_period_bulk, site_ids: {:?}", site_ids);
-		let site_ids_str = Self::join_site_ids(site_ids);
-		let url = self.prepare_url(&format!("/sites/{}/dataPeriod.json", site_ids_str), ())?;
-		trace!("site_data_period_bulk, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_data_period_bulk, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteDataPeriodBulkTop>(res.body())?;
-		Ok(res.date_period_list.site_energy_list)
+		let mut out = Vec::with_capacity(site_ids.len());
+		let chunks = bulk_chunks(&site_ids);
+		let mut progress = self.progress("site_data_period_bulk", chunks.len());
+		for chunk in chunks {
+			let site_ids_str = Self::join_site_ids(chunk);
+			let res: response::SiteDataPeriodBulkTop = self
+				.perform_request(
+					"site_data_period_bulk",
+					&format!("/sites/{}/dataPeriod.json", site_ids_str),
+					(),
+				)
+				.await?;
+			self.check_count("site_data_period_bulk", &res.date_period_list);
+			out.extend(res.date_period_list.site_energy_list);
+			progress.advance();
+		}
+		Ok(self.post_process("site_data_period_bulk", out))
 	}
 
 	/// Return the energy production start and end dates of the site.
-	pub async fn site_energy(&self, site_id: u64, params: &request::SiteEnergy) -> Result<response::SiteEnergy, Error<C::Error>> {
+	pub async fn site_energy(
+		&self,
+		site_id: impl Into<SiteId>,
+		params: &request::SiteEnergy,
+	) -> Result<response::SiteEnergy, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_energy, site_id: {}, params: {:?}", site_id, params);
-		let url = self.prepare_url(&format!("/site/{}/energy.json", site_id), params)?;
-		trace!("site_energy, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_energy, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteEnergyTop>(res.body())?;
-		Ok(res.energy)
+		let params = request::WithProfile {
+			params,
+			time_unit: self.profile_time_unit(site_id, params.time_unit),
+			meters: None,
+		};
+		params.validate()?;
+		let res: response::SiteEnergyTop = self
+			.perform_request("site_energy", &format!("/site/{}/energy.json", site_id), params)
+			.await?;
+		Ok(self.post_process("site_energy", res.energy))
+	}
+
+	/// Same as [Client::site_energy()], but the periods longer than the API allows for the time unit are split into
+	/// multiple requests, see [TimeUnit::max_energy_period_months()]
+	///
+	/// The windows are fetched sequentially and their values are concatenated.
+	#[cfg(feature = "chrono")]
+	pub async fn site_energy_full_range(
+		&self,
+		site_id: impl Into<SiteId>,
+		params: &request::SiteEnergy,
+	) -> Result<response::SiteEnergy, Error<C::Error>> {
+		let site_id = site_id.into();
+		trace!("site_energy_full_range, site_id: {}, params: {:?}", site_id, params);
+		let time_unit = params
+			.time_unit
+			.or_else(|| self.profile_time_unit(site_id, None))
+			.unwrap_or(TimeUnit::Day);
+		let Some(months) = time_unit.max_energy_period_months() else {
+			return self.site_energy(site_id, params).await;
+		};
+		let windows = month_windows(params.start_date, params.end_date, months);
+		let mut progress = self.progress("site_energy_full_range", windows.len());
+		let mut out: Option<response::SiteEnergy> = None;
+		for (start_date, end_date) in windows {
+			let window = request::SiteEnergy {
+				start_date,
+				end_date,
+				time_unit: Some(time_unit),
+			};
+			let res = self.site_energy(site_id, &window).await?;
+			progress.advance();
+			match &mut out {
+				Some(out) => out.values.extend(res.values),
+				None => out = Some(res),
+			}
+		}
+		Ok(out.expect("At least one window is always requested"))
 	}
 
 	/// Return the energy production start and end dates of the multiple sites.
+	///
+	/// More than 100 sites are requested in multiple chunks, the results are merged.
 	pub async fn site_energy_bulk(
 		&self,
-		site_ids: &[u64],
+		site_ids: &[impl Into<SiteId> + Copy],
 		params: &request::SiteEnergy,
 	) -> Result<response::SiteEnergyBulkList, Error<C::Error>> {
+		let site_ids = site_ids.iter().map(|site_id| (*site_id).into()).collect::<Vec<SiteId>>();
 		trace!("site_energy_bulk, site_ids: {:?}, params: {:?}", site_ids, params);
-		let site_ids_str = Self::join_site_ids(site_ids);
-		let url = self.prepare_url(&format!("/sites/{}/energy.json", site_ids_str), params)?;
-		trace!("site_energy_bulk, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_energy_bulk, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteEnergyBulkTop>(res.body())?;
-		Ok(res.sites_energy)
+		params.validate()?;
+		let mut out: Option<response::SiteEnergyBulkList> = None;
+		let chunks = bulk_chunks(&site_ids);
+		let mut progress = self.progress("site_energy_bulk", chunks.len());
+		for chunk in chunks {
+			let site_ids_str = Self::join_site_ids(chunk);
+			let res: response::SiteEnergyBulkTop = self
+				.perform_request("site_energy_bulk", &format!("/sites/{}/energy.json", site_ids_str), params)
+				.await?;
+			self.check_count("site_energy_bulk", &res.sites_energy);
+			match &mut out {
+				Some(out) => {
+					let mut chunk = res.sites_energy;
+					if chunk.unit != out.unit {
+						if let Some(unit) = PowerUnit::from_api(&out.unit) {
+							chunk.normalize_unit(unit);
+						}
+						Self::check_unit(&out.unit, chunk.unit)?;
+					}
+					out.count += chunk.count;
+					out.site_energy_list.extend(chunk.site_energy_list);
+				}
+				None => out = Some(res.sites_energy),
+			}
+			progress.advance();
+		}
+		Ok(self.post_process("site_energy_bulk", out.expect("At least one chunk is always requested")))
 	}
 
 	/// Return the site total energy produced for a given period.
 	pub async fn site_time_frame_energy(
 		&self,
-		site_id: u64,
+		site_id: impl Into<SiteId>,
 		params: &request::SiteTotalEnergy,
 	) -> Result<response::SiteTimeframeEnergy, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_time_frame_energy, site_id: {}, params: {:?}", site_id, params);
-		let url = self.prepare_url(&format!("/site/{}/timeFrameEnergy.json", site_id), params)?;
-		trace!("site_time_frame_energy, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_time_frame_energy, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteTimeframeEnergyTop>(res.body())?;
-		Ok(res.timeframe_energy)
+		params.validate()?;
+		let res: response::SiteTimeframeEnergyTop = self
+			.perform_request(
+				"site_time_frame_energy",
+				&format!("/site/{}/timeFrameEnergy.json", site_id),
+				params,
+			)
+			.await?;
+		Ok(self.post_process("site_time_frame_energy", res.timeframe_energy))
 	}
 
 	/// Return the multiple sites total energy produced for a given period.
+	///
+	/// More than 100 sites are requested in multiple chunks.
 	pub async fn site_time_frame_energy_bulk(
 		&self,
-		site_ids: &[u64],
+		site_ids: &[impl Into<SiteId> + Copy],
 		params: &request::SiteTotalEnergy,
 	) -> Result<Vec<response::SiteTimeframeEnergyBulk>, Error<C::Error>> {
+		let site_ids = site_ids.iter().map(|site_id| (*site_id).into()).collect::<Vec<SiteId>>();
 		trace!("site_time_frame_energy_bulk, site_ids: {:?}, params: {:?}", site_ids, params);
-		let site_ids_str = Self::join_site_ids(site_ids);
-		let url = self.prepare_url(&format!("/sites/{}/timeFrameEnergy.json", site_ids_str), params)?;
-		trace!("site_time_frame_energy_bulk, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_time_frame_energy_bulk, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteTimeframeEnergyBulkTop>(res.body())?;
-		Ok(res.timeframe_energy_list.timeframe_energy_list)
+		params.validate()?;
+		let mut out = Vec::with_capacity(site_ids.len());
+		let chunks = bulk_chunks(&site_ids);
+		let mut progress = self.progress("site_time_frame_energy_bulk", chunks.len());
+		for chunk in chunks {
+			let site_ids_str = Self::join_site_ids(chunk);
+			let res: response::SiteTimeframeEnergyBulkTop = self
+				.perform_request(
+					"site_time_frame_energy_bulk",
+					&format!("/sites/{}/timeFrameEnergy.json", site_ids_str),
+					params,
+				)
+				.await?;
+			self.check_count("site_time_frame_energy_bulk", &res.timeframe_energy_list);
+			out.extend(res.timeframe_energy_list.timeframe_energy_list);
+			progress.advance();
+		}
+		Ok(self.post_process("site_time_frame_energy_bulk", out))
 	}
 
 	/// Return the site power measurements in 15 minutes resolution.
-	pub async fn site_power(&self, site_id: u64, params: &request::DateTimeRange) -> Result<response::SitePower, Error<C::Error>> {
+	pub async fn site_power(
+		&self,
+		site_id: impl Into<SiteId>,
+		params: &request::DateTimeRange,
+	) -> Result<response::SitePower, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_power, site_id: {}, params: {:?}", site_id, params);
-		let url = self.prepare_url(&format!("/site/{}/power.json", site_id), params)?;
-		trace!("site_power, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_power, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SitePowerTop>(res.body())?;
-		Ok(res.power)
+		params.validate_max_period(limits::POWER_MAX_PERIOD)?;
+		let res: response::SitePowerTop = self
+			.perform_request("site_power", &format!("/site/{}/power.json", site_id), params)
+			.await?;
+		Ok(self.post_process("site_power", res.power))
 	}
 
 	/// Return the multiple sites power measurements in 15 minutes resolution.
+	///
+	/// More than 100 sites are requested in multiple chunks, the results are merged.
 	pub async fn site_power_bulk(
 		&self,
-		site_ids: &[u64],
+		site_ids: &[impl Into<SiteId> + Copy],
 		params: &request::DateTimeRange,
 	) -> Result<response::SitePowerValueList, Error<C::Error>> {
+		let site_ids = site_ids.iter().map(|site_id| (*site_id).into()).collect::<Vec<SiteId>>();
 		trace!("site_power_bulk, site_ids: {:?}, params: {:?}", site_ids, params);
-		let site_ids_str = Self::join_site_ids(site_ids);
-		let url = self.prepare_url(&format!("/sites/{}/power.json", site_ids_str), params)?;
-		trace!("site_power_bulk, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_power_bulk, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SitePowerBulkTop>(res.body())?;
-		Ok(res.power_date_values_list)
+		params.validate_max_period(limits::POWER_MAX_PERIOD)?;
+		let mut out: Option<response::SitePowerValueList> = None;
+		let chunks = bulk_chunks(&site_ids);
+		let mut progress = self.progress("site_power_bulk", chunks.len());
+		for chunk in chunks {
+			let site_ids_str = Self::join_site_ids(chunk);
+			let res: response::SitePowerBulkTop = self
+				.perform_request("site_power_bulk", &format!("/sites/{}/power.json", site_ids_str), params)
+				.await?;
+			self.check_count("site_power_bulk", &res.power_date_values_list);
+			match &mut out {
+				Some(out) => {
+					let mut chunk = res.power_date_values_list;
+					if chunk.unit != out.unit {
+						if let Some(unit) = PowerUnit::from_api(&out.unit) {
+							chunk.normalize_unit(unit);
+						}
+						Self::check_unit(&out.unit, chunk.unit)?;
+					}
+					out.count += chunk.count;
+					out.site_energy_list.extend(chunk.site_energy_list);
+				}
+				None => out = Some(res.power_date_values_list),
+			}
+			progress.advance();
+		}
+		Ok(self.post_process("site_power_bulk", out.expect("At least one chunk is always requested")))
 	}
 
 	/// Display the site overview data.
-	pub async fn site_overview(&self, site_id: u64) -> Result<response::SiteOverview, Error<C::Error>> {
+	pub async fn site_overview(&self, site_id: impl Into<SiteId>) -> Result<response::SiteOverview, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_overview, site_id: {}", site_id);
-		let url = self.prepare_url(&format!("/site/{}/overview.json", site_id), ())?;
-		trace!("site_overview, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_overview, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteOverviewTop>(res.body())?;
-		Ok(res.overview)
+		let res: response::SiteOverviewTop = self
+			.perform_request("site_overview", &format!("/site/{}/overview.json", site_id), ())
+			.await?;
+		Ok(self.post_process("site_overview", res.overview))
+	}
+
+	/// Displays the site details by following [response::SiteUris::details] of the previously fetched site.
+	pub async fn follow_details(&self, uris: &response::SiteUris) -> Result<response::Site, Error<C::Error>> {
+		trace!("follow_details, uri: {}", uris.details);
+		let res: response::SiteDetailsTop = self
+			.perform_request("follow_details", &Self::uri_path(&uris.details), ())
+			.await?;
+		Ok(self.post_process("follow_details", res.details))
+	}
+
+	/// Return the energy production start and end dates of the site by following [response::SiteUris::data_period] of the
+	/// previously fetched site.
+	pub async fn follow_data_period(&self, uris: &response::SiteUris) -> Result<response::DataPeriod, Error<C::Error>> {
+		trace!("follow_data_period, uri: {}", uris.data_period);
+		let res: response::SiteDataPeriodTop = self
+			.perform_request("follow_data_period", &Self::uri_path(&uris.data_period), ())
+			.await?;
+		Ok(self.post_process("follow_data_period", res.data_period))
+	}
+
+	/// Display the site overview data by following [response::SiteUris::overview] of the previously fetched site.
+	pub async fn follow_overview(&self, uris: &response::SiteUris) -> Result<response::SiteOverview, Error<C::Error>> {
+		trace!("follow_overview, uri: {}", uris.overview);
+		let res: response::SiteOverviewTop = self
+			.perform_request("follow_overview", &Self::uri_path(&uris.overview), ())
+			.await?;
+		Ok(self.post_process("follow_overview", res.overview))
 	}
 
 	// todo site overview bulk
@@ -342,188 +1475,443 @@ impl<C: HttpClientAdapter> Client<C> {
 	/// Detailed site power measurements from meters such as consumption, export (feed-in), import (purchase), etc.
 	pub async fn site_power_details(
 		&self,
-		site_id: u64,
+		site_id: impl Into<SiteId>,
 		params: &request::SitePowerDetails<'_>,
 	) -> Result<response::SiteMetersDetails, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_power_details, site_id: {}, params: {:?}", site_id, params);
-		let url = self.prepare_url(&format!("/site/{}/powerDetails.json", site_id), params)?;
-		trace!("site_power_details, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_power_details, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SitePowerDetailsTop>(res.body())?;
-		Ok(res.power_details)
+		params.validate()?;
+		let res: response::SitePowerDetailsTop = self
+			.perform_request(
+				"site_power_details",
+				&format!("/site/{}/powerDetails.json", site_id),
+				request::WithProfile {
+					params,
+					time_unit: None,
+					meters: self.profile_meters(site_id, params.meters),
+				},
+			)
+			.await?;
+		Ok(self.post_process("site_power_details", res.power_details))
 	}
 
 	/// Detailed site energy measurements from meters such as consumption, export (feed-in), import (purchase), etc.
 	pub async fn site_energy_details(
 		&self,
-		site_id: u64,
+		site_id: impl Into<SiteId>,
 		params: &request::MetersDateTimeRange<'_>,
 	) -> Result<response::SiteMetersDetails, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_energy_details, site_id: {}, params: {:?}", site_id, params);
-		let url = self.prepare_url(&format!("/site/{}/energyDetails.json", site_id), params)?;
-		trace!("site_energy_details, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_energy_details, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteEnergyDetailsTop>(res.body())?;
-		Ok(res.energy_details)
+		let params = request::WithProfile {
+			params,
+			time_unit: self.profile_time_unit(site_id, params.time_unit),
+			meters: self.profile_meters(site_id, params.meters),
+		};
+		params.validate()?;
+		let res: response::SiteEnergyDetailsTop = self
+			.perform_request(
+				"site_energy_details",
+				&format!("/site/{}/energyDetails.json", site_id),
+				params,
+			)
+			.await?;
+		Ok(self.post_process("site_energy_details", res.energy_details))
 	}
 
 	/// Retrieves the current power flow between all elements of the site including PV array, storage (battery), loads (consumption) and grid.
-	pub async fn site_current_power_flow(&self, site_id: u64) -> Result<response::SiteCurrentPowerFlow, Error<C::Error>> {
+	pub async fn site_current_power_flow(
+		&self,
+		site_id: impl Into<SiteId>,
+	) -> Result<response::SiteCurrentPowerFlow, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_current_power_flow, site_id: {}", site_id);
-		let url = self.prepare_url(&format!("/site/{}/currentPowerFlow.json", site_id), ())?;
-		trace!("site_current_power_flow, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_current_power_flow, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteCurrentPowerFlowTop>(res.body())?;
-		Ok(res.site_current_power_flow)
+		let res: response::SiteCurrentPowerFlowTop = self
+			.perform_request(
+				"site_current_power_flow",
+				&format!("/site/{}/currentPowerFlow.json", site_id),
+				(),
+			)
+			.await?;
+		Ok(self.post_process("site_current_power_flow", res.site_current_power_flow))
 	}
 
 	/// Get detailed storage information from batteries: the state of energy, power and lifetime energy.
 	pub async fn site_storage_data(
 		&self,
-		site_id: u64,
+		site_id: impl Into<SiteId>,
 		params: &request::SiteStorageData<'_>,
 	) -> Result<response::SiteStorageData, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_storage_data, site_id: {}, params: {:?}", site_id, params);
-		let url = self.prepare_url(&format!("/site/{}/storageData.json", site_id), params)?;
-		trace!("site_storage_data, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_storage_data, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteStorageDataTop>(res.body())?;
-		Ok(res.storage_data)
+		params.validate()?;
+		let res: response::SiteStorageDataTop = self
+			.perform_request("site_storage_data", &format!("/site/{}/storageData.json", site_id), params)
+			.await?;
+		self.check_count("site_storage_data", &res.storage_data);
+		for battery in &res.storage_data.batteries {
+			self.check_count("site_storage_data", battery);
+		}
+		Ok(self.post_process("site_storage_data", res.storage_data))
+	}
+
+	/// Same as [Client::site_storage_data()], but the periods longer than one week, the API limit, are fetched in multiple
+	/// weekly windows
+	///
+	/// The telemetries of every battery are concatenated, the samples repeated at the window boundaries are removed.
+	#[cfg(feature = "chrono")]
+	pub async fn site_storage_data_range(
+		&self,
+		site_id: impl Into<SiteId>,
+		params: &request::SiteStorageData<'_>,
+	) -> Result<response::SiteStorageData, Error<C::Error>> {
+		let site_id = site_id.into();
+		trace!("site_storage_data_range, site_id: {}, params: {:?}", site_id, params);
+		let mut out: Option<response::SiteStorageData> = None;
+		let windows = week_windows(params.start_time, params.end_time);
+		let mut progress = self.progress("site_storage_data_range", windows.len());
+		for (start_time, end_time) in windows {
+			let window = request::SiteStorageData {
+				start_time,
+				end_time,
+				serials: params.serials,
+			};
+			let res = self.site_storage_data(site_id, &window).await?;
+			progress.advance();
+			let Some(out) = &mut out else {
+				out = Some(res);
+				continue;
+			};
+			for battery in res.batteries {
+				match out
+					.batteries
+					.iter_mut()
+					.find(|existing| existing.serial_number == battery.serial_number)
+				{
+					Some(existing) => existing.telemetries.extend(battery.telemetries),
+					None => out.batteries.push(battery),
+				}
+			}
+		}
+		let mut out = out.expect("At least one window is always requested");
+		for battery in &mut out.batteries {
+			crate::series::dedup_by_timestamp(&mut battery.telemetries);
+			battery.telemetry_count = battery.telemetries.len();
+		}
+		out.battery_count = out.batteries.len();
+		Ok(out)
 	}
 
-	// todo site image
+	/// Returns the site image as uploaded to the server, scaled down to fit `params.max_width` and `params.max_height`
+	///
+	/// `name` is the file name from the `SITE_IMAGE` URI of the site, e.g. `site.jpg`. When `params.hash` is the hash of
+	/// the current image the API responds with HTTP 304 and [ImageResult::NotModified](response::ImageResult) is returned,
+	/// so passing the hash of the previously received [ImageResult::Image](response::ImageResult) downloads the image only
+	/// when it changes.
+	pub async fn site_image(
+		&self,
+		site_id: impl Into<SiteId>,
+		name: &str,
+		params: &request::SiteImage<'_>,
+	) -> Result<response::ImageResult, Error<C::Error>> {
+		let site_id = site_id.into();
+		trace!("site_image, site_id: {}, name: {}, params: {:?}", site_id, name, params);
+		let path = format!("/site/{}/siteImage/{}", site_id, self.path_encoding.encode(name));
+		match self.send_request("site_image", &path, params).await {
+			Ok(fetched) => Ok(response::ImageResult::Image(fetched.body, fetched.etag)),
+			Err(Error::Api(StatusCode::NOT_MODIFIED, _)) => Ok(response::ImageResult::NotModified),
+			Err(e) => Err(self.record_error("site_image", e)),
+		}
+	}
 
 	/// Returns all environmental benefits based on site energy production: CO2 emissions saved, equivalent trees planted, and light bulbs powered for a day.
 	pub async fn site_env_benefits(
 		&self,
-		site_id: u64,
+		site_id: impl Into<SiteId>,
 		params: &request::SiteEnvBenefits,
 	) -> Result<response::SiteEnvBenefits, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_env_benefits, site_id: {}, params: {:?}", site_id, params);
-		let url = self.prepare_url(&format!("/site/{}/envBenefits.json", site_id), params)?;
-		trace!("site_env_benefits, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_env_benefits, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteEnvBenefitsTop>(res.body())?;
-		Ok(res.env_benefits)
+		let res: response::SiteEnvBenefitsTop = self
+			.perform_request("site_env_benefits", &format!("/site/{}/envBenefits.json", site_id), params)
+			.await?;
+		Ok(self.post_process("site_env_benefits", res.env_benefits))
 	}
 
 	// todo site installer logo image
 
 	/// Return the inventory of SolarEdge equipment in the site, including inverters/SMIs, batteries, meters, gateways and sensors.
-	pub async fn site_inventory(&self, site_id: u64) -> Result<response::SiteInventory, Error<C::Error>> {
+	pub async fn site_inventory(&self, site_id: impl Into<SiteId>) -> Result<response::SiteInventory, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_inventory, site_id: {}", site_id);
-		let url = self.prepare_url(&format!("/site/{}/inventory.json", site_id), ())?;
-		trace!("site_inventory, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_inventory, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteInventoryTop>(res.body())?;
-		Ok(res.inventory)
+		let res: response::SiteInventoryTop = self
+			.perform_request("site_inventory", &format!("/site/{}/inventory.json", site_id), ())
+			.await?;
+		Ok(self.post_process("site_inventory", res.inventory))
 	}
 
 	/// Returns for each meter on site its lifetime energy reading, metadata and the device to which it’s connected to.
 	pub async fn site_meters(
 		&self,
-		site_id: u64,
+		site_id: impl Into<SiteId>,
 		params: &request::MetersDateTimeRange<'_>,
 	) -> Result<response::SiteMeters, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("site_meters, site_id: {}, params: {:?}", site_id, params);
-		let url = self.prepare_url(&format!("/site/{}/meters.json", site_id), params)?;
-		trace!("site_meters, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("site_meters, response: {:?}", res);
-		let res = serde_json::from_slice::<response::SiteMetersTop>(res.body())?;
-		Ok(res.meter_energy_details)
+		let params = request::WithProfile {
+			params,
+			time_unit: self.profile_time_unit(site_id, params.time_unit),
+			meters: self.profile_meters(site_id, params.meters),
+		};
+		params.validate()?;
+		let res: response::SiteMetersTop = self
+			.perform_request("site_meters", &format!("/site/{}/meters.json", site_id), params)
+			.await?;
+		Ok(self.post_process("site_meters", res.meter_energy_details))
 	}
 
 	/// Return a list of inverters/SMIs in the specific site.
-	pub async fn equipment_list(&self, site_id: u64) -> Result<Vec<response::Equipment>, Error<C::Error>> {
+	pub async fn equipment_list(&self, site_id: impl Into<SiteId>) -> Result<Vec<response::Equipment>, Error<C::Error>> {
+		let site_id = site_id.into();
 		trace!("equipment_list, site_id: {}", site_id);
-		let url = self.prepare_url(&format!("/equipment/{}/list.json", site_id), ())?;
-		trace!("equipment_list, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
-			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("equipment_list, response: {:?}", res);
-		let res = serde_json::from_slice::<response::EquipmentListTop>(res.body())?;
-		Ok(res.reporters.list)
+		let res: response::EquipmentListTop = self
+			.perform_request("equipment_list", &format!("/equipment/{}/list.json", site_id), ())
+			.await?;
+		self.check_count("equipment_list", &res.reporters);
+		Ok(self.post_process("equipment_list", res.reporters.list))
 	}
 
 	/// Return specific inverter data for a given timeframe.
 	pub async fn equipment_data(
 		&self,
-		site_id: u64,
-		serial_number: &str,
+		site_id: impl Into<SiteId>,
+		serial_number: impl Into<SerialNumber>,
 		params: &request::DateTimeRange,
 	) -> Result<Vec<response::EquipmentTelemetry>, Error<C::Error>> {
+		let site_id = site_id.into();
+		let serial_number: SerialNumber = serial_number.into();
 		trace!("equipment_data, site_id: {}, params: {:?}", site_id, params);
-		let serial_number = utf8_percent_encode(serial_number, NON_ALPHANUMERIC);
-		let url = self.prepare_url(&format!("/equipment/{}/{}/data.json", site_id, serial_number), params)?;
-		trace!("equipment_data, url: {}", url);
-		let res = self
-			.client
-			.execute(Self::request_get(url))
+		params.validate_max_period(limits::TELEMETRY_MAX_PERIOD)?;
+		let serial_number = self.path_encoding.encode(&serial_number);
+		let res: response::EquipmentDataTop = self
+			.perform_request(
+				"equipment_data",
+				&format!("/equipment/{}/{}/data.json", site_id, serial_number),
+				params,
+			)
+			.await?;
+		self.check_count("equipment_data", &res.data);
+		Ok(self.post_process("equipment_data", res.data.telemetries))
+	}
+
+	/// Same as [Client::equipment_data()], but the periods longer than one week, the API limit, are fetched in multiple
+	/// weekly windows
+	///
+	/// The telemetries are concatenated, the samples repeated at the window boundaries are removed.
+	#[cfg(feature = "chrono")]
+	pub async fn equipment_data_range(
+		&self,
+		site_id: impl Into<SiteId>,
+		serial_number: impl Into<SerialNumber>,
+		params: &request::DateTimeRange,
+	) -> Result<Vec<response::EquipmentTelemetry>, Error<C::Error>> {
+		let site_id = site_id.into();
+		let serial_number: SerialNumber = serial_number.into();
+		trace!("equipment_data_range, site_id: {}, params: {:?}", site_id, params);
+		let mut out = vec![];
+		let windows = week_windows(params.start_time, params.end_time);
+		let mut progress = self.progress("equipment_data_range", windows.len());
+		for (start_time, end_time) in windows {
+			let window = request::DateTimeRange { start_time, end_time };
+			out.extend(self.equipment_data(site_id, &serial_number, &window).await?);
+			progress.advance();
+		}
+		crate::series::dedup_by_timestamp(&mut out);
+		Ok(out)
+	}
+
+	/// Returns the telemetries of all inverters of the site `site_id` listed by [Client::equipment_list()] keyed by
+	/// their serial number
+	///
+	/// The telemetries of the inverters are fetched with [Client::equipment_data_range()], at most 3 inverters, the
+	/// concurrency limit of the API, at the same time. The first failure fails the whole call.
+	#[cfg(feature = "chrono")]
+	pub async fn equipment_data_all(
+		&self,
+		site_id: impl Into<SiteId>,
+		params: &request::DateTimeRange,
+	) -> Result<HashMap<SerialNumber, Vec<response::EquipmentTelemetry>>, Error<C::Error>> {
+		let site_id = site_id.into();
+		trace!("equipment_data_all, site_id: {}, params: {:?}", site_id, params);
+		let equipment = self.equipment_list(site_id).await?;
+		let requests = equipment.into_iter().map(|equipment| async move {
+			let telemetries = self.equipment_data_range(site_id, &equipment.serial_number, params).await?;
+			Ok((equipment.serial_number, telemetries))
+		});
+		join_bounded(requests, limits::MAX_CONCURRENT_REQUESTS)
 			.await
-			.map_err(Error::HttpRequest)?
-			.error_for_status()?;
-		trace!("equipment_data, response: {:?}", res);
-		let res = serde_json::from_slice::<response::EquipmentDataTop>(res.body())?;
-		Ok(res.data.telemetries)
+			.into_iter()
+			.collect()
 	}
 
-	// todo equipment changelog
-	// todo account list api
-	// todo sensors api
+	/// Fetches the current state of the site `site_id` for the dashboard refresh
+	///
+	/// The overview, the current power flow and the environmental benefits are fetched concurrently. If the power flow
+	/// reports the storage, its data for the hour before [SiteOverview::last_update_time](response::SiteOverview) is
+	/// fetched afterwards. The first failure fails the whole call.
+	#[cfg(feature = "chrono")]
+	pub async fn site_snapshot(&self, site_id: impl Into<SiteId>) -> Result<SiteSnapshot, Error<C::Error>>
+	where
+		C::Error: 'static,
+	{
+		let site_id = site_id.into();
+		trace!("site_snapshot, site_id: {}", site_id);
+		let mut batch = self.batch();
+		let overview = batch.add(|c| c.site_overview(site_id));
+		let power_flow = batch.add(|c| c.site_current_power_flow(site_id));
+		let env_benefits = batch.add(|c| async move {
+			c.site_env_benefits(site_id, &request::SiteEnvBenefits { system_units: None })
+				.await
+		});
+		let mut results = batch.execute().await;
+		let overview = results.take(overview).expect("Taken once")?;
+		let power_flow = results.take(power_flow).expect("Taken once")?;
+		let env_benefits = results.take(env_benefits).expect("Taken once")?;
+		let storage = if power_flow.storage.is_some() {
+			let end_time = overview.last_update_time;
+			let params = request::SiteStorageData {
+				start_time: end_time - chrono::Duration::hours(1),
+				end_time,
+				serials: None,
+			};
+			Some(self.site_storage_data(site_id, &params).await?)
+		} else {
+			None
+		};
+		Ok(SiteSnapshot {
+			overview,
+			power_flow,
+			storage,
+			env_benefits,
+		})
+	}
+
+	/// Returns a list of equipment component replacements ordered by date. This method is applicable to inverters,
+	/// optimizers, batteries and gateways.
+	pub async fn equipment_changelog(
+		&self,
+		site_id: impl Into<SiteId>,
+		serial_number: impl Into<SerialNumber>,
+	) -> Result<Vec<response::EquipmentChange>, Error<C::Error>> {
+		let site_id = site_id.into();
+		let serial_number: SerialNumber = serial_number.into();
+		trace!("equipment_changelog, site_id: {}, serial_number: {}", site_id, serial_number);
+		let serial_number = self.path_encoding.encode(&serial_number);
+		let res: response::EquipmentChangeLogTop = self
+			.perform_request(
+				"equipment_changelog",
+				&format!("/equipment/{}/{}/changeLog.json", site_id, serial_number),
+				(),
+			)
+			.await?;
+		self.check_count("equipment_changelog", &res.change_log);
+		Ok(self.post_process("equipment_changelog", res.change_log.list))
+	}
+
+	/// Return the account and list of sub-accounts related to the given token.
+	pub async fn accounts_list(&self, params: &request::AccountsList<'_>) -> Result<Vec<response::Account>, Error<C::Error>> {
+		trace!("accounts_list, params: {:?}", params);
+		params.validate()?;
+		let res: response::AccountsListTop = self.perform_request("accounts_list", "/accounts/list.json", params).await?;
+		self.check_page_count("accounts_list", &res.accounts, params.start_index, params.size);
+		Ok(self.post_process("accounts_list", res.accounts.list))
+	}
+
+	/// Returns the account and all sub-accounts related to the given token walking the pages of [Client::accounts_list()]
+	///
+	/// `params.size` sets the page size, 100 (the API maximum) if not set, `params.start_index` sets the index of the
+	/// first returned account. Use [Paginator::try_collect()] to get all accounts in a [Vec].
+	pub fn accounts_list_all<'a>(
+		&'a self,
+		params: &'a request::AccountsList<'a>,
+	) -> Paginator<'a, response::Account, Error<C::Error>> {
+		trace!("accounts_list_all, params: {:?}", params);
+		let page_size = params.size.unwrap_or(MAX_PAGE_SIZE);
+		Paginator::new(params.start_index.unwrap_or(0), page_size, move |start_index, size| {
+			Box::pin(async move {
+				let params = request::AccountsList {
+					size: Some(size),
+					start_index: Some(start_index),
+					..*params
+				};
+				let res: response::AccountsListTop = self
+					.perform_request("accounts_list_all", "/accounts/list.json", &params)
+					.await?;
+				Ok(Page {
+					items: res.accounts.list,
+					total: res.accounts.count,
+				})
+			})
+		})
+	}
+
+	/// Returns a list of all the sensors in the site, and the device to which they are connected.
+	pub async fn equipment_sensors(&self, site_id: impl Into<SiteId>) -> Result<Vec<response::GatewaySensors>, Error<C::Error>> {
+		let site_id = site_id.into();
+		trace!("equipment_sensors, site_id: {}", site_id);
+		let res: response::EquipmentSensorsTop = self
+			.perform_request("equipment_sensors", &format!("/equipment/{}/sensors.json", site_id), ())
+			.await?;
+		self.check_count("equipment_sensors", &res.site_sensors);
+		for gateway in &res.site_sensors.list {
+			self.check_count("equipment_sensors", gateway);
+		}
+		Ok(self.post_process("equipment_sensors", res.site_sensors.list))
+	}
+
+	/// Returns the data of all the sensors in the site, by the gateway they are connected to.
+	pub async fn site_sensor_data(
+		&self,
+		site_id: impl Into<SiteId>,
+		params: &request::SiteSensorData,
+	) -> Result<Vec<response::GatewaySensorData>, Error<C::Error>> {
+		let site_id = site_id.into();
+		trace!("site_sensor_data, site_id: {}, params: {:?}", site_id, params);
+		params.validate()?;
+		let res: response::SiteSensorDataTop = self
+			.perform_request("site_sensor_data", &format!("/site/{}/sensors.json", site_id), params)
+			.await?;
+		for gateway in &res.site_sensors.data {
+			self.check_count("site_sensor_data", gateway);
+		}
+		Ok(self.post_process("site_sensor_data", res.site_sensors.data))
+	}
 }
 
 impl<C: Clone> Clone for Client<C> {
 	fn clone(&self) -> Self {
 		Self {
 			client: self.client.clone(),
+			environment: self.environment.clone(),
 			base_url: self.base_url.clone(),
 			api_key: self.api_key.clone(),
+			count_check: self.count_check,
+			profiles: self.profiles.clone(),
+			rate_limiter: self.rate_limiter.clone(),
+			retry: self.retry.clone(),
+			api_version: self.api_version.clone(),
+			meta_recorder: self.meta_recorder.clone(),
+			audit_sink: self.audit_sink.clone(),
+			raw_recorder: self.raw_recorder.clone(),
+			error_collector: self.error_collector.clone(),
+			skipped_recorder: self.skipped_recorder.clone(),
+			progress_sink: self.progress_sink.clone(),
+			middlewares: self.middlewares.clone(),
+			path_encoding: self.path_encoding,
+			quota: Arc::clone(&self.quota),
+			cache: self.cache.clone(),
+			priority: self.priority,
+			post_processors: self.post_processors.clone(),
 		}
 	}
 }
@@ -532,12 +1920,28 @@ impl<C: fmt::Debug> fmt::Debug for Client<C> {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		f.debug_struct("Client")
 			.field("client", &self.client)
+			.field("environment", &self.environment)
 			.field("base_url", &self.base_url)
 			.field("api_key", &"<hidden>")
+			.field("count_check", &self.count_check)
+			.field("profiles", &self.profiles)
+			.field("rate_limiter", &self.rate_limiter)
+			.field("retry", &self.retry)
+			.field("api_version", &self.api_version)
+			.field("path_encoding", &self.path_encoding)
+			.field("cache", &self.cache)
 			.finish()
 	}
 }
 
+/// Response to a single request attempt
+struct Fetched {
+	body: Vec<u8>,
+	deprecation: Option<Deprecation>,
+	/// `ETag` header, the hash of the image returned by [Client::site_image()]
+	etag: Option<String>,
+}
+
 trait ResponseExt: Sized {
 	fn error_for_status<E>(self) -> Result<Self, Error<E>>;
 }
@@ -545,10 +1949,66 @@ trait ResponseExt: Sized {
 impl ResponseExt for Response<Vec<u8>> {
 	fn error_for_status<E>(self) -> Result<Self, Error<E>> {
 		let status = self.status();
-		if status.is_client_error() || status.is_server_error() {
+		if status == StatusCode::TOO_MANY_REQUESTS {
+			let retry_after = self
+				.headers()
+				.get(header::RETRY_AFTER)
+				.and_then(|value| value.to_str().ok())
+				.and_then(|value| parse_retry_after(value, SystemTime::now()));
+			Err(Error::RateLimited {
+				retry_after,
+				body: self.into_body(),
+			})
+		} else if status == StatusCode::NOT_MODIFIED || status.is_client_error() || status.is_server_error() {
+			// 304 is only returned to the conditional requests, see Client::site_image()
 			Err(Error::Api(status, self.into_body()))
 		} else {
 			Ok(self)
 		}
 	}
 }
+
+/// Splits the period into the consecutive non-overlapping windows of at most `months` months, both ends of the window are
+/// included, at least one window is returned
+#[cfg(feature = "chrono")]
+pub(crate) fn month_windows(start_date: Date, end_date: Date, months: u32) -> Vec<(Date, Date)> {
+	let mut out = vec![];
+	let mut window_start = start_date;
+	loop {
+		let window_end = window_start
+			.checked_add_months(chrono::Months::new(months))
+			.and_then(|next_start| next_start.pred_opt())
+			.map_or(end_date, |window_end| window_end.min(end_date));
+		out.push((window_start, window_end));
+		match window_end.succ_opt() {
+			Some(next_start) if next_start <= end_date => window_start = next_start,
+			_ => break out,
+		}
+	}
+}
+
+/// Splits the period into the consecutive windows of at most one week, at least one window is returned
+#[cfg(feature = "chrono")]
+pub(crate) fn week_windows(start_time: DateTime, end_time: DateTime) -> Vec<(DateTime, DateTime)> {
+	let mut out = vec![];
+	let mut window_start = start_time;
+	loop {
+		let window_end = window_start
+			.checked_add_signed(chrono::Duration::days(i64::from(limits::TELEMETRY_MAX_DAYS)))
+			.map_or(end_time, |week_end| week_end.min(end_time));
+		out.push((window_start, window_end));
+		if window_end >= end_time {
+			break out;
+		}
+		window_start = window_end;
+	}
+}
+
+/// Splits the site ids into the chunks accepted by the bulk endpoints, at least one chunk is returned
+pub(crate) fn bulk_chunks(site_ids: &[SiteId]) -> Vec<&[SiteId]> {
+	if site_ids.is_empty() {
+		vec![site_ids]
+	} else {
+		site_ids.chunks(limits::MAX_BULK_SITES).collect()
+	}
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http_adapter::Response;
+
+use crate::error::parse_http_date;
+use crate::ApiErrorBody;
+
+/// Advance warning that the endpoint is deprecated, detected in the API response
+///
+/// The notice is detected from the `Deprecation` (RFC 9745) and `Sunset` (RFC 8594) headers of any response and from the
+/// message of the error response mentioning the deprecation. The client logs it as a warning for every response and
+/// records it in [ResponseMeta::deprecation](crate::ResponseMeta::deprecation) of the successful responses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deprecation {
+	/// Value of the `Deprecation` header, e.g. `@1688169599` for the date since which the endpoint is deprecated
+	pub deprecated: Option<String>,
+	/// Value of the `Sunset` header, the HTTP date after which the endpoint stops working, see [Deprecation::sunset_at()]
+	pub sunset: Option<String>,
+	/// Message of the error response that mentions the deprecation
+	pub message: Option<String>,
+}
+
+impl Deprecation {
+	/// Detects the deprecation notice in the response, `None` if there is none
+	pub(crate) fn from_response(res: &Response<Vec<u8>>) -> Option<Self> {
+		let header = |name| {
+			res.headers()
+				.get(name)
+				.and_then(|value| value.to_str().ok())
+				.map(|value| value.trim().to_string())
+		};
+		let message = if res.status().is_client_error() || res.status().is_server_error() {
+			ApiErrorBody::parse(res.body())
+				.map(|body| body.message)
+				.filter(|message| message.to_lowercase().contains("deprecat"))
+		} else {
+			None
+		};
+		let out = Self {
+			deprecated: header("deprecation"),
+			sunset: header("sunset"),
+			message,
+		};
+		if out.deprecated.is_some() || out.sunset.is_some() || out.message.is_some() {
+			Some(out)
+		} else {
+			None
+		}
+	}
+
+	/// Time after which the endpoint stops working, `None` if the `Sunset` header is missing or malformed
+	pub fn sunset_at(&self) -> Option<SystemTime> {
+		let secs = parse_http_date(self.sunset.as_deref()?.trim())?;
+		Some(UNIX_EPOCH + Duration::from_secs(secs))
+	}
+}
+
+impl fmt::Display for Deprecation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("endpoint is deprecated")?;
+		if let Some(deprecated) = &self.deprecated {
+			write!(f, ", deprecation: {deprecated}")?;
+		}
+		if let Some(sunset) = &self.sunset {
+			write!(f, ", sunset: {sunset}")?;
+		}
+		if let Some(message) = &self.message {
+			write!(f, ", message: {message}")?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,402 @@
+//! Tools for troubleshooting mismatches between the API responses and the types of this crate
+
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{DeserializeOwned, DeserializeSeed, Deserializer, Error as _, MapAccess, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::Value;
+
+use crate::{response, SiteSortBy, SortOrder};
+
+/// Single discrepancy between the raw response and the target type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DriftFinding {
+	/// Response contains a field that the target type doesn't know about
+	UnknownField { path: String },
+	/// Response contains `null` in the place where the target type doesn't expect it
+	UnexpectedNull { path: String },
+	/// Value in the response can't be converted to the target type, also reported for missing fields
+	Mismatch { path: String, message: String },
+}
+
+impl Display for DriftFinding {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		match self {
+			DriftFinding::UnknownField { path } => write!(f, "unknown field: {path}"),
+			DriftFinding::UnexpectedNull { path } => write!(f, "unexpected null: {path}"),
+			DriftFinding::Mismatch { path, message } => write!(f, "mismatch at {path}: {message}"),
+		}
+	}
+}
+
+/// Result of [detect_drift()]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DriftReport {
+	pub findings: Vec<DriftFinding>,
+	/// `true` if the response was successfully parsed into the target type despite the findings
+	pub parsed: bool,
+}
+
+impl DriftReport {
+	/// `true` if the response matches the target type exactly
+	pub fn is_clean(&self) -> bool {
+		self.parsed && self.findings.is_empty()
+	}
+}
+
+impl Display for DriftReport {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		if self.is_clean() {
+			return f.write_str("no drift detected");
+		}
+		let status = if self.parsed {
+			"parsed with drift"
+		} else {
+			"parse failed"
+		};
+		f.write_str(status)?;
+		for finding in &self.findings {
+			write!(f, "\n  {finding}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Parses the raw response `body` into `T` and reports every discrepancy found on the way
+///
+/// Unknown fields are collected for the whole response. Parsing stops at the first fatal problem (unexpected `null`,
+/// mismatched type or missing field) which is then reported with the JSON path where it happened. Returns an error only
+/// if `body` is not a valid JSON.
+///
+/// # Example
+/// ```
+/// use solaredge::diagnostics::{detect_drift, DriftFinding};
+/// use solaredge::response::VersionCurrentTop;
+///
+/// let report = detect_drift::<VersionCurrentTop>(br#"{"version": {"release": "1.0.0", "build": 5}}"#).unwrap();
+/// assert_eq!(
+///    vec![DriftFinding::UnknownField { path: "$.version.build".to_string() }],
+///    report.findings
+/// );
+/// ```
+pub fn detect_drift<T: DeserializeOwned>(body: &[u8]) -> Result<DriftReport, serde_json::Error> {
+	let value = serde_json::from_slice::<Value>(body)?;
+	let state = RefCell::new(State::default());
+	let res = T::deserialize(Tracker {
+		value: &value,
+		path: "$".to_string(),
+		state: &state,
+	});
+	let mut state = state.into_inner();
+	if let Err(e) = res {
+		state.fail(&value, "$".to_string(), &e);
+	}
+	Ok(DriftReport {
+		findings: state.findings,
+		parsed: !state.failed,
+	})
+}
+
+#[derive(Default)]
+struct State {
+	findings: Vec<DriftFinding>,
+	failed: bool,
+}
+
+impl State {
+	/// Records the failure at the deepest level only, the same error is then propagated through all the parents
+	fn fail(&mut self, value: &Value, path: String, e: &serde_json::Error) {
+		if !self.failed {
+			self.failed = true;
+			self.findings.push(if value.is_null() {
+				DriftFinding::UnexpectedNull { path }
+			} else {
+				DriftFinding::Mismatch {
+					path,
+					message: e.to_string(),
+				}
+			});
+		}
+	}
+}
+
+struct Tracker<'a> {
+	value: &'a Value,
+	path: String,
+	state: &'a RefCell<State>,
+}
+
+impl<'a> Tracker<'a> {
+	fn child(&self, value: &'a Value, path: String) -> Self {
+		Self {
+			value,
+			path,
+			state: self.state,
+		}
+	}
+
+	/// Unknown fields are skipped when `known_fields` are passed so that the parsing can continue for the types with
+	/// `deny_unknown_fields`, they are already reported by that time
+	fn visit_object<V: Visitor<'a>>(
+		self,
+		known_fields: Option<&'static [&'static str]>,
+		visitor: V,
+	) -> Result<V::Value, serde_json::Error> {
+		match self.value {
+			Value::Object(fields) => visitor.visit_map(TrackerMap {
+				parent: self,
+				fields: fields.iter(),
+				known_fields,
+				value: None,
+			}),
+			value => value.deserialize_map(visitor),
+		}
+	}
+}
+
+impl<'a> Deserializer<'a> for Tracker<'a> {
+	type Error = serde_json::Error;
+
+	fn deserialize_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		match self.value {
+			Value::Object(_) => self.deserialize_map(visitor),
+			Value::Array(_) => self.deserialize_seq(visitor),
+			value => value.deserialize_any(visitor),
+		}
+	}
+
+	fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		if self.value.is_null() {
+			visitor.visit_none()
+		} else {
+			visitor.visit_some(self)
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'a>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_seq<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		match self.value {
+			Value::Array(items) => visitor.visit_seq(TrackerSeq {
+				parent: self,
+				items: items.iter().enumerate(),
+			}),
+			value => value.deserialize_seq(visitor),
+		}
+	}
+
+	fn deserialize_tuple<V: Visitor<'a>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'a>>(
+		self,
+		_name: &'static str,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_map<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		self.visit_object(None, visitor)
+	}
+
+	fn deserialize_struct<V: Visitor<'a>>(
+		self,
+		_name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		if let Value::Object(object) = self.value {
+			let mut state = self.state.borrow_mut();
+			for key in object.keys() {
+				if !fields.contains(&key.as_str()) {
+					state.findings.push(DriftFinding::UnknownField {
+						path: format!("{}.{key}", self.path),
+					});
+				}
+			}
+		}
+		self.visit_object(Some(fields), visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'a>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		self.value.deserialize_enum(name, variants, visitor)
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		visitor.visit_unit()
+	}
+
+	forward_to_deserialize_any! {
+		<W: Visitor<'a>>
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct identifier
+	}
+}
+
+struct TrackerSeq<'a> {
+	parent: Tracker<'a>,
+	items: std::iter::Enumerate<std::slice::Iter<'a, Value>>,
+}
+
+impl<'a> SeqAccess<'a> for TrackerSeq<'a> {
+	type Error = serde_json::Error;
+
+	fn next_element_seed<T: DeserializeSeed<'a>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+		let Some((i, value)) = self.items.next() else {
+			return Ok(None);
+		};
+		let path = format!("{}[{i}]", self.parent.path);
+		seed
+			.deserialize(self.parent.child(value, path.clone()))
+			.map(Some)
+			.map_err(|e| {
+				self.parent.state.borrow_mut().fail(value, path, &e);
+				e
+			})
+	}
+}
+
+struct TrackerMap<'a> {
+	parent: Tracker<'a>,
+	fields: serde_json::map::Iter<'a>,
+	known_fields: Option<&'static [&'static str]>,
+	value: Option<(&'a String, &'a Value)>,
+}
+
+impl<'a> MapAccess<'a> for TrackerMap<'a> {
+	type Error = serde_json::Error;
+
+	fn next_key_seed<K: DeserializeSeed<'a>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+		let known_fields = self.known_fields;
+		let is_known = |(key, _): &(&String, &Value)| known_fields.map_or(true, |known| known.contains(&key.as_str()));
+		let Some((key, value)) = self.fields.find(is_known) else {
+			return Ok(None);
+		};
+		self.value = Some((key, value));
+		seed.deserialize(BorrowedStrDeserializer::new(key)).map(Some)
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'a>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+		let (key, value) = self
+			.value
+			.take()
+			.ok_or_else(|| serde_json::Error::custom("value requested before key"))?;
+		let path = format!("{}.{key}", self.parent.path);
+		seed.deserialize(self.parent.child(value, path.clone())).map_err(|e| {
+			self.parent.state.borrow_mut().fail(value, path, &e);
+			e
+		})
+	}
+}
+
+/// Returns the index of the first site that is out of order for the given sort, see [response::Site::cmp_by()]
+///
+/// `None` means that `sites` are sorted as requested, can be used to check the results of
+/// [Client::sites_list()](crate::Client::sites_list) with [SitesList::sort_property](crate::SitesList::sort_property).
+/// The API sorts in ascending order when no `sort_order` is specified.
+pub fn find_unsorted_site(sites: &[response::Site], sort_by: SiteSortBy, sort_order: SortOrder) -> Option<usize> {
+	sites
+		.windows(2)
+		.position(|pair| {
+			let ordering = pair[0].cmp_by(&pair[1], sort_by);
+			match sort_order {
+				SortOrder::Ascending => ordering.is_gt(),
+				SortOrder::Descending => ordering.is_lt(),
+			}
+		})
+		.map(|i| i + 1)
+}
+
+/// Disagreement between the number of items reported by the API and the number of items actually received
+///
+/// Usually it means that the response was truncated or that there are more pages to fetch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CountMismatch {
+	pub reported: usize,
+	pub actual: usize,
+}
+
+impl Display for CountMismatch {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		write!(f, "reported count: {}, actual count: {}", self.reported, self.actual)
+	}
+}
+
+/// Response that carries the item count alongside the items
+pub trait Counted {
+	/// Count as reported by the API
+	fn reported_count(&self) -> usize;
+
+	/// Number of the received items
+	fn actual_count(&self) -> usize;
+
+	/// Returns `Some` if the reported count doesn't match the number of the received items
+	fn count_mismatch(&self) -> Option<CountMismatch> {
+		let reported = self.reported_count();
+		let actual = self.actual_count();
+		(reported != actual).then_some(CountMismatch { reported, actual })
+	}
+
+	/// Returns `Some` if the page of at most `size` items starting at `start_index` doesn't hold all the items that fall
+	/// into it, for the paged lists reporting the total count across all pages, e.g.
+	/// [Client::sites_list()](crate::Client::sites_list). [CountMismatch::reported] is then the expected page length.
+	fn page_count_mismatch(&self, start_index: usize, size: usize) -> Option<CountMismatch> {
+		let reported = self.reported_count().saturating_sub(start_index).min(size);
+		let actual = self.actual_count();
+		(reported != actual).then_some(CountMismatch { reported, actual })
+	}
+}
+
+macro_rules! impl_counted {
+	($($typ: ty => $count: ident, $list: ident;)+) => {
+		$(
+			impl Counted for $typ {
+				#[inline]
+				fn reported_count(&self) -> usize {
+					self.$count
+				}
+
+				#[inline]
+				fn actual_count(&self) -> usize {
+					self.$list.len()
+				}
+			}
+		)+
+	};
+}
+
+impl<T> Counted for response::List<T> {
+	#[inline]
+	fn reported_count(&self) -> usize {
+		self.count
+	}
+
+	#[inline]
+	fn actual_count(&self) -> usize {
+		self.list.len()
+	}
+}
+
+impl_counted! {
+	response::SitesListSites => count, site;
+	response::DataPeriodBulkList => count, site_energy_list;
+	response::SiteEnergyBulkList => count, site_energy_list;
+	response::SiteTimeframeEnergyList => count, timeframe_energy_list;
+	response::SitePowerValueList => count, site_energy_list;
+	response::StorageBattery => telemetry_count, telemetries;
+	response::SiteStorageData => battery_count, batteries;
+	response::EquipmentData => count, telemetries;
+	response::GatewaySensors => count, sensors;
+	response::GatewaySensorData => count, telemetries;
+}
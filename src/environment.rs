@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use url::Url;
+
+const PRODUCTION_URL: &str = "https://monitoringapi.solaredge.com";
+
+/// Selects where the [Client](crate::Client) sends its requests
+///
+/// Can be parsed from a string to allow switching via configuration:
+///  * `production` - [Environment::Production]
+///  * `replay:<directory>` - [Environment::Replay] with the fixtures from `<directory>`
+///  * anything else is parsed as URL for [Environment::Custom]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Environment {
+	/// Official SolarEdge monitoring API at `https://monitoringapi.solaredge.com`
+	#[default]
+	Production,
+	/// API-compatible server at the specified base URL, e.g. staging proxy or local mock server
+	Custom(Url),
+	/// No HTTP requests are made, responses are read from the fixture files in the specified directory
+	///
+	/// Fixture path is the endpoint path relative to that directory, e.g. `<directory>/site/1/details.json`.
+	Replay(PathBuf),
+}
+
+impl Environment {
+	/// Base URL that the endpoint paths are joined to
+	///
+	/// For [Environment::Replay] the production URL is returned, it's only used for logging in that case.
+	pub fn base_url(&self) -> Url {
+		match self {
+			Environment::Production | Environment::Replay(_) => Url::parse(PRODUCTION_URL).expect("Static URL parsing failed"),
+			Environment::Custom(url) => url.clone(),
+		}
+	}
+}
+
+impl FromStr for Environment {
+	type Err = url::ParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.eq_ignore_ascii_case("production") {
+			Ok(Environment::Production)
+		} else if let Some(dir) = s.strip_prefix("replay:") {
+			Ok(Environment::Replay(PathBuf::from(dir)))
+		} else {
+			Url::parse(s).map(Environment::Custom)
+		}
+	}
+}
@@ -1,14 +1,37 @@
-use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fmt, io};
 
 use http_adapter::http;
+use serde_json::Value;
+
+use crate::secret::{redact_api_key, RedactedDebug};
+use crate::{InvalidRequest, RateLimitExceeded, SecretError, SiteId};
 
-#[derive(Debug)]
 pub enum Error<E> {
 	UrlParse(url::ParseError),
 	UrlEncode(serde_urlencoded::ser::Error),
 	HttpRequest(E),
 	Json(serde_json::Error),
 	Api(http::StatusCode, Vec<u8>),
+	Replay(PathBuf, io::Error),
+	/// API throttled the request with HTTP 429 status
+	RateLimited {
+		/// Delay requested by the `Retry-After` header
+		retry_after: Option<Duration>,
+		body: Vec<u8>,
+	},
+	/// Request was not sent because it would exceed the daily budget, see [RateLimiter](crate::RateLimiter)
+	BudgetExhausted(RateLimitExceeded),
+	/// Request parameters violate the documented API limits, the request was not sent, see [Validate](crate::Validate)
+	InvalidRequest(InvalidRequest),
+	/// [SecretProvider](crate::SecretProvider) failed to provide the API key
+	Secret(SecretError),
+	/// Responses to the chunks of a bulk request are in the different units that can't be converted to each other
+	UnitMismatch {
+		expected: String,
+		actual: String,
+	},
 }
 
 impl<E: fmt::Display> fmt::Display for Error<E> {
@@ -21,20 +44,221 @@ impl<E: fmt::Display> fmt::Display for Error<E> {
 				write!(f, "Parameter encoding error: {e}")
 			}
 			Error::HttpRequest(e) => {
-				write!(f, "HTTP request error: {e}")
+				write!(f, "HTTP request error: {}", redact_api_key(&e.to_string()))
 			}
 			Error::Json(e) => {
 				write!(f, "JSON error: {e}")
 			}
-			Error::Api(status, _) => {
-				write!(f, "Solaredge HTTP API error: {status}")
+			Error::Api(status, body) => {
+				write!(f, "Solaredge HTTP API error: {status}")?;
+				if let Some(body) = ApiErrorBody::parse(body) {
+					write!(f, ", {body}")?;
+				}
+				Ok(())
+			}
+			Error::Replay(path, e) => {
+				write!(f, "Replay fixture error, path: {}, error: {e}", path.display())
+			}
+			Error::RateLimited { retry_after, .. } => {
+				write!(f, "Solaredge HTTP API rate limit exceeded")?;
+				if let Some(retry_after) = retry_after {
+					write!(f, ", retry after: {}s", retry_after.as_secs())?;
+				}
+				Ok(())
+			}
+			Error::BudgetExhausted(e) => {
+				write!(f, "Rate limit error: {e}")
+			}
+			Error::InvalidRequest(e) => {
+				write!(f, "Invalid request: {e}")
+			}
+			Error::Secret(e) => {
+				write!(f, "API key error: {e}")
 			}
+			Error::UnitMismatch { expected, actual } => {
+				write!(
+					f,
+					"Unit mismatch between the bulk responses, expected: {expected}, actual: {actual}"
+				)
+			}
+		}
+	}
+}
+
+/// The API key is hidden in the output of the HTTP client adapter error
+impl<E: fmt::Debug> fmt::Debug for Error<E> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::UrlParse(e) => f.debug_tuple("UrlParse").field(e).finish(),
+			Error::UrlEncode(e) => f.debug_tuple("UrlEncode").field(e).finish(),
+			Error::HttpRequest(e) => f.debug_tuple("HttpRequest").field(&RedactedDebug(e)).finish(),
+			Error::Json(e) => f.debug_tuple("Json").field(e).finish(),
+			Error::Api(status, body) => f.debug_tuple("Api").field(status).field(body).finish(),
+			Error::Replay(path, e) => f.debug_tuple("Replay").field(path).field(e).finish(),
+			Error::RateLimited { retry_after, body } => f
+				.debug_struct("RateLimited")
+				.field("retry_after", retry_after)
+				.field("body", body)
+				.finish(),
+			Error::BudgetExhausted(e) => f.debug_tuple("BudgetExhausted").field(e).finish(),
+			Error::InvalidRequest(e) => f.debug_tuple("InvalidRequest").field(e).finish(),
+			Error::Secret(e) => f.debug_tuple("Secret").field(e).finish(),
+			Error::UnitMismatch { expected, actual } => f
+				.debug_struct("UnitMismatch")
+				.field("expected", expected)
+				.field("actual", actual)
+				.finish(),
 		}
 	}
 }
 
 impl<E: fmt::Debug + fmt::Display> std::error::Error for Error<E> {}
 
+impl<E> Error<E> {
+	/// Parsed body of the [Error::Api], `None` for the other errors or if the body has no recognizable error description
+	pub fn api_error_body(&self) -> Option<ApiErrorBody> {
+		match self {
+			Error::Api(_, body) | Error::RateLimited { body, .. } => ApiErrorBody::parse(body),
+			_ => None,
+		}
+	}
+}
+
+/// [Error] of a request for the specific site, returned by the futures of [Client::site_task()](crate::Client::site_task)
+/// so that the failures of the concurrently running tasks can be attributed
+#[derive(Debug)]
+pub struct SiteError<E> {
+	pub site_id: SiteId,
+	pub error: Error<E>,
+}
+
+/// Result of the request for the specific site, the value is paired with the site id
+pub type SiteResult<T, E> = Result<(SiteId, T), SiteError<E>>;
+
+impl<E: fmt::Display> fmt::Display for SiteError<E> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Site {}: {}", self.site_id, self.error)
+	}
+}
+
+impl<E: fmt::Debug + fmt::Display + 'static> std::error::Error for SiteError<E> {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.error)
+	}
+}
+
+/// Parses the value of the `Retry-After` header, either the number of seconds or the HTTP date
+pub(crate) fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+	let value = value.trim();
+	if let Ok(seconds) = value.parse::<u64>() {
+		return Some(Duration::from_secs(seconds));
+	}
+	let at = UNIX_EPOCH.checked_add(Duration::from_secs(parse_http_date(value)?))?;
+	Some(at.duration_since(now).unwrap_or_default())
+}
+
+/// Parses the IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`, into the Unix timestamp
+pub(crate) fn parse_http_date(value: &str) -> Option<u64> {
+	const MONTHS: [&str; 12] = [
+		"Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+	];
+	let (_weekday, rest) = value.split_once(", ")?;
+	let mut parts = rest.split(' ');
+	let day = parts.next()?.parse::<u64>().ok()?;
+	let month = parts.next()?;
+	let month = MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+	let year = parts.next()?.parse::<u64>().ok()?;
+	let mut time = parts.next()?.split(':').map(|part| part.parse::<u64>().ok());
+	let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+	if parts.next() != Some("GMT") || parts.next().is_some() || time.next().is_some() {
+		return None;
+	}
+	let is_leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+	let days_in_month = match month {
+		2 if is_leap => 29,
+		2 => 28,
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	};
+	// 60 seconds is a leap second
+	if !(1970..=9999).contains(&year) || !(1..=days_in_month).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+		return None;
+	}
+	// days from civil, see http://howardhinnant.github.io/date_algorithms.html
+	let (y, m) = if month <= 2 {
+		(year - 1, month + 9)
+	} else {
+		(year, month - 3)
+	};
+	let era = y / 400;
+	let yoe = y % 400;
+	let doy = (153 * m + 2) / 5 + day - 1;
+	let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+	let days = era.checked_mul(146097)?.checked_add(doe)?.checked_sub(719468)?;
+	days.checked_mul(86400)?.checked_add(hour * 3600 + minute * 60 + second)
+}
+
+/// Error description returned by the API along with the 4xx and 5xx statuses
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiErrorBody {
+	/// Error code if the API provides one, numeric codes are converted to string
+	pub code: Option<String>,
+	pub message: String,
+}
+
+impl ApiErrorBody {
+	/// Parses the raw error body
+	///
+	/// Recognizes the JSON objects with `message` (and optional `code`) fields, the same fields nested under `error` and
+	/// the `{"String": "<message>"}` form used by the SolarEdge API. Returns `None` for the other bodies, e.g. HTML error
+	/// pages.
+	pub fn parse(body: &[u8]) -> Option<Self> {
+		fn as_string(value: &Value) -> Option<String> {
+			match value {
+				Value::String(s) => Some(s.clone()),
+				Value::Number(n) => Some(n.to_string()),
+				_ => None,
+			}
+		}
+
+		let Value::Object(object) = serde_json::from_slice::<Value>(body).ok()? else {
+			return None;
+		};
+		let object = match object.get("error") {
+			Some(Value::Object(error)) => error,
+			Some(Value::String(message)) => {
+				return Some(Self {
+					code: None,
+					message: message.clone(),
+				})
+			}
+			_ => &object,
+		};
+		let message = ["message", "String", "description"]
+			.into_iter()
+			.find_map(|key| object.get(key).and_then(as_string))?;
+		Some(Self {
+			code: object.get("code").and_then(as_string),
+			message,
+		})
+	}
+}
+
+impl fmt::Display for ApiErrorBody {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if let Some(code) = &self.code {
+			write!(f, "{code}: ")?;
+		}
+		f.write_str(&self.message)
+	}
+}
+
+impl<E> From<InvalidRequest> for Error<E> {
+	fn from(s: InvalidRequest) -> Self {
+		Self::InvalidRequest(s)
+	}
+}
+
 impl<E> From<url::ParseError> for Error<E> {
 	fn from(s: url::ParseError) -> Self {
 		Self::UrlParse(s)
@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::Error;
+
+/// Coarse classification of the [Error] used for aggregating the failures in the [ErrorReport]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorClass {
+	/// Request couldn't be built: [Error::UrlParse], [Error::UrlEncode] or [Error::InvalidRequest]
+	Request,
+	/// HTTP client adapter failed, e.g. connection error: [Error::HttpRequest]
+	Transport,
+	/// API responded with HTTP 4xx status other than 429: [Error::Api]
+	ClientError,
+	/// API responded with HTTP 5xx status: [Error::Api]
+	ServerError,
+	/// API throttled the request or the local budget was exhausted: [Error::RateLimited] or [Error::BudgetExhausted]
+	RateLimited,
+	/// Response doesn't match the expected schema: [Error::Json] or [Error::UnitMismatch]
+	Parse,
+	/// API key couldn't be obtained: [Error::Secret]
+	Secret,
+	/// Recorded fixture couldn't be read: [Error::Replay]
+	Replay,
+}
+
+impl fmt::Display for ErrorClass {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			ErrorClass::Request => "request",
+			ErrorClass::Transport => "transport",
+			ErrorClass::ClientError => "client error",
+			ErrorClass::ServerError => "server error",
+			ErrorClass::RateLimited => "rate limited",
+			ErrorClass::Parse => "parse",
+			ErrorClass::Secret => "secret",
+			ErrorClass::Replay => "replay",
+		})
+	}
+}
+
+impl<E> Error<E> {
+	pub fn class(&self) -> ErrorClass {
+		match self {
+			Error::UrlParse(_) | Error::UrlEncode(_) | Error::InvalidRequest(_) => ErrorClass::Request,
+			Error::HttpRequest(_) => ErrorClass::Transport,
+			Error::Api(status, _) if status.is_server_error() => ErrorClass::ServerError,
+			Error::Api(..) => ErrorClass::ClientError,
+			Error::RateLimited { .. } | Error::BudgetExhausted(_) => ErrorClass::RateLimited,
+			Error::Json(_) | Error::UnitMismatch { .. } => ErrorClass::Parse,
+			Error::Secret(_) => ErrorClass::Secret,
+			Error::Replay(..) => ErrorClass::Replay,
+		}
+	}
+}
+
+/// Failures of the requests within the sliding window, returned by
+/// [Client::error_report()](crate::Client::error_report)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorReport {
+	/// Length of the window the failures are counted over
+	pub window: Duration,
+	/// Number of the failures by the client function (e.g. `site_details`) and by the class
+	pub failures: BTreeMap<String, BTreeMap<ErrorClass, u32>>,
+}
+
+impl ErrorReport {
+	/// Total number of the failures
+	pub fn total(&self) -> u32 {
+		self.failures.values().flat_map(|classes| classes.values()).sum()
+	}
+
+	/// Number of the failures by the class across all functions
+	pub fn by_class(&self) -> BTreeMap<ErrorClass, u32> {
+		let mut out = BTreeMap::new();
+		for (class, count) in self.failures.values().flatten() {
+			*out.entry(*class).or_insert(0) += count;
+		}
+		out
+	}
+
+	/// Number of the failures by the client function across all classes
+	pub fn by_endpoint(&self) -> BTreeMap<&str, u32> {
+		self
+			.failures
+			.iter()
+			.map(|(endpoint, classes)| (endpoint.as_str(), classes.values().sum()))
+			.collect()
+	}
+}
+
+/// Formats the report as a single line suitable for logging, e.g. `2 failures in 3600s, site_details: transport 1, server error 1`
+impl fmt::Display for ErrorReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} failures in {}s", self.total(), self.window.as_secs())?;
+		for (endpoint, classes) in &self.failures {
+			write!(f, ", {endpoint}:")?;
+			let mut sep = " ";
+			for (class, count) in classes {
+				write!(f, "{sep}{class} {count}")?;
+				sep = ", ";
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Sliding window of the failed requests shared between the clones of the client
+#[derive(Debug)]
+pub(crate) struct ErrorCollector {
+	window: Duration,
+	failures: Mutex<VecDeque<(Instant, String, ErrorClass)>>,
+}
+
+impl ErrorCollector {
+	pub fn new(window: Duration) -> Self {
+		Self {
+			window,
+			failures: Mutex::new(VecDeque::new()),
+		}
+	}
+
+	pub fn record(&self, endpoint: &str, class: ErrorClass) {
+		let now = Instant::now();
+		let mut failures = self.lock_failures();
+		Self::expire(&mut failures, now, self.window);
+		failures.push_back((now, endpoint.to_string(), class));
+	}
+
+	pub fn report(&self) -> ErrorReport {
+		let mut failures = self.lock_failures();
+		Self::expire(&mut failures, Instant::now(), self.window);
+		let mut out = ErrorReport {
+			window: self.window,
+			failures: BTreeMap::new(),
+		};
+		for (_, endpoint, class) in failures.iter() {
+			*out.failures.entry(endpoint.clone()).or_default().entry(*class).or_insert(0) += 1;
+		}
+		out
+	}
+
+	fn expire(failures: &mut VecDeque<(Instant, String, ErrorClass)>, now: Instant, window: Duration) {
+		while failures
+			.front()
+			.is_some_and(|(at, _, _)| now.saturating_duration_since(*at) > window)
+		{
+			failures.pop_front();
+		}
+	}
+
+	fn lock_failures(&self) -> MutexGuard<'_, VecDeque<(Instant, String, ErrorClass)>> {
+		self.failures.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
@@ -0,0 +1,51 @@
+use std::borrow::Cow;
+
+use serde_json::{Number, Value};
+
+/// Correction of a known server quirk applied to the untyped response before the typed deserialization, so the response
+/// types keep the documented field types
+type Fixup = fn(&mut Value);
+
+/// Fixups by the client function that performs the request, e.g. `site_inventory`
+const FIXUPS: &[(&str, Fixup)] = &[("site_inventory", inventory_nameplate_capacity)];
+
+/// Applies the fixups registered for the `endpoint` to the response `body`, the body is returned unchanged if there are
+/// none or if it's not valid JSON, the typed deserialization reports the error in that case
+pub(crate) fn apply<'b>(endpoint: &str, body: &'b [u8]) -> Cow<'b, [u8]> {
+	let mut fixups = FIXUPS
+		.iter()
+		.filter(|(fixup_endpoint, _)| *fixup_endpoint == endpoint)
+		.peekable();
+	if fixups.peek().is_none() {
+		return Cow::Borrowed(body);
+	}
+	let Ok(mut value) = serde_json::from_slice::<Value>(body) else {
+		return Cow::Borrowed(body);
+	};
+	for (_, fixup) in fixups {
+		fixup(&mut value);
+	}
+	serde_json::to_vec(&value).map_or(Cow::Borrowed(body), Cow::Owned)
+}
+
+/// Batteries in the inventory sometimes report `nameplateCapacity` as a string, e.g. `"9800"`
+fn inventory_nameplate_capacity(value: &mut Value) {
+	if let Some(batteries) = value.pointer_mut("/Inventory/batteries").and_then(Value::as_array_mut) {
+		for battery in batteries {
+			coerce_number(battery, "nameplateCapacity");
+		}
+	}
+}
+
+/// Replaces the string value of the `field` of the `object` with the number it contains
+fn coerce_number(object: &mut Value, field: &str) {
+	if let Some(value) = object.get_mut(field) {
+		if let Some(number) = value
+			.as_str()
+			.and_then(|s| s.trim().parse::<f64>().ok())
+			.and_then(Number::from_f64)
+		{
+			*value = Value::Number(number);
+		}
+	}
+}
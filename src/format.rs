@@ -0,0 +1,126 @@
+//! Human-readable formatting of the energy and power values
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Separators used when formatting the numbers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+	/// Separator between the groups of thousands, `None` to disable grouping
+	pub thousands_separator: Option<char>,
+	pub decimal_separator: char,
+}
+
+impl NumberFormat {
+	/// `1,234.5`
+	pub const EN: Self = Self {
+		thousands_separator: Some(','),
+		decimal_separator: '.',
+	};
+	/// `1.234,5`
+	pub const DE: Self = Self {
+		thousands_separator: Some('.'),
+		decimal_separator: ',',
+	};
+	/// `1 234,5` with the narrow no-break space
+	pub const FR: Self = Self {
+		thousands_separator: Some('\u{202F}'),
+		decimal_separator: ',',
+	};
+	/// `1234.5`
+	pub const PLAIN: Self = Self {
+		thousands_separator: None,
+		decimal_separator: '.',
+	};
+
+	/// Formats `value` rounded to `decimals` decimal places
+	pub fn format(&self, value: f64, decimals: usize) -> String {
+		if !value.is_finite() {
+			return value.to_string();
+		}
+		let formatted = format!("{:.*}", decimals, value.abs());
+		let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+		let mut out = String::with_capacity(formatted.len() + formatted.len() / 3 + 1);
+		if value.is_sign_negative() && formatted.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+			out.push('-');
+		}
+		for (i, digit) in integer.chars().enumerate() {
+			if i > 0 && (integer.len() - i) % 3 == 0 {
+				if let Some(separator) = self.thousands_separator {
+					out.push(separator);
+				}
+			}
+			out.push(digit);
+		}
+		if !fraction.is_empty() {
+			out.push(self.decimal_separator);
+			out.push_str(fraction);
+		}
+		out
+	}
+}
+
+impl Default for NumberFormat {
+	#[inline]
+	fn default() -> Self {
+		Self::EN
+	}
+}
+
+const SI_PREFIXES: [(f64, &str); 4] = [(1e9, "G"), (1e6, "M"), (1e3, "k"), (1., "")];
+
+fn format_si(value: f64, unit: &str, decimals: usize, number_format: &NumberFormat) -> String {
+	let (factor, prefix) = SI_PREFIXES
+		.into_iter()
+		.find(|(factor, _)| value.abs() >= *factor)
+		.unwrap_or((1., ""));
+	format!("{} {prefix}{unit}", number_format.format(value / factor, decimals))
+}
+
+/// Formats the power in W auto-scaling it to W, kW, MW or GW, e.g. `4.20 kW`
+pub fn format_power(watts: f64, decimals: usize, number_format: &NumberFormat) -> String {
+	format_si(watts, "W", decimals, number_format)
+}
+
+/// Formats the energy in Wh auto-scaling it to Wh, kWh, MWh or GWh, e.g. `24.00 kWh`
+pub fn format_energy(watt_hours: f64, decimals: usize, number_format: &NumberFormat) -> String {
+	format_si(watt_hours, "Wh", decimals, number_format)
+}
+
+/// Formats the energy in Wh always as kWh, e.g. `1,234.5 kWh`
+pub fn format_kwh(watt_hours: f64, decimals: usize, number_format: &NumberFormat) -> String {
+	format!("{} kWh", number_format.format(watt_hours / 1000., decimals))
+}
+
+/// Power in W that is displayed auto-scaled with [NumberFormat::EN], the precision defaults to 2 decimal places
+///
+/// ```
+/// use solaredge::format::Power;
+///
+/// assert_eq!("4.20 kW", Power(4200.).to_string());
+/// assert_eq!("1.5 MW", format!("{:.1}", Power(1_500_000.)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Power(pub f64);
+
+impl Display for Power {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(&format_power(self.0, f.precision().unwrap_or(2), &NumberFormat::EN))
+	}
+}
+
+/// Energy in Wh that is displayed auto-scaled with [NumberFormat::EN], the precision defaults to 2 decimal places
+///
+/// ```
+/// use solaredge::format::Energy;
+///
+/// assert_eq!("24.00 kWh", Energy(24000.).to_string());
+/// assert_eq!("950 Wh", format!("{:.0}", Energy(950.)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Energy(pub f64);
+
+impl Display for Energy {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(&format_energy(self.0, f.precision().unwrap_or(2), &NumberFormat::EN))
+	}
+}
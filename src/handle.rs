@@ -0,0 +1,286 @@
+use std::fmt;
+
+use http_adapter::HttpClientAdapter;
+
+use crate::api::request;
+use crate::{response, Client, Error, SerialNumber, SiteId};
+
+/// Client bound to a single site, created with [Client::site()]
+///
+/// Every function delegates to the [Client] function of the same name without the `site_` prefix, passing the site id
+/// of the handle.
+///
+/// # Example
+/// ```
+/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+/// # mod http_adapter_reqwest {
+/// #    #[derive(Default)]
+/// #    pub struct ReqwestAdapter;
+/// #    #[async_trait::async_trait(?Send)]
+/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+/// #       type Error = String;
+/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+/// #    }
+/// # }
+/// async fn run() -> Result<(), solaredge::Error<String>> {
+///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+///    let site = client.site(1);
+///    let details = site.details().await?;
+///    let overview = site.overview().await?;
+///    let flow = site.power_flow().await?;
+///    Ok(())
+/// }
+/// ```
+pub struct SiteHandle<'c, C> {
+	client: &'c Client<C>,
+	site_id: SiteId,
+}
+
+impl<'c, C: HttpClientAdapter> SiteHandle<'c, C> {
+	pub(crate) fn new(client: &'c Client<C>, site_id: SiteId) -> Self {
+		Self { client, site_id }
+	}
+
+	#[inline]
+	pub fn id(&self) -> SiteId {
+		self.site_id
+	}
+
+	#[inline]
+	pub fn client(&self) -> &'c Client<C> {
+		self.client
+	}
+
+	/// [Client::site_details()]
+	pub async fn details(&self) -> Result<response::Site, Error<C::Error>> {
+		self.client.site_details(self.site_id).await
+	}
+
+	/// [Client::site_data_period()]
+	pub async fn data_period(&self) -> Result<response::DataPeriod, Error<C::Error>> {
+		self.client.site_data_period(self.site_id).await
+	}
+
+	/// [Client::site_energy()]
+	pub async fn energy(&self, params: &request::SiteEnergy) -> Result<response::SiteEnergy, Error<C::Error>> {
+		self.client.site_energy(self.site_id, params).await
+	}
+
+	/// [Client::site_energy_full_range()]
+	#[cfg(feature = "chrono")]
+	pub async fn energy_full_range(&self, params: &request::SiteEnergy) -> Result<response::SiteEnergy, Error<C::Error>> {
+		self.client.site_energy_full_range(self.site_id, params).await
+	}
+
+	/// [Client::site_time_frame_energy()]
+	pub async fn time_frame_energy(
+		&self,
+		params: &request::SiteTotalEnergy,
+	) -> Result<response::SiteTimeframeEnergy, Error<C::Error>> {
+		self.client.site_time_frame_energy(self.site_id, params).await
+	}
+
+	/// [Client::site_power()]
+	pub async fn power(&self, params: &request::DateTimeRange) -> Result<response::SitePower, Error<C::Error>> {
+		self.client.site_power(self.site_id, params).await
+	}
+
+	/// [Client::site_overview()]
+	pub async fn overview(&self) -> Result<response::SiteOverview, Error<C::Error>> {
+		self.client.site_overview(self.site_id).await
+	}
+
+	/// [Client::site_power_details()]
+	pub async fn power_details(
+		&self,
+		params: &request::SitePowerDetails<'_>,
+	) -> Result<response::SiteMetersDetails, Error<C::Error>> {
+		self.client.site_power_details(self.site_id, params).await
+	}
+
+	/// [Client::site_energy_details()]
+	pub async fn energy_details(
+		&self,
+		params: &request::MetersDateTimeRange<'_>,
+	) -> Result<response::SiteMetersDetails, Error<C::Error>> {
+		self.client.site_energy_details(self.site_id, params).await
+	}
+
+	/// [Client::site_current_power_flow()]
+	pub async fn power_flow(&self) -> Result<response::SiteCurrentPowerFlow, Error<C::Error>> {
+		self.client.site_current_power_flow(self.site_id).await
+	}
+
+	/// [Client::site_storage_data()]
+	pub async fn storage_data(&self, params: &request::SiteStorageData<'_>) -> Result<response::SiteStorageData, Error<C::Error>> {
+		self.client.site_storage_data(self.site_id, params).await
+	}
+
+	/// [Client::site_storage_data_range()]
+	#[cfg(feature = "chrono")]
+	pub async fn storage_data_range(
+		&self,
+		params: &request::SiteStorageData<'_>,
+	) -> Result<response::SiteStorageData, Error<C::Error>> {
+		self.client.site_storage_data_range(self.site_id, params).await
+	}
+
+	/// [Client::site_env_benefits()]
+	pub async fn env_benefits(&self, params: &request::SiteEnvBenefits) -> Result<response::SiteEnvBenefits, Error<C::Error>> {
+		self.client.site_env_benefits(self.site_id, params).await
+	}
+
+	/// [Client::site_image()]
+	pub async fn image(&self, name: &str, params: &request::SiteImage<'_>) -> Result<response::ImageResult, Error<C::Error>> {
+		self.client.site_image(self.site_id, name, params).await
+	}
+
+	/// [Client::site_inventory()]
+	pub async fn inventory(&self) -> Result<response::SiteInventory, Error<C::Error>> {
+		self.client.site_inventory(self.site_id).await
+	}
+
+	/// [Client::site_meters()]
+	pub async fn meters(&self, params: &request::MetersDateTimeRange<'_>) -> Result<response::SiteMeters, Error<C::Error>> {
+		self.client.site_meters(self.site_id, params).await
+	}
+
+	/// [Client::equipment_list()]
+	pub async fn equipment_list(&self) -> Result<Vec<response::Equipment>, Error<C::Error>> {
+		self.client.equipment_list(self.site_id).await
+	}
+
+	/// [Client::equipment_sensors()]
+	pub async fn sensors(&self) -> Result<Vec<response::GatewaySensors>, Error<C::Error>> {
+		self.client.equipment_sensors(self.site_id).await
+	}
+
+	/// Returns an [EquipmentHandle] for the equipment with `serial_number` at this site
+	#[inline]
+	pub fn equipment(&self, serial_number: impl Into<SerialNumber>) -> EquipmentHandle<'c, C> {
+		EquipmentHandle::new(self.client, self.site_id, serial_number.into())
+	}
+
+	/// [Client::site_sensor_data()]
+	pub async fn sensor_data(
+		&self,
+		params: &request::SiteSensorData,
+	) -> Result<Vec<response::GatewaySensorData>, Error<C::Error>> {
+		self.client.site_sensor_data(self.site_id, params).await
+	}
+}
+
+impl<C> Clone for SiteHandle<'_, C> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<C> Copy for SiteHandle<'_, C> {}
+
+impl<C> fmt::Debug for SiteHandle<'_, C> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SiteHandle")
+			.field("site_id", &self.site_id)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Client bound to a single piece of equipment, created with [Client::equipment()] or [SiteHandle::equipment()]
+///
+/// # Example
+/// ```
+/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+/// # mod http_adapter_reqwest {
+/// #    #[derive(Default)]
+/// #    pub struct ReqwestAdapter;
+/// #    #[async_trait::async_trait(?Send)]
+/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+/// #       type Error = String;
+/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+/// #    }
+/// # }
+/// async fn run() -> Result<(), solaredge::Error<String>> {
+///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+///    let site = client.site(1);
+///    for equipment in site.equipment_list().await? {
+///       let changes = site.equipment(&equipment.serial_number).changelog().await?;
+///       println!("{}: {} changes", equipment.serial_number, changes.len());
+///    }
+///    Ok(())
+/// }
+/// ```
+pub struct EquipmentHandle<'c, C> {
+	client: &'c Client<C>,
+	site_id: SiteId,
+	serial_number: SerialNumber,
+}
+
+impl<'c, C: HttpClientAdapter> EquipmentHandle<'c, C> {
+	pub(crate) fn new(client: &'c Client<C>, site_id: SiteId, serial_number: SerialNumber) -> Self {
+		Self {
+			client,
+			site_id,
+			serial_number,
+		}
+	}
+
+	#[inline]
+	pub fn site_id(&self) -> SiteId {
+		self.site_id
+	}
+
+	#[inline]
+	pub fn serial_number(&self) -> &SerialNumber {
+		&self.serial_number
+	}
+
+	#[inline]
+	pub fn client(&self) -> &'c Client<C> {
+		self.client
+	}
+
+	/// [SiteHandle] of the site this equipment belongs to
+	#[inline]
+	pub fn site(&self) -> SiteHandle<'c, C> {
+		SiteHandle::new(self.client, self.site_id)
+	}
+
+	/// [Client::equipment_data()]
+	pub async fn data(&self, params: &request::DateTimeRange) -> Result<Vec<response::EquipmentTelemetry>, Error<C::Error>> {
+		self.client.equipment_data(self.site_id, &self.serial_number, params).await
+	}
+
+	/// [Client::equipment_data_range()]
+	#[cfg(feature = "chrono")]
+	pub async fn data_range(&self, params: &request::DateTimeRange) -> Result<Vec<response::EquipmentTelemetry>, Error<C::Error>> {
+		self
+			.client
+			.equipment_data_range(self.site_id, &self.serial_number, params)
+			.await
+	}
+
+	/// [Client::equipment_changelog()]
+	pub async fn changelog(&self) -> Result<Vec<response::EquipmentChange>, Error<C::Error>> {
+		self.client.equipment_changelog(self.site_id, &self.serial_number).await
+	}
+}
+
+impl<C> Clone for EquipmentHandle<'_, C> {
+	fn clone(&self) -> Self {
+		Self {
+			client: self.client,
+			site_id: self.site_id,
+			serial_number: self.serial_number.clone(),
+		}
+	}
+}
+
+impl<C> fmt::Debug for EquipmentHandle<'_, C> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("EquipmentHandle")
+			.field("site_id", &self.site_id)
+			.field("serial_number", &self.serial_number)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,33 @@
+//! Request metrics recorded with the [metrics] crate facade, the names are documented in the `metrics` feature description
+
+use std::time::Duration;
+
+use crate::{Error, ErrorClass};
+
+/// Records a single attempt of the request to the `endpoint`
+pub(crate) fn record<T, E>(endpoint: &str, res: &Result<T, Error<E>>, elapsed: Duration) {
+	let endpoint = endpoint.to_string();
+	metrics::counter!("solaredge_requests_total", "endpoint" => endpoint.clone()).increment(1);
+	metrics::histogram!("solaredge_request_duration_seconds", "endpoint" => endpoint.clone()).record(elapsed.as_secs_f64());
+	if let Err(e) = res {
+		metrics::counter!("solaredge_request_errors_total", "endpoint" => endpoint, "status" => status_label(e)).increment(1);
+	}
+}
+
+fn status_label<E>(e: &Error<E>) -> String {
+	let label = match e {
+		Error::Api(status, _) => return status.as_str().to_string(),
+		Error::RateLimited { .. } => "429",
+		e => match e.class() {
+			ErrorClass::Request => "request",
+			ErrorClass::Transport => "transport",
+			ErrorClass::ClientError => "client_error",
+			ErrorClass::ServerError => "server_error",
+			ErrorClass::RateLimited => "rate_limited",
+			ErrorClass::Parse => "parse",
+			ErrorClass::Secret => "secret",
+			ErrorClass::Replay => "replay",
+		},
+	};
+	label.to_string()
+}
@@ -0,0 +1,38 @@
+use serde::de::DeserializeOwned;
+
+use crate::lenient::collect_skipped;
+
+/// Parser of the response bodies into the typed responses
+///
+/// The alternative parsers report their errors as [serde_json::Error] via [serde::de::Error::custom()], so that
+/// [Error::Json](crate::Error::Json) stays the same regardless of the parser. The `strict` mode works with any parser
+/// implementing the serde data model as it's implemented with the serde attributes of the response types.
+pub(crate) trait JsonBackend {
+	fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, serde_json::Error>;
+}
+
+/// [serde_json] with the default settings
+pub(crate) struct SerdeJson;
+
+impl JsonBackend for SerdeJson {
+	#[inline]
+	fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, serde_json::Error> {
+		serde_json::from_slice(body)
+	}
+}
+
+/// Parser selected for this build
+pub(crate) type Backend = SerdeJson;
+
+/// Parses the response `body` with the [Backend], in the `lenient` mode the list elements that fail to deserialize are
+/// skipped and returned along with the result
+pub(crate) fn parse<T: DeserializeOwned>(
+	body: &[u8],
+	lenient: bool,
+) -> (Result<T, serde_json::Error>, Vec<(usize, serde_json::Error)>) {
+	if lenient {
+		collect_skipped(|| Backend::from_slice(body))
+	} else {
+		(Backend::from_slice(body), vec![])
+	}
+}
@@ -0,0 +1,63 @@
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+/// Result of the requests along with the list elements that were skipped because they failed to deserialize, returned by
+/// [Client::with_lenient()](crate::Client::with_lenient)
+#[derive(Debug)]
+pub struct Lenient<T> {
+	pub value: T,
+	/// Index of the skipped element within its list and the deserialization error, in the order the responses were
+	/// received
+	pub skipped: Vec<(usize, serde_json::Error)>,
+}
+
+impl<T> Lenient<T> {
+	pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Lenient<U> {
+		Lenient {
+			value: f(self.value),
+			skipped: self.skipped,
+		}
+	}
+}
+
+pub(crate) type SkippedRecorder = Arc<Mutex<Vec<(usize, serde_json::Error)>>>;
+
+thread_local! {
+	/// Set while the response is being deserialized in the lenient mode
+	static SKIPPED: RefCell<Option<Vec<(usize, serde_json::Error)>>> = const { RefCell::new(None) };
+}
+
+/// Runs the deserialization `f` in the lenient mode returning its result and the skipped list elements
+pub(crate) fn collect_skipped<T>(f: impl FnOnce() -> T) -> (T, Vec<(usize, serde_json::Error)>) {
+	let outer = SKIPPED.with(|skipped| skipped.replace(Some(vec![])));
+	let out = f();
+	let skipped = SKIPPED.with(|skipped| skipped.replace(outer)).unwrap_or_default();
+	(out, skipped)
+}
+
+/// Deserializes the list skipping the elements that fail to deserialize when in the lenient mode, see [collect_skipped()]
+pub(crate) fn list<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+	D: Deserializer<'de>,
+	T: DeserializeOwned,
+{
+	if !SKIPPED.with(|skipped| skipped.borrow().is_some()) {
+		return Vec::deserialize(deserializer);
+	}
+	let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+	let mut out = Vec::with_capacity(values.len());
+	for (i, value) in values.into_iter().enumerate() {
+		match serde_json::from_value(value) {
+			Ok(item) => out.push(item),
+			Err(e) => SKIPPED.with(|skipped| {
+				if let Some(skipped) = skipped.borrow_mut().as_mut() {
+					skipped.push((i, e));
+				}
+			}),
+		}
+	}
+	Ok(out)
+}
@@ -28,15 +28,107 @@
 //!    Ok(())
 //! }
 //! ```
+//!
+//! ## Features
+//!  * `analytics` (enabled by default) - the `analytics` module with the helpers combining the data of the different
+//!    endpoints and the GeoJSON export
+//!  * `chrono` (enabled by default) - dates and date-times are exposed as [chrono](https://crates.io/crates/chrono) types,
+//!    when disabled they are exposed as validated string newtypes from the `api::date` module, for consumers that only
+//!    forward the data
+//!  * `metrics` - every request attempt is recorded with the [metrics](https://crates.io/crates/metrics) crate facade,
+//!    install any metrics exporter to collect them: `solaredge_requests_total` and `solaredge_request_errors_total`
+//!    counters and `solaredge_request_duration_seconds` histogram labeled with the `endpoint`, e.g. `site_details`, the
+//!    errors are also labeled with the `status`, the HTTP status code or the error class, e.g. `transport`
+//!  * `serialize` - derives `serde::Serialize` on all response types and the enums they use, e.g. for persisting the
+//!    responses, the output uses the same field names and formats as the API
+//!  * `strict` - response parsing fails on any field unknown to this crate, run `cargo test --features strict` to check the
+//!    recorded fixtures in `tests/fixtures` for the API drift
+//!  * `testing` - `testing::MockClient` implementing [SolarEdgeApi] with the canned responses for the downstream tests
+//!  * `tower` - [Client] implements `tower::Service<Endpoint>` returning the raw response body, for stacking the standard
+//!    tower layers
+//!  * `zeroize` - the API key is wiped from the memory when the client is dropped, the request URLs containing it are
+//!    wiped after the request
+//!
+//! For the small builds, e.g. the collectors on Raspberry Pi or OpenWrt, disable the default features:
+//! `solaredge = { version = "*", default-features = false }`. This leaves the client with the core response types, the
+//! dates are then the string newtypes and `chrono` isn't compiled.
 
 pub use api::enums::*;
+pub use api::ids::{SerialNumber, SiteId};
 pub use api::request::*;
 pub use api::response;
+pub use audit::{AuditEntry, AuditSink};
+pub use batch::{Batch, BatchHandle, BatchResults};
+pub use cache::{CacheStore, EndpointClass, EndpointKind, MemoryCacheStore, ResponseCache};
+pub use cassette::{CassetteAdapter, CassetteError, CassetteMode};
 pub use client::Client;
-pub use error::Error;
+pub use deprecation::Deprecation;
+pub use environment::Environment;
+pub use error::{ApiErrorBody, Error, SiteError, SiteResult};
+pub use error_report::{ErrorClass, ErrorReport};
+pub use handle::{EquipmentHandle, SiteHandle};
+pub use lenient::Lenient;
+pub use meta::{RawResponse, ResponseMeta, WithMeta, WithRaw};
+pub use middleware::ClientMiddleware;
+pub use pagination::Paginator;
+pub use path_encoding::PathEncoding;
+pub use plan::RequestPlan;
+pub use profile::SiteProfile;
+pub use progress::{Progress, ProgressSink};
+pub use quota::{QuotaScope, QuotaTracker};
+pub use rate_limit::{RateLimitExceeded, RateLimiter, RateLimiterUsage, RateLimits, RequestPriority};
+pub use request_key::RequestKey;
+pub use retry::RetryPolicy;
+pub use secret::{EnvSecret, FileSecret, SecretError, SecretProvider};
+#[cfg(feature = "tower")]
+pub use service::Endpoint;
+#[cfg(feature = "chrono")]
+pub use snapshot::SiteSnapshot;
+pub use solaredge_api::SolarEdgeApi;
 
+#[cfg(feature = "analytics")]
+pub mod analytics;
 pub mod api;
+mod audit;
+mod batch;
+mod cache;
+#[cfg(feature = "chrono")]
+pub mod calendar;
+mod cassette;
 pub mod client;
+mod deprecation;
+pub mod diagnostics;
+mod environment;
 mod error;
+mod error_report;
+mod fixup;
+pub mod format;
+mod handle;
+#[cfg(feature = "metrics")]
+mod instrument;
+mod json;
+mod lenient;
+pub mod limits;
+mod meta;
+mod middleware;
+mod pagination;
+mod path_encoding;
+mod plan;
+mod post_process;
+mod profile;
+mod progress;
+mod quota;
+mod rate_limit;
+mod request_key;
+mod retry;
+mod secret;
+pub mod series;
+#[cfg(feature = "tower")]
+mod service;
+#[cfg(feature = "chrono")]
+mod snapshot;
+mod solaredge_api;
+#[cfg(feature = "testing")]
+pub mod testing;
 #[cfg(test)]
 mod tests;
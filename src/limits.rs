@@ -0,0 +1,44 @@
+//! Documented limits of the SolarEdge API
+//!
+//! These are the values used by the request validation, the splitting of the long periods and the large site lists, the
+//! [RequestPlan](crate::RequestPlan) and the default [RateLimits](crate::RateLimits), exposed for sizing the requests in
+//! the user code.
+
+use crate::{MaxPeriod, TimeUnit};
+
+/// Maximum number of the sites in a single request of the bulk endpoints, e.g.
+/// [Client::site_energy_bulk()](crate::Client::site_energy_bulk)
+pub const MAX_BULK_SITES: usize = 100;
+
+/// Maximum page size of the list endpoints, e.g. [Client::sites_list()](crate::Client::sites_list)
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Daily request quota of the account API key for the requests that don't target specific sites
+pub const DAILY_QUOTA_PER_KEY: u32 = 300;
+
+/// Daily request quota of every site, the bulk requests are charged to each of the included sites
+pub const DAILY_QUOTA_PER_SITE: u32 = 300;
+
+/// Maximum number of the concurrent requests from the same source IP
+pub const MAX_CONCURRENT_REQUESTS: usize = 3;
+
+/// Longest period in days accepted by the telemetry endpoints: equipment, storage and sensor data
+pub const TELEMETRY_MAX_DAYS: u32 = 7;
+
+/// Longest period accepted by the telemetry endpoints, see [TELEMETRY_MAX_DAYS]
+pub const TELEMETRY_MAX_PERIOD: MaxPeriod = MaxPeriod::Days(TELEMETRY_MAX_DAYS);
+
+/// Longest period accepted by the site power and power details endpoints
+pub const POWER_MAX_PERIOD: MaxPeriod = MaxPeriod::Months(1);
+
+/// Longest period in months accepted by the site energy endpoints with the `QUARTER_OF_AN_HOUR` and `HOUR` time units
+pub const ENERGY_MAX_MONTHS_INTRADAY: u32 = 1;
+
+/// Longest period in months accepted by the site energy endpoints with the `DAY` time unit
+pub const ENERGY_MAX_MONTHS_DAILY: u32 = 12;
+
+/// Longest period accepted by the site energy endpoints with the `time_unit`, `None` if unlimited
+#[inline]
+pub fn energy_max_period(time_unit: TimeUnit) -> Option<MaxPeriod> {
+	MaxPeriod::for_energy(time_unit)
+}
@@ -0,0 +1,94 @@
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::{Deprecation, RequestKey};
+
+/// Provenance of a single API response, collected by [Client::with_meta()](crate::Client::with_meta)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseMeta {
+	/// Time when the response was received
+	pub fetched_at: SystemTime,
+	/// Client function that performed the request, e.g. `site_details`
+	pub endpoint: String,
+	/// Endpoint path without the query, e.g. `/site/1/details.json`
+	pub path: String,
+	/// Identity of the request including its parameters
+	pub request_key: RequestKey,
+	/// API version set with [Client::with_api_version()](crate::Client::with_api_version)
+	pub api_version: Option<String>,
+	/// `true` if the response was read from the recorded fixtures of [Environment::Replay](crate::Environment::Replay) or
+	/// from the [ResponseCache](crate::ResponseCache) instead of the API
+	pub from_cache: bool,
+	/// Number of the attempts it took to get the response, more than 1 if it was retried, 0 if it was served from the
+	/// [ResponseCache](crate::ResponseCache)
+	pub attempts: u32,
+	/// Deprecation notice of the endpoint sent along with the response
+	pub deprecation: Option<Deprecation>,
+}
+
+/// Result of the requests along with the provenance of every response it was built from
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithMeta<T> {
+	pub value: T,
+	/// In the order the responses were received, multiple entries for the functions that make several requests, e.g.
+	/// [Client::follow_details()](crate::Client::follow_details) or the paginators
+	pub meta: Vec<ResponseMeta>,
+}
+
+impl<T> WithMeta<T> {
+	/// Time of the earliest response, `None` if no request was made
+	pub fn fetched_at(&self) -> Option<SystemTime> {
+		self.meta.iter().map(|meta| meta.fetched_at).min()
+	}
+
+	/// `true` if any of the responses was read from the recorded fixtures
+	pub fn any_from_cache(&self) -> bool {
+		self.meta.iter().any(|meta| meta.from_cache)
+	}
+
+	pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WithMeta<U> {
+		WithMeta {
+			value: f(self.value),
+			meta: self.meta,
+		}
+	}
+}
+
+pub(crate) type MetaRecorder = Arc<Mutex<Vec<ResponseMeta>>>;
+
+/// Original body of a single API response, collected by [Client::with_raw()](crate::Client::with_raw)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawResponse {
+	/// Client function that performed the request, e.g. `site_details`
+	pub endpoint: String,
+	/// Endpoint path without the query, e.g. `/site/1/details.json`
+	pub path: String,
+	/// Response body exactly as received
+	pub body: String,
+}
+
+impl RawResponse {
+	/// Parses the body as untyped JSON
+	pub fn json(&self) -> serde_json::Result<serde_json::Value> {
+		serde_json::from_str(&self.body)
+	}
+}
+
+/// Result of the requests along with the original bodies of the responses it was built from
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithRaw<T> {
+	pub value: T,
+	/// In the order the responses were received
+	pub raw: Vec<RawResponse>,
+}
+
+impl<T> WithRaw<T> {
+	pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WithRaw<U> {
+		WithRaw {
+			value: f(self.value),
+			raw: self.raw,
+		}
+	}
+}
+
+pub(crate) type RawRecorder = Arc<Mutex<Vec<RawResponse>>>;
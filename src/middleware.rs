@@ -0,0 +1,24 @@
+use http_adapter::{Request, Response};
+
+/// Hooks around every HTTP request performed by the client, registered with
+/// [Client::with_middleware()](crate::Client::with_middleware)
+///
+/// Use it for the cross-cutting concerns like injecting the headers, collecting the metrics or the custom logging. The hooks
+/// are called for every attempt including the retries, in the order the middlewares were registered. They are called
+/// synchronously on the request path and are not called in [Environment::Replay](crate::Environment::Replay) as no HTTP
+/// request is performed there. The request URI contains the API key, don't log it as is.
+pub trait ClientMiddleware: Send + Sync {
+	/// Called before the `request` is passed to the HTTP client, `endpoint` is the client function performing the request,
+	/// e.g. `site_details`
+	#[inline]
+	fn before_request(&self, endpoint: &str, request: &mut Request<Vec<u8>>) {
+		let _ = (endpoint, request);
+	}
+
+	/// Called with every `response` received from the HTTP client before its status is checked, so the error responses
+	/// are included, the failures of the HTTP client itself are not
+	#[inline]
+	fn after_response(&self, endpoint: &str, response: &Response<Vec<u8>>) {
+		let _ = (endpoint, response);
+	}
+}
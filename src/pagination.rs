@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::limits::MAX_PAGE_SIZE;
+
+/// Single page of a list endpoint along with the total number of the items reported by the API
+pub(crate) struct Page<T> {
+	pub items: Vec<T>,
+	pub total: usize,
+}
+
+type PageFuture<'a, T, E> = Pin<Box<dyn Future<Output = Result<Page<T>, E>> + 'a>>;
+type FetchPage<'a, T, E> = dyn FnMut(u32, u32) -> PageFuture<'a, T, E> + 'a;
+
+/// [Stream] of the items of a paginated list endpoint, e.g. [Client::sites_list_all()](crate::Client::sites_list_all)
+///
+/// The pages are requested lazily, the next one only when all items of the previous page have been consumed. The
+/// stream ends after a page that is shorter than the page size or once the total number of the items reported by the
+/// API is reached. After an error is yielded the stream ends.
+pub struct Paginator<'a, T, E> {
+	fetch: Box<FetchPage<'a, T, E>>,
+	page_size: u32,
+	next_index: u32,
+	buffer: VecDeque<T>,
+	pending: Option<PageFuture<'a, T, E>>,
+	done: bool,
+}
+
+impl<'a, T, E> Paginator<'a, T, E> {
+	/// `fetch` is called with the start index and the size of the page to request
+	pub(crate) fn new(start_index: u32, page_size: u32, fetch: impl FnMut(u32, u32) -> PageFuture<'a, T, E> + 'a) -> Self {
+		Self {
+			fetch: Box::new(fetch),
+			page_size: page_size.clamp(1, MAX_PAGE_SIZE),
+			next_index: start_index,
+			buffer: VecDeque::new(),
+			pending: None,
+			done: false,
+		}
+	}
+
+	/// Collects all the remaining items into a [Vec], stops at the first error
+	pub async fn try_collect(mut self) -> Result<Vec<T>, E> {
+		let mut out = vec![];
+		while let Some(item) = poll_fn(|cx| Pin::new(&mut self).poll_next(cx)).await {
+			out.push(item?);
+		}
+		Ok(out)
+	}
+}
+
+// the items are never pinned and the pending future is boxed
+impl<T, E> Unpin for Paginator<'_, T, E> {}
+
+impl<T, E> Stream for Paginator<'_, T, E> {
+	type Item = Result<T, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		loop {
+			if let Some(item) = this.buffer.pop_front() {
+				return Poll::Ready(Some(Ok(item)));
+			}
+			if this.done {
+				return Poll::Ready(None);
+			}
+			let pending = this
+				.pending
+				.get_or_insert_with(|| (this.fetch)(this.next_index, this.page_size));
+			let page = match pending.as_mut().poll(cx) {
+				Poll::Ready(page) => page,
+				Poll::Pending => return Poll::Pending,
+			};
+			this.pending = None;
+			match page {
+				Ok(page) => {
+					let received = u32::try_from(page.items.len()).unwrap_or(u32::MAX);
+					this.next_index = this.next_index.saturating_add(received);
+					this.done = received < this.page_size || usize::try_from(this.next_index).map_or(true, |next| next >= page.total);
+					this.buffer.extend(page.items);
+				}
+				Err(e) => {
+					this.done = true;
+					return Poll::Ready(Some(Err(e)));
+				}
+			}
+		}
+	}
+}
+
+impl<T, E> fmt::Debug for Paginator<'_, T, E> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Paginator")
+			.field("page_size", &self.page_size)
+			.field("next_index", &self.next_index)
+			.field("buffered", &self.buffer.len())
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,47 @@
+use std::borrow::Cow;
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+
+/// Characters not allowed in the path segment by RFC 3986 and the ones that the URL parser would treat as the segment end
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+	.add(b' ')
+	.add(b'"')
+	.add(b'#')
+	.add(b'%')
+	.add(b'/')
+	.add(b'<')
+	.add(b'>')
+	.add(b'?')
+	.add(b'[')
+	.add(b'\\')
+	.add(b']')
+	.add(b'^')
+	.add(b'`')
+	.add(b'{')
+	.add(b'|')
+	.add(b'}');
+
+/// Percent-encoding of the values inserted into the endpoint paths, e.g. the serial numbers, set with
+/// [Client::with_path_encoding()](crate::Client::with_path_encoding)
+///
+/// The API matches the serial numbers in the path literally, so the encoding must leave the characters like `-` intact.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PathEncoding {
+	/// Encodes only the characters that can't appear in the path segment, e.g. `7E1234AB-C5` is sent as is and `SN 1/2` as
+	/// `SN%201%2F2`
+	#[default]
+	Segment,
+	/// Encodes every character except the ASCII letters and digits, e.g. `7E1234AB-C5` is sent as `7E1234AB%2DC5`
+	NonAlphanumeric,
+}
+
+impl PathEncoding {
+	/// Encodes the `segment` to be inserted into the endpoint path
+	pub fn encode(self, segment: &str) -> Cow<'_, str> {
+		let set = match self {
+			PathEncoding::Segment => PATH_SEGMENT,
+			PathEncoding::NonAlphanumeric => NON_ALPHANUMERIC,
+		};
+		utf8_percent_encode(segment, set).into()
+	}
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[cfg(feature = "chrono")]
+use crate::api::request;
+use crate::client::bulk_chunks;
+#[cfg(feature = "chrono")]
+use crate::client::{month_windows, week_windows};
+#[cfg(feature = "chrono")]
+use crate::TimeUnit;
+use crate::{RateLimitExceeded, RateLimiter, RateLimits, SiteId};
+
+/// Estimate of the API calls of a job, built before running it to check that it fits the daily budgets
+///
+/// The requests are counted the same way as the [Client](crate::Client) splits them, e.g. the
+/// [equipment_data_range()](RequestPlan::equipment_data_range) counts one request per week of the period, and they are
+/// charged to the budgets the same way as by the [RateLimiter]: the requests targeting the sites are charged to each of the
+/// included sites, the rest to the API key. The retries are not included.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use solaredge::{RateLimits, RequestPlan};
+///
+/// let mut plan = RequestPlan::new();
+/// plan.key_requests(1) // sites list
+///    .bulk_requests(&[1_u64, 2, 3], 1) // site energy of all sites
+///    .polling(1, Duration::from_secs(15 * 60), 2); // power flow and overview of site 1 every 15 minutes
+/// assert_eq!(1 + 1 + 96 * 2, plan.total_requests());
+/// assert_eq!(Ok(()), plan.check(&RateLimits::default()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequestPlan {
+	/// Number of the HTTP requests, a bulk request is counted once regardless of the number of the included sites
+	pub requests: u32,
+	/// Requests charged to the daily budget of the API key
+	pub key_requests: u32,
+	/// Requests charged to the daily budget of every site
+	pub site_requests: BTreeMap<SiteId, u32>,
+}
+
+impl RequestPlan {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds `count` requests that don't target specific sites, e.g. sites or accounts list
+	pub fn key_requests(&mut self, count: u32) -> &mut Self {
+		self.requests = self.requests.saturating_add(count);
+		self.key_requests = self.key_requests.saturating_add(count);
+		self
+	}
+
+	/// Adds `count` requests to the site `site_id`, e.g. site details or overview
+	pub fn site_requests(&mut self, site_id: impl Into<SiteId>, count: u32) -> &mut Self {
+		self.requests = self.requests.saturating_add(count);
+		let site_requests = self.site_requests.entry(site_id.into()).or_default();
+		*site_requests = site_requests.saturating_add(count);
+		self
+	}
+
+	/// Adds `count` calls of the bulk function, e.g. [Client::site_energy_bulk()](crate::Client::site_energy_bulk), for
+	/// the sites `site_ids`, every call is split into the chunks of at most 100 sites
+	pub fn bulk_requests(&mut self, site_ids: &[impl Into<SiteId> + Copy], count: u32) -> &mut Self {
+		let site_ids = site_ids.iter().map(|site_id| (*site_id).into()).collect::<Vec<_>>();
+		let chunks = u32::try_from(bulk_chunks(&site_ids).len()).unwrap_or(u32::MAX);
+		self.requests = self.requests.saturating_add(chunks.saturating_mul(count));
+		for site_id in site_ids {
+			let site_requests = self.site_requests.entry(site_id).or_default();
+			*site_requests = site_requests.saturating_add(count);
+		}
+		self
+	}
+
+	/// Adds a day of polling the site `site_id` every `interval` with `requests_per_poll` requests
+	pub fn polling(&mut self, site_id: impl Into<SiteId>, interval: Duration, requests_per_poll: u32) -> &mut Self {
+		let polls = u32::try_from(div_ceil(86400, interval.as_secs().max(1))).unwrap_or(u32::MAX);
+		self.site_requests(site_id, polls.saturating_mul(requests_per_poll))
+	}
+
+	/// Adds the requests of [Client::site_energy_full_range()](crate::Client::site_energy_full_range), the time unit
+	/// defaults to [TimeUnit::Day] when not set in the `params`
+	#[cfg(feature = "chrono")]
+	pub fn site_energy_full_range(&mut self, site_id: impl Into<SiteId>, params: &request::SiteEnergy) -> &mut Self {
+		let count = match params.time_unit.unwrap_or(TimeUnit::Day).max_energy_period_months() {
+			Some(months) => month_windows(params.start_date, params.end_date, months).len(),
+			None => 1,
+		};
+		self.site_requests(site_id, u32::try_from(count).unwrap_or(u32::MAX))
+	}
+
+	/// Adds the requests of [Client::equipment_data_range()](crate::Client::equipment_data_range)
+	#[cfg(feature = "chrono")]
+	pub fn equipment_data_range(&mut self, site_id: impl Into<SiteId>, params: &request::DateTimeRange) -> &mut Self {
+		let count = week_windows(params.start_time, params.end_time).len();
+		self.site_requests(site_id, u32::try_from(count).unwrap_or(u32::MAX))
+	}
+
+	/// Adds the requests of [Client::site_storage_data_range()](crate::Client::site_storage_data_range)
+	#[cfg(feature = "chrono")]
+	pub fn site_storage_data_range(&mut self, site_id: impl Into<SiteId>, params: &request::SiteStorageData<'_>) -> &mut Self {
+		let count = week_windows(params.start_time, params.end_time).len();
+		self.site_requests(site_id, u32::try_from(count).unwrap_or(u32::MAX))
+	}
+
+	#[inline]
+	pub fn total_requests(&self) -> u32 {
+		self.requests
+	}
+
+	/// Checks that the whole plan fits into a single day of the fresh budgets `limits`, returns the first exceeded budget
+	pub fn check(&self, limits: &RateLimits) -> Result<(), RateLimitExceeded> {
+		self.check_budgets(limits.daily_per_key, |_| limits.daily_per_site)
+	}
+
+	/// Checks that the whole plan fits into the budgets remaining today in the `limiter`, returns the first exceeded budget
+	pub fn check_remaining(&self, limiter: &RateLimiter) -> Result<(), RateLimitExceeded> {
+		self.check_budgets(limiter.remaining_key_budget(), |site_id| {
+			limiter.remaining_site_budget(site_id.0)
+		})
+	}
+
+	/// Number of the days needed to run the plan with the fresh budgets `limits` every day, `None` if any of the budgets
+	/// needed by the plan is zero
+	pub fn days_required(&self, limits: &RateLimits) -> Option<u32> {
+		fn days(requests: u32, limit: Option<u32>) -> Option<u32> {
+			match limit {
+				Some(0) if requests > 0 => None,
+				Some(limit) if limit > 0 => u32::try_from(div_ceil(u64::from(requests), u64::from(limit))).ok(),
+				_ => Some(u32::from(requests > 0)),
+			}
+		}
+
+		let key_days = days(self.key_requests, limits.daily_per_key)?;
+		self.site_requests.values().try_fold(key_days, |out, requests| {
+			days(*requests, limits.daily_per_site).map(|site_days| out.max(site_days))
+		})
+	}
+
+	fn check_budgets(
+		&self,
+		key_budget: Option<u32>,
+		site_budget: impl Fn(SiteId) -> Option<u32>,
+	) -> Result<(), RateLimitExceeded> {
+		if key_budget.is_some_and(|budget| self.key_requests > budget) {
+			return Err(RateLimitExceeded::Key);
+		}
+		match self
+			.site_requests
+			.iter()
+			.find(|(site_id, requests)| site_budget(**site_id).is_some_and(|budget| **requests > budget))
+		{
+			Some((site_id, _)) => Err(RateLimitExceeded::Site(site_id.0)),
+			None => Ok(()),
+		}
+	}
+}
+
+fn div_ceil(lhs: u64, rhs: u64) -> u64 {
+	lhs / rhs + u64::from(lhs % rhs != 0)
+}
@@ -0,0 +1,52 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use log::warn;
+
+type ProcessFn = Arc<dyn Fn(&mut dyn Any) + Send + Sync>;
+
+/// Processor registered with [Client::with_post_processor()](crate::Client::with_post_processor) along with the type it
+/// accepts
+#[derive(Clone)]
+struct PostProcessor {
+	type_id: TypeId,
+	type_name: &'static str,
+	process: ProcessFn,
+}
+
+/// Post-processors by the client function that performs the request, e.g. `site_energy_details`
+#[derive(Clone, Default)]
+pub(crate) struct PostProcessors {
+	processors: HashMap<&'static str, Vec<PostProcessor>>,
+}
+
+impl PostProcessors {
+	pub fn add<T: 'static>(&mut self, endpoint: &'static str, process: impl Fn(&mut T) + Send + Sync + 'static) {
+		self.processors.entry(endpoint).or_default().push(PostProcessor {
+			type_id: TypeId::of::<T>(),
+			type_name: type_name::<T>(),
+			process: Arc::new(move |value| {
+				if let Some(value) = value.downcast_mut::<T>() {
+					process(value)
+				}
+			}),
+		});
+	}
+
+	/// Runs the processors registered for the `endpoint` on the `value` in the order they were added
+	pub fn apply<T: 'static>(&self, endpoint: &str, value: &mut T) {
+		for processor in self.processors.get(endpoint).into_iter().flatten() {
+			if processor.type_id == TypeId::of::<T>() {
+				(processor.process)(value);
+			} else {
+				warn!(
+					"{}, post-processor for {} skipped, the endpoint returns {}",
+					endpoint,
+					processor.type_name,
+					type_name::<T>()
+				);
+			}
+		}
+	}
+}
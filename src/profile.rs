@@ -0,0 +1,32 @@
+use crate::response::Site;
+use crate::{MeterType, TimeUnit};
+
+/// Per-site defaults registered with [Client::with_site_profile()](crate::Client::with_site_profile)
+///
+/// The values are used by the client when the corresponding request parameter is `None`:
+///  * `time_unit` - [Client::site_energy()](crate::Client::site_energy),
+///    [Client::site_energy_details()](crate::Client::site_energy_details) and [Client::site_meters()](crate::Client::site_meters)
+///  * `meters` - [Client::site_power_details()](crate::Client::site_power_details),
+///    [Client::site_energy_details()](crate::Client::site_energy_details) and [Client::site_meters()](crate::Client::site_meters)
+///
+/// The bulk endpoints don't use the profiles.
+#[derive(Clone, Debug, Default)]
+pub struct SiteProfile {
+	/// IANA time zone of the site, e.g. `Europe/Berlin`
+	///
+	/// The API expects and returns the site local time so it's not sent with the requests, it's kept for the callers that
+	/// need to convert the timestamps.
+	pub time_zone: Option<String>,
+	pub time_unit: Option<TimeUnit>,
+	pub meters: Option<Vec<MeterType>>,
+}
+
+impl SiteProfile {
+	/// Profile with the time zone taken from the site location
+	pub fn from_site(site: &Site) -> Self {
+		Self {
+			time_zone: Some(site.location.time_zone.clone()).filter(|tz| !tz.is_empty()),
+			..Self::default()
+		}
+	}
+}
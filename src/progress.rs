@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+/// Receiver of the [Progress] of the client functions that perform several requests, set with
+/// [Client::with_progress()](crate::Client::with_progress)
+///
+/// The progress is reported after each completed request by the bulk functions splitting the site ids into the chunks
+/// (e.g. [Client::site_energy_bulk()](crate::Client::site_energy_bulk)) and the functions splitting the period into the
+/// windows (e.g. [Client::site_energy_full_range()](crate::Client::site_energy_full_range)). It's implemented for the
+/// closures taking `&Progress`.
+pub trait ProgressSink: Send + Sync {
+	fn progress(&self, progress: &Progress);
+}
+
+impl<F> ProgressSink for F
+where
+	F: Fn(&Progress) + Send + Sync,
+{
+	#[inline]
+	fn progress(&self, progress: &Progress) {
+		self(progress)
+	}
+}
+
+/// State of the multi-request operation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress<'a> {
+	/// Client function performing the operation, e.g. `equipment_data_range`
+	pub operation: &'a str,
+	/// Number of the completed requests
+	pub done: usize,
+	/// Total number of the requests in the operation
+	pub total: usize,
+	/// Time since the operation started
+	pub elapsed: Duration,
+}
+
+impl Progress<'_> {
+	/// Completed part of the operation from 0 to 1
+	pub fn fraction(&self) -> f64 {
+		if self.total == 0 {
+			1.
+		} else {
+			self.done as f64 / self.total as f64
+		}
+	}
+
+	/// Estimated time until the operation completes assuming the remaining requests take as long as the completed ones,
+	/// `None` until the first request completes
+	pub fn eta(&self) -> Option<Duration> {
+		let done = u32::try_from(self.done).ok().filter(|done| *done > 0)?;
+		let remaining = u32::try_from(self.total.saturating_sub(self.done)).ok()?;
+		Some(self.elapsed / done * remaining)
+	}
+
+	#[inline]
+	pub fn is_complete(&self) -> bool {
+		self.done >= self.total
+	}
+}
+
+/// Counts the completed requests of the operation and reports them to the sink
+pub(crate) struct ProgressTracker<'s> {
+	sink: Option<&'s dyn ProgressSink>,
+	operation: &'static str,
+	done: usize,
+	total: usize,
+	started: Instant,
+}
+
+impl<'s> ProgressTracker<'s> {
+	pub fn new(sink: Option<&'s dyn ProgressSink>, operation: &'static str, total: usize) -> Self {
+		Self {
+			sink,
+			operation,
+			done: 0,
+			total,
+			started: Instant::now(),
+		}
+	}
+
+	/// Marks one more request as completed
+	pub fn advance(&mut self) {
+		self.done += 1;
+		if let Some(sink) = self.sink {
+			sink.progress(&Progress {
+				operation: self.operation,
+				done: self.done,
+				total: self.total,
+				elapsed: self.started.elapsed(),
+			});
+		}
+	}
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use crate::rate_limit::{day_of, site_ids_from_path, DailyCounts};
+use crate::{RateLimiterUsage, RateLimits, SiteId};
+
+/// Budget that the [QuotaTracker] reports on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum QuotaScope {
+	/// Requests that don't target specific sites, e.g. sites or accounts list
+	Key,
+	/// Requests targeting the site, bulk requests are counted for each of the included sites
+	Site(SiteId),
+}
+
+/// Counts the requests sent by the [Client](crate::Client) today, available with
+/// [Client::quota()](crate::Client::quota)
+///
+/// Unlike the [RateLimiter](crate::RateLimiter) it never rejects the requests, it's meant for the schedulers deciding how
+/// often they can poll. The requests are counted the same way as by the rate limiter: every request attempt including
+/// the retries, the failed ones and the ones served by [Environment::Replay](crate::Environment::Replay), the counts are
+/// reset at midnight UTC. Like [RateLimiter::usage()](crate::RateLimiter::usage) the usage can be persisted with
+/// [QuotaTracker::usage()] and restored after a restart. The tracker is shared between the clones of the client.
+///
+/// # Example
+/// ```
+/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+/// # mod http_adapter_reqwest {
+/// #    #[derive(Default)]
+/// #    pub struct ReqwestAdapter;
+/// #    #[async_trait::async_trait(?Send)]
+/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+/// #       type Error = String;
+/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+/// #    }
+/// # }
+/// use solaredge::{Client, QuotaScope, QuotaTracker, RateLimits, SiteId};
+///
+/// let tracker = QuotaTracker::new(RateLimits::default());
+/// let client = Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY").with_quota_tracker(tracker);
+/// assert_eq!(0, client.quota().used(QuotaScope::Site(SiteId(1))));
+/// assert_eq!(Some(300), client.quota().remaining(QuotaScope::Key));
+/// ```
+pub struct QuotaTracker {
+	limits: RateLimits,
+	counts: Mutex<DailyCounts>,
+}
+
+impl QuotaTracker {
+	/// Tracker reporting the remaining budgets against the daily limits of the `limits`
+	pub fn new(limits: RateLimits) -> Self {
+		Self {
+			limits,
+			counts: Mutex::new(DailyCounts::default()),
+		}
+	}
+
+	#[inline]
+	pub fn limits(&self) -> &RateLimits {
+		&self.limits
+	}
+
+	/// Requests counted today for the `scope`
+	pub fn used(&self, scope: QuotaScope) -> u32 {
+		self.used_at(scope, SystemTime::now())
+	}
+
+	/// Requests left today for the `scope`, `None` if the corresponding limit is disabled
+	pub fn remaining(&self, scope: QuotaScope) -> Option<u32> {
+		self.remaining_at(scope, SystemTime::now())
+	}
+
+	/// Snapshot of today's usage, can be persisted and passed to [QuotaTracker::restore()] after a restart
+	pub fn usage(&self) -> RateLimiterUsage {
+		self.lock_counts(SystemTime::now()).usage()
+	}
+
+	/// Restores the usage saved with [QuotaTracker::usage()] or [RateLimiter::usage()](crate::RateLimiter::usage)
+	///
+	/// The usage is only applied if it was saved today (UTC), the usage of a previous day is ignored and `false` is
+	/// returned. The restored counts are added to the requests made since this tracker was created.
+	pub fn restore(&self, usage: &RateLimiterUsage) -> bool {
+		self.lock_counts(SystemTime::now()).restore(usage)
+	}
+
+	pub(crate) fn used_at(&self, scope: QuotaScope, now: SystemTime) -> u32 {
+		let counts = self.lock_counts(now);
+		match scope {
+			QuotaScope::Key => counts.key_used(),
+			QuotaScope::Site(site_id) => counts.site_used(site_id.0),
+		}
+	}
+
+	pub(crate) fn remaining_at(&self, scope: QuotaScope, now: SystemTime) -> Option<u32> {
+		let limit = match scope {
+			QuotaScope::Key => self.limits.daily_per_key,
+			QuotaScope::Site(_) => self.limits.daily_per_site,
+		};
+		limit.map(|limit| limit.saturating_sub(self.used_at(scope, now)))
+	}
+
+	/// Counts the request for the endpoint `path`
+	pub(crate) fn record(&self, path: &str) {
+		self.record_at(path, SystemTime::now());
+	}
+
+	pub(crate) fn record_at(&self, path: &str, now: SystemTime) {
+		self.lock_counts(now).add(&site_ids_from_path(path));
+	}
+
+	/// Locks the counts resetting them if the day of `now` has changed
+	fn lock_counts(&self, now: SystemTime) -> MutexGuard<'_, DailyCounts> {
+		let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+		counts.roll_over(day_of(now));
+		counts
+	}
+}
+
+impl Default for QuotaTracker {
+	#[inline]
+	fn default() -> Self {
+		Self::new(RateLimits::default())
+	}
+}
+
+impl fmt::Debug for QuotaTracker {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("QuotaTracker")
+			.field("limits", &self.limits)
+			.field("key_used", &self.used(QuotaScope::Key))
+			.finish()
+	}
+}
@@ -0,0 +1,339 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::limits;
+
+/// Limits enforced by [RateLimiter], `None` disables the corresponding limit
+///
+/// The defaults match the documented SolarEdge quota: 300 requests per day per account key and per site, 3 concurrent
+/// requests, see the [limits](crate::limits) module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimits {
+	/// Daily budget for the requests that don't target specific sites, e.g. sites or accounts list
+	pub daily_per_key: Option<u32>,
+	/// Daily budget for every site, bulk requests are charged to each of the included sites
+	pub daily_per_site: Option<u32>,
+	/// Maximum number of the requests in flight, the extra requests wait for a free slot
+	pub concurrent: Option<usize>,
+}
+
+impl Default for RateLimits {
+	fn default() -> Self {
+		Self {
+			daily_per_key: Some(limits::DAILY_QUOTA_PER_KEY),
+			daily_per_site: Some(limits::DAILY_QUOTA_PER_SITE),
+			concurrent: Some(limits::MAX_CONCURRENT_REQUESTS),
+		}
+	}
+}
+
+/// Priority of the requests waiting for a free concurrency slot of the [RateLimiter], set with
+/// [Client::with_priority()](crate::Client::with_priority)
+///
+/// When the concurrency limit is reached the waiting requests are dispatched highest priority first and in the order of
+/// arrival within the same priority, so e.g. the realtime power flow polling isn't delayed by the backfill of the history.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RequestPriority {
+	/// Background work that can wait, e.g. backfill of the history
+	Low,
+	#[default]
+	Normal,
+	/// Latency-sensitive requests, e.g. realtime power flow
+	High,
+}
+
+/// Daily budget that a request would exceed, returned in [Error::BudgetExhausted](crate::Error::BudgetExhausted)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitExceeded {
+	Key,
+	Site(u64),
+}
+
+impl fmt::Display for RateLimitExceeded {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			RateLimitExceeded::Key => f.write_str("daily request budget of the API key is exhausted"),
+			RateLimitExceeded::Site(site_id) => write!(f, "daily request budget of the site {site_id} is exhausted"),
+		}
+	}
+}
+
+/// Budget usage of a [RateLimiter] or a [QuotaTracker](crate::QuotaTracker) for a single day, see [RateLimiter::usage()]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimiterUsage {
+	/// Days since the Unix epoch (UTC) that the usage belongs to
+	pub day: u64,
+	pub key_used: u32,
+	pub site_used: HashMap<u64, u32>,
+}
+
+/// Tracks the request budgets of a [Client](crate::Client), enabled with
+/// [Client::with_rate_limits()](crate::Client::with_rate_limits)
+///
+/// Requests that would exceed a daily budget are rejected without being sent, requests above the concurrency limit are
+/// delayed until one of the running requests finishes and then dispatched by their [RequestPriority]. Budgets are reset at midnight UTC. The limiter is shared between
+/// the clones of the client.
+pub struct RateLimiter {
+	limits: RateLimits,
+	state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+	counts: DailyCounts,
+	active: usize,
+	/// Requests waiting for a free slot in the dispatch order
+	waiters: BTreeMap<WaiterKey, Waker>,
+	/// Arrival order of the next waiting request
+	next_waiter: u64,
+}
+
+type WaiterKey = (Reverse<RequestPriority>, u64);
+
+impl State {
+	/// Wakes the next waiting request if there is a free slot
+	fn wake_next(&self, limits: &RateLimits) {
+		if limits.concurrent.map_or(true, |limit| self.active < limit) {
+			if let Some(waker) = self.waiters.values().next() {
+				waker.wake_by_ref();
+			}
+		}
+	}
+}
+
+impl RateLimiter {
+	pub fn new(limits: RateLimits) -> Self {
+		Self {
+			limits,
+			state: Mutex::new(State::default()),
+		}
+	}
+
+	#[inline]
+	pub fn limits(&self) -> &RateLimits {
+		&self.limits
+	}
+
+	/// Requests left today for the API key, `None` if the limit is disabled
+	pub fn remaining_key_budget(&self) -> Option<u32> {
+		let state = self.lock_state();
+		self
+			.limits
+			.daily_per_key
+			.map(|limit| limit.saturating_sub(state.counts.key_used))
+	}
+
+	/// Requests left today for the site `site_id`, `None` if the limit is disabled
+	pub fn remaining_site_budget(&self, site_id: u64) -> Option<u32> {
+		let state = self.lock_state();
+		let used = state.counts.site_used(site_id);
+		self.limits.daily_per_site.map(|limit| limit.saturating_sub(used))
+	}
+
+	/// Number of the requests currently in flight
+	pub fn active_requests(&self) -> usize {
+		self.state.lock().unwrap_or_else(|e| e.into_inner()).active
+	}
+
+	/// Snapshot of today's budget usage, can be persisted and passed to [RateLimiter::restore()] after a restart so
+	/// that the process doesn't assume a fresh daily budget
+	pub fn usage(&self) -> RateLimiterUsage {
+		self.lock_state().counts.usage()
+	}
+
+	/// Restores the budget usage saved with [RateLimiter::usage()]
+	///
+	/// The usage is only applied if it was saved today (UTC), the usage of a previous day is ignored and `false` is
+	/// returned. The restored counts are added to the requests made since this limiter was created.
+	pub fn restore(&self, usage: &RateLimiterUsage) -> bool {
+		self.lock_state().counts.restore(usage)
+	}
+
+	/// Locks the state resetting the budgets if the day has changed
+	fn lock_state(&self) -> MutexGuard<'_, State> {
+		let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+		state.counts.roll_over(today());
+		state
+	}
+
+	/// Charges the request for the endpoint `path` to the budgets, nothing is charged if any of the budgets is exhausted
+	pub(crate) fn charge(&self, path: &str) -> Result<(), RateLimitExceeded> {
+		let site_ids = site_ids_from_path(path);
+		let mut state = self.lock_state();
+		if site_ids.is_empty() {
+			if self.limits.daily_per_key.is_some_and(|limit| state.counts.key_used >= limit) {
+				return Err(RateLimitExceeded::Key);
+			}
+		} else if let Some(limit) = self.limits.daily_per_site {
+			if let Some(&site_id) = site_ids.iter().find(|site_id| state.counts.site_used(**site_id) >= limit) {
+				return Err(RateLimitExceeded::Site(site_id));
+			}
+		}
+		state.counts.add(&site_ids);
+		Ok(())
+	}
+
+	/// Waits for a free concurrency slot, the slot is released when the returned permit is dropped
+	pub(crate) fn acquire(&self, priority: RequestPriority) -> Acquire<'_> {
+		Acquire {
+			limiter: self,
+			priority,
+			waiter: None,
+		}
+	}
+}
+
+impl fmt::Debug for RateLimiter {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("RateLimiter")
+			.field("limits", &self.limits)
+			.field("active_requests", &self.active_requests())
+			.finish()
+	}
+}
+
+pub(crate) struct Acquire<'l> {
+	limiter: &'l RateLimiter,
+	priority: RequestPriority,
+	/// Position in the queue once the request had to wait
+	waiter: Option<WaiterKey>,
+}
+
+impl<'l> Future for Acquire<'l> {
+	type Output = Permit<'l>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		let limiter = this.limiter;
+		let mut state = limiter.state.lock().unwrap_or_else(|e| e.into_inner());
+		let key = this.waiter.unwrap_or((Reverse(this.priority), state.next_waiter));
+		let free = limiter.limits.concurrent.map_or(true, |limit| state.active < limit);
+		if free && state.waiters.keys().next().map_or(true, |first| key <= *first) {
+			if let Some(waiter) = this.waiter.take() {
+				state.waiters.remove(&waiter);
+			}
+			state.active += 1;
+			state.wake_next(&limiter.limits);
+			Poll::Ready(Permit { limiter })
+		} else {
+			if this.waiter.is_none() {
+				state.next_waiter += 1;
+				this.waiter = Some(key);
+			}
+			state.waiters.insert(key, cx.waker().clone());
+			Poll::Pending
+		}
+	}
+}
+
+impl Drop for Acquire<'_> {
+	fn drop(&mut self) {
+		if let Some(waiter) = self.waiter {
+			let mut state = self.limiter.state.lock().unwrap_or_else(|e| e.into_inner());
+			state.waiters.remove(&waiter);
+			state.wake_next(&self.limiter.limits);
+		}
+	}
+}
+
+pub(crate) struct Permit<'l> {
+	limiter: &'l RateLimiter,
+}
+
+impl Drop for Permit<'_> {
+	fn drop(&mut self) {
+		let mut state = self.limiter.state.lock().unwrap_or_else(|e| e.into_inner());
+		state.active -= 1;
+		state.wake_next(&self.limiter.limits);
+	}
+}
+
+/// Requests counted on a single UTC day, shared by the [RateLimiter] and the [QuotaTracker](crate::QuotaTracker)
+#[derive(Default)]
+pub(crate) struct DailyCounts {
+	/// Days since the Unix epoch
+	day: u64,
+	key_used: u32,
+	site_used: HashMap<u64, u32>,
+}
+
+impl DailyCounts {
+	#[inline]
+	pub fn key_used(&self) -> u32 {
+		self.key_used
+	}
+
+	#[inline]
+	pub fn site_used(&self, site_id: u64) -> u32 {
+		self.site_used.get(&site_id).copied().unwrap_or(0)
+	}
+
+	/// Resets the counts if the `day` has changed
+	pub fn roll_over(&mut self, day: u64) {
+		if self.day != day {
+			*self = Self { day, ..Self::default() };
+		}
+	}
+
+	/// Counts the request for the API key if `site_ids` is empty, otherwise for each of the sites
+	pub fn add(&mut self, site_ids: &[u64]) {
+		if site_ids.is_empty() {
+			self.key_used = self.key_used.saturating_add(1);
+		} else {
+			for site_id in site_ids {
+				let used = self.site_used.entry(*site_id).or_default();
+				*used = used.saturating_add(1);
+			}
+		}
+	}
+
+	pub fn usage(&self) -> RateLimiterUsage {
+		RateLimiterUsage {
+			day: self.day,
+			key_used: self.key_used,
+			site_used: self.site_used.clone(),
+		}
+	}
+
+	/// Adds the `usage` if it's of the same day, returns `false` otherwise
+	pub fn restore(&mut self, usage: &RateLimiterUsage) -> bool {
+		if usage.day != self.day {
+			return false;
+		}
+		self.key_used = self.key_used.saturating_add(usage.key_used);
+		for (site_id, used) in &usage.site_used {
+			let site_used = self.site_used.entry(*site_id).or_default();
+			*site_used = site_used.saturating_add(*used);
+		}
+		true
+	}
+}
+
+/// Days since the Unix epoch in UTC
+fn today() -> u64 {
+	day_of(SystemTime::now())
+}
+
+/// Days since the Unix epoch in UTC at `time`
+pub(crate) fn day_of(time: SystemTime) -> u64 {
+	time
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |since_epoch| since_epoch.as_secs() / 86400)
+}
+
+/// Extracts the ids of the sites targeted by the endpoint, e.g. `/sites/1,2/energy.json` or `/equipment/1/<serial>/data.json`
+pub(crate) fn site_ids_from_path(path: &str) -> Vec<u64> {
+	let mut segments = path.trim_start_matches('/').split('/');
+	match (segments.next(), segments.next()) {
+		(Some("site" | "sites" | "equipment"), Some(ids)) => ids.split(',').filter_map(|id| id.parse().ok()).collect(),
+		_ => vec![],
+	}
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// Stable identity of a request, the endpoint path along with its parameters
+///
+/// Two requests have equal keys when they'd return the same data: the order of the query parameters doesn't matter and
+/// the API key is not included. The [hash](RequestKey::stable_hash) is FNV-1a of the canonical form so it stays the same across
+/// processes and crate versions and can be stored, e.g. as an idempotency key in a job queue. The client reports the key
+/// of every request in [ResponseMeta::request_key](crate::ResponseMeta::request_key).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RequestKey {
+	canonical: String,
+}
+
+impl RequestKey {
+	/// Key of the request to the endpoint `path` (e.g. `/site/1/energy.json`) with the `params`, same as the request
+	/// structs passed to the corresponding [Client](crate::Client) function
+	pub fn new(path: &str, params: impl Serialize) -> Result<Self, serde_urlencoded::ser::Error> {
+		let query = serde_urlencoded::to_string(params)?;
+		let mut pairs = url::form_urlencoded::parse(query.as_bytes())
+			.filter(|(key, _)| key != "api_key")
+			.collect::<Vec<_>>();
+		pairs.sort();
+		let mut canonical = format!("/{}", path.trim_start_matches('/'));
+		if !pairs.is_empty() {
+			canonical.push('?');
+			canonical.push_str(
+				&url::form_urlencoded::Serializer::new(String::new())
+					.extend_pairs(pairs)
+					.finish(),
+			);
+		}
+		Ok(Self { canonical })
+	}
+
+	/// Canonical form of the request, path followed by the sorted query, e.g. `/site/1/energy.json?endDate=...&startDate=...`
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		&self.canonical
+	}
+
+	/// 64-bit FNV-1a hash of the canonical form
+	#[inline]
+	pub fn stable_hash(&self) -> u64 {
+		fnv1a(self.canonical.as_bytes())
+	}
+
+	/// Same request made by the `account`, the canonical form is prefixed with it, e.g. `1f2e3d4c5b6a7988:/site/1/energy.json`
+	pub(crate) fn scoped(&self, account: u64) -> Self {
+		Self {
+			canonical: format!("{account:016x}:{}", self.canonical),
+		}
+	}
+}
+
+/// 64-bit FNV-1a hash of the `bytes`
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+	const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+	const PRIME: u64 = 0x0000_0100_0000_01b3;
+	bytes
+		.iter()
+		.fold(OFFSET_BASIS, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+}
+
+/// Formats the [hash](RequestKey::stable_hash) as 16 hex digits
+impl fmt::Display for RequestKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:016x}", self.stable_hash())
+	}
+}
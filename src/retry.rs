@@ -0,0 +1,102 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use http_adapter::http::StatusCode;
+
+use crate::Error;
+
+/// Retry schedule for the failed requests, enabled with [Client::with_retry()](crate::Client::with_retry)
+///
+/// The delay before the retry number `n` (starting from 1) is `initial_backoff * multiplier^(n - 1)` capped at
+/// `max_backoff` and then reduced by a random fraction of up to `jitter` so that the clients failing at the same time
+/// don't retry in lockstep. [Error::RateLimited] is retried when `retry_on_status` contains HTTP 429, the delay is then
+/// at least the one requested by the API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+	/// Total number of attempts including the first one, `1` disables the retries
+	pub max_attempts: u32,
+	pub initial_backoff: Duration,
+	pub max_backoff: Duration,
+	pub multiplier: f64,
+	/// Fraction of the delay that is randomized, from `0.` (no jitter) to `1.` (full jitter)
+	pub jitter: f64,
+	/// HTTP statuses of the [Error::Api] that are retried
+	pub retry_on_status: Vec<StatusCode>,
+	/// Whether to retry the [Error::HttpRequest] returned by the HTTP client adapter, e.g. connection failures
+	pub retry_on_http_error: bool,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self {
+			max_attempts: 3,
+			initial_backoff: Duration::from_millis(500),
+			max_backoff: Duration::from_secs(30),
+			multiplier: 2.,
+			jitter: 0.5,
+			retry_on_status: vec![
+				StatusCode::INTERNAL_SERVER_ERROR,
+				StatusCode::BAD_GATEWAY,
+				StatusCode::SERVICE_UNAVAILABLE,
+				StatusCode::GATEWAY_TIMEOUT,
+			],
+			retry_on_http_error: true,
+		}
+	}
+}
+
+impl RetryPolicy {
+	/// `true` if the `error` is transient according to this policy
+	pub fn is_retryable<E>(&self, error: &Error<E>) -> bool {
+		match error {
+			Error::HttpRequest(_) => self.retry_on_http_error,
+			Error::Api(status, _) => self.retry_on_status.contains(status),
+			Error::RateLimited { .. } => self.retry_on_status.contains(&StatusCode::TOO_MANY_REQUESTS),
+			_ => false,
+		}
+	}
+
+	/// Delay before the next attempt after the `attempt` (starting from 1) failed with `error`, `None` if the request
+	/// should not be retried
+	pub fn delay_for<E>(&self, attempt: u32, error: &Error<E>) -> Option<Duration> {
+		if attempt >= self.max_attempts || !self.is_retryable(error) {
+			return None;
+		}
+		let exponent = i32::try_from(attempt - 1).unwrap_or(i32::MAX);
+		let backoff = (self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent)).min(self.max_backoff.as_secs_f64());
+		let jitter = self.jitter.clamp(0., 1.) * random_fraction();
+		let delay = Duration::from_secs_f64((backoff * (1. - jitter)).max(0.));
+		Some(match error {
+			Error::RateLimited {
+				retry_after: Some(retry_after),
+				..
+			} => delay.max(*retry_after),
+			_ => delay,
+		})
+	}
+}
+
+/// Random number in `[0, 1)`, the quality is more than enough for the jitter
+fn random_fraction() -> f64 {
+	let random = RandomState::new().build_hasher().finish();
+	(random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub(crate) type SleepFn = dyn Fn(Duration) -> Pin<Box<dyn Future<Output = ()>>> + Send + Sync;
+
+#[derive(Clone)]
+pub(crate) struct Retry {
+	pub policy: RetryPolicy,
+	pub sleep: Arc<SleepFn>,
+}
+
+impl fmt::Debug for Retry {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Retry").field("policy", &self.policy).finish_non_exhaustive()
+	}
+}
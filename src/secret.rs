@@ -0,0 +1,161 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{env, fmt, fs};
+
+/// Error of the [SecretProvider]
+pub type SecretError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Source of the API key consulted by the [Client](crate::Client) before every request
+///
+/// Allows the key to be kept out of the application configuration and rotated without recreating the client, pass it to
+/// [Client::new_with_secret_provider()](crate::Client::new_with_secret_provider). Implementations are provided for
+/// [EnvSecret], [FileSecret] and for the closures returning `Result<String, SecretError>`, e.g. the one calling into the
+/// secret store like Vault. The provider is called for every request, so it should cache the key if getting it is
+/// expensive.
+pub trait SecretProvider: Send + Sync {
+	/// Current API key
+	fn api_key(&self) -> Result<String, SecretError>;
+
+	/// Called when the API rejects the key with HTTP 403, should drop the cached key if any and return `true` if the key
+	/// may have changed, in which case the request is retried once with the new key
+	fn refresh(&self) -> bool {
+		false
+	}
+}
+
+impl<F> SecretProvider for F
+where
+	F: Fn() -> Result<String, SecretError> + Send + Sync,
+{
+	fn api_key(&self) -> Result<String, SecretError> {
+		self()
+	}
+}
+
+/// Reads the API key from the environment variable
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvSecret {
+	var: String,
+}
+
+impl EnvSecret {
+	pub fn new(var: impl Into<String>) -> Self {
+		Self { var: var.into() }
+	}
+}
+
+impl SecretProvider for EnvSecret {
+	fn api_key(&self) -> Result<String, SecretError> {
+		env::var(&self.var).map_err(|e| format!("Can't read the API key from the environment variable {}: {e}", self.var).into())
+	}
+
+	#[inline]
+	fn refresh(&self) -> bool {
+		true
+	}
+}
+
+/// Reads the API key from the file, the surrounding whitespace is trimmed
+///
+/// The file is read for every request so the key can be rotated by replacing the file, e.g. the mounted Kubernetes secret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileSecret {
+	path: PathBuf,
+}
+
+impl FileSecret {
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into() }
+	}
+}
+
+impl SecretProvider for FileSecret {
+	fn api_key(&self) -> Result<String, SecretError> {
+		fs::read_to_string(&self.path)
+			.map(|key| key.trim().to_string())
+			.map_err(|e| format!("Can't read the API key from the file {}: {e}", self.path.display()).into())
+	}
+
+	#[inline]
+	fn refresh(&self) -> bool {
+		true
+	}
+}
+
+/// String holding the API key, wiped on drop with the `zeroize` feature
+#[cfg(feature = "zeroize")]
+pub(crate) type KeyString = zeroize::Zeroizing<String>;
+#[cfg(not(feature = "zeroize"))]
+pub(crate) type KeyString = String;
+
+#[inline]
+pub(crate) fn key_string(key: String) -> KeyString {
+	#[cfg(feature = "zeroize")]
+	return zeroize::Zeroizing::new(key);
+	#[cfg(not(feature = "zeroize"))]
+	key
+}
+
+/// API key of the client, either fixed at construction time or provided on demand
+#[derive(Clone)]
+pub(crate) enum ApiKey {
+	Plain(KeyString),
+	Provider(Arc<dyn SecretProvider>),
+}
+
+impl ApiKey {
+	pub fn get(&self) -> Result<KeyString, SecretError> {
+		match self {
+			ApiKey::Plain(key) => Ok(key.clone()),
+			ApiKey::Provider(provider) => provider.api_key().map(key_string),
+		}
+	}
+
+	pub fn refresh(&self) -> bool {
+		match self {
+			ApiKey::Plain(_) => false,
+			ApiKey::Provider(provider) => provider.refresh(),
+		}
+	}
+}
+
+/// Drops the request URL containing the API key, wiping it with the `zeroize` feature
+pub(crate) fn wipe_url(url: url::Url) {
+	#[cfg(feature = "zeroize")]
+	zeroize::Zeroize::zeroize(&mut String::from(url));
+	#[cfg(not(feature = "zeroize"))]
+	drop(url);
+}
+
+/// Replaces the values of the `api_key` query parameters in `s` with `<hidden>`, the errors of the HTTP client adapters
+/// often include the request URL
+pub(crate) fn redact_api_key(s: &str) -> Cow<'_, str> {
+	const PARAM: &str = "api_key=";
+	if !s.contains(PARAM) {
+		return Cow::Borrowed(s);
+	}
+	let mut out = String::with_capacity(s.len());
+	let mut rest = s;
+	while let Some(pos) = rest.find(PARAM) {
+		let value_start = pos + PARAM.len();
+		out.push_str(&rest[..value_start]);
+		out.push_str("<hidden>");
+		rest = &rest[value_start..];
+		let value_end = rest
+			.find(|c: char| matches!(c, '&' | '#' | ')' | '"' | '\'' | '>' | ',') || c.is_whitespace())
+			.unwrap_or(rest.len());
+		rest = &rest[value_end..];
+	}
+	out.push_str(rest);
+	Cow::Owned(out)
+}
+
+/// Formats the [fmt::Debug] output of the value with [redact_api_key()] applied
+pub(crate) struct RedactedDebug<'a, T>(pub &'a T);
+
+impl<T: fmt::Debug> fmt::Debug for RedactedDebug<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&redact_api_key(&format!("{:?}", self.0)))
+	}
+}
@@ -0,0 +1,258 @@
+//! Post-processing helpers for the time series returned by the API
+
+use std::collections::HashMap;
+
+use crate::api::DateTime;
+use crate::response;
+
+/// Item of the time series that carries its timestamp
+pub trait Timestamped {
+	fn timestamp(&self) -> &DateTime;
+}
+
+impl Timestamped for response::SiteDateValue {
+	#[inline]
+	fn timestamp(&self) -> &DateTime {
+		&self.date
+	}
+}
+
+impl Timestamped for response::EquipmentTelemetry {
+	#[inline]
+	fn timestamp(&self) -> &DateTime {
+		&self.date
+	}
+}
+
+impl Timestamped for response::SensorTelemetry {
+	#[inline]
+	fn timestamp(&self) -> &DateTime {
+		&self.date
+	}
+}
+
+impl Timestamped for response::BatteryTelemetry {
+	#[inline]
+	fn timestamp(&self) -> &DateTime {
+		&self.timestamp
+	}
+}
+
+/// Removes the items with duplicate timestamps keeping the latest one (the one that comes last in `series`)
+///
+/// The API sometimes repeats the boundary samples when the range is fetched in several requests, this function can be
+/// used after concatenating the results of those requests. Returns the number of the removed items.
+pub fn dedup_by_timestamp<T: Timestamped>(series: &mut Vec<T>) -> usize {
+	let mut last_index = HashMap::with_capacity(series.len());
+	for (i, item) in series.iter().enumerate() {
+		last_index.insert(item.timestamp(), i);
+	}
+	let mut keep = vec![false; series.len()];
+	for i in last_index.into_values() {
+		keep[i] = true;
+	}
+	let len = series.len();
+	let mut keep = keep.into_iter();
+	series.retain(|_| keep.next().unwrap_or(true));
+	len - series.len()
+}
+
+/// Sorts the series by timestamp, the relative order of the items with equal timestamps is preserved
+pub fn sort_by_timestamp<T: Timestamped>(series: &mut [T]) {
+	if find_non_monotonic(series).is_some() {
+		series.sort_by(|a, b| a.timestamp().cmp(b.timestamp()));
+	}
+}
+
+/// Returns the index of the first item which timestamp is not strictly greater than the timestamp of the previous one
+///
+/// `None` means that the series is sorted and contains no duplicate timestamps.
+pub fn find_non_monotonic<T: Timestamped>(series: &[T]) -> Option<usize> {
+	series
+		.windows(2)
+		.position(|pair| pair[0].timestamp() >= pair[1].timestamp())
+		.map(|i| i + 1)
+}
+
+/// Response that contains one or more time series
+pub trait SortSeries {
+	/// Sorts every contained series by timestamp
+	fn sort_series(&mut self);
+
+	/// `true` if every contained series is strictly increasing by timestamp
+	fn is_series_monotonic(&self) -> bool;
+}
+
+impl<T: Timestamped> SortSeries for Vec<T> {
+	#[inline]
+	fn sort_series(&mut self) {
+		sort_by_timestamp(self);
+	}
+
+	#[inline]
+	fn is_series_monotonic(&self) -> bool {
+		find_non_monotonic(self).is_none()
+	}
+}
+
+macro_rules! impl_sort_series {
+	($($typ: ty => $series: ident;)+) => {
+		$(
+			impl SortSeries for $typ {
+				fn sort_series(&mut self) {
+					self.$series.sort_series();
+				}
+
+				fn is_series_monotonic(&self) -> bool {
+					self.$series.is_series_monotonic()
+				}
+			}
+		)+
+	};
+}
+
+macro_rules! impl_sort_series_list {
+	($($typ: ty => $list: ident;)+) => {
+		$(
+			impl SortSeries for $typ {
+				fn sort_series(&mut self) {
+					self.$list.iter_mut().for_each(SortSeries::sort_series);
+				}
+
+				fn is_series_monotonic(&self) -> bool {
+					self.$list.iter().all(SortSeries::is_series_monotonic)
+				}
+			}
+		)+
+	};
+}
+
+impl_sort_series! {
+	response::SiteEnergy => values;
+	response::SiteEnergyValues => values;
+	response::SiteEnergyBulk => energy_values;
+	response::SiteEnergyList => power_data_value_series;
+	response::SitePower => values;
+	response::SiteMeterValue => values;
+	response::SiteMeterValueExt => values;
+	response::StorageBattery => telemetries;
+	response::EquipmentData => telemetries;
+	response::GatewaySensorData => telemetries;
+}
+
+impl_sort_series_list! {
+	response::SiteEnergyBulkList => site_energy_list;
+	response::SitePowerValueList => site_energy_list;
+	response::SiteMetersDetails => meters;
+	response::SiteMeters => meters;
+	response::SiteStorageData => batteries;
+}
+
+/// Resolution of the site local times that are ambiguous or nonexistent because of the DST transition, see [local_to_utc()]
+///
+/// For the fold (the repeated hour in autumn) the two candidate instants are the ones with the summer and the winter
+/// offsets. For the gap (the skipped hour in spring) the candidates are the local time interpreted with the offset before
+/// and after the transition.
+#[cfg(feature = "chrono")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DstPolicy {
+	/// Earlier of the two candidate instants
+	#[default]
+	Earliest,
+	/// Later of the two candidate instants
+	Latest,
+	/// Drop the item
+	Skip,
+	/// Fail with [DstError]
+	Error,
+}
+
+/// Kind of the DST transition that made the local time ambiguous
+#[cfg(feature = "chrono")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DstTransition {
+	/// Local time occurs twice
+	Fold,
+	/// Local time doesn't exist
+	Gap,
+}
+
+/// Local time that couldn't be converted to UTC with [DstPolicy::Error]
+#[cfg(feature = "chrono")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DstError {
+	pub local: DateTime,
+	pub transition: DstTransition,
+}
+
+#[cfg(feature = "chrono")]
+impl std::fmt::Display for DstError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self.transition {
+			DstTransition::Fold => write!(f, "Local time {} is ambiguous because of the DST transition", self.local),
+			DstTransition::Gap => write!(f, "Local time {} doesn't exist because of the DST transition", self.local),
+		}
+	}
+}
+
+#[cfg(feature = "chrono")]
+impl std::error::Error for DstError {}
+
+/// Converts the site `local` time to UTC in the time zone `tz` resolving the DST fold and gap according to the `policy`
+///
+/// Returns `Ok(None)` if the time is ambiguous or nonexistent and the `policy` is [DstPolicy::Skip].
+#[cfg(feature = "chrono")]
+pub fn local_to_utc<Tz: chrono::TimeZone>(
+	local: &DateTime,
+	tz: &Tz,
+	policy: DstPolicy,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, DstError> {
+	use chrono::{Duration, LocalResult, Offset, TimeZone, Utc};
+
+	let (transition, earliest, latest) = match tz.from_local_datetime(local) {
+		LocalResult::Single(d) => return Ok(Some(d.with_timezone(&Utc))),
+		LocalResult::Ambiguous(a, b) => (DstTransition::Fold, a.with_timezone(&Utc), b.with_timezone(&Utc)),
+		LocalResult::None => {
+			// offsets in effect well before and after the transition
+			let offset_at = |shift| {
+				tz.from_local_datetime(&(*local + shift))
+					.earliest()
+					.map(|d| d.offset().fix().local_minus_utc())
+			};
+			let error = DstError {
+				local: *local,
+				transition: DstTransition::Gap,
+			};
+			let before = offset_at(Duration::hours(-6)).ok_or_else(|| error.clone())?;
+			let after = offset_at(Duration::hours(6)).ok_or(error)?;
+			let with_offset = |offset: i32| Utc.from_utc_datetime(&(*local - Duration::seconds(i64::from(offset))));
+			let (a, b) = (with_offset(before), with_offset(after));
+			(DstTransition::Gap, a.min(b), a.max(b))
+		}
+	};
+	match policy {
+		DstPolicy::Earliest => Ok(Some(earliest)),
+		DstPolicy::Latest => Ok(Some(latest)),
+		DstPolicy::Skip => Ok(None),
+		DstPolicy::Error => Err(DstError {
+			local: *local,
+			transition,
+		}),
+	}
+}
+
+/// Pairs every item of the series of the site local times with its UTC timestamp, see [local_to_utc()]
+#[cfg(feature = "chrono")]
+pub fn series_to_utc<'s, T: Timestamped, Tz: chrono::TimeZone>(
+	series: &'s [T],
+	tz: &Tz,
+	policy: DstPolicy,
+) -> Result<Vec<(chrono::DateTime<chrono::Utc>, &'s T)>, DstError> {
+	let mut out = Vec::with_capacity(series.len());
+	for item in series {
+		if let Some(utc) = local_to_utc(item.timestamp(), tz, policy)? {
+			out.push((utc, item));
+		}
+	}
+	Ok(out)
+}
@@ -0,0 +1,93 @@
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use http_adapter::HttpClientAdapter;
+use serde::Serialize;
+use tower_service::Service;
+
+use crate::{Client, Error, RequestKey};
+
+/// Request to an API endpoint for the [Service] implementation of the [Client], enabled with the `tower` feature
+///
+/// The [Client] as a `tower::Service<Endpoint>` goes through the same pipeline as [Client::fetch_raw()] (the API key, the
+/// rate limits, the retries, the audit and the response metadata) and returns the raw response body, so the standard tower
+/// layers like the timeouts or the load shedding can be stacked on top of it. The client is cloned for every call, so the
+/// returned future doesn't borrow the service.
+///
+/// # Example
+/// ```
+/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+/// # mod http_adapter_reqwest {
+/// #    #[derive(Clone, Default)]
+/// #    pub struct ReqwestAdapter;
+/// #    #[async_trait::async_trait(?Send)]
+/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+/// #       type Error = String;
+/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+/// #    }
+/// # }
+/// use tower_service::Service;
+///
+/// async fn run() -> Result<(), solaredge::Error<String>> {
+///    let mut client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+///    let endpoint = solaredge::Endpoint::new("site_overview", "/site/1/overview.json", ())?;
+///    let body = client.call(endpoint).await?;
+///    Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+	/// Name reported in the logs, the [ErrorReport](crate::ErrorReport) and the [AuditEntry](crate::AuditEntry), e.g.
+	/// `site_overview`
+	pub name: Cow<'static, str>,
+	/// Endpoint path, e.g. `/site/1/overview.json`
+	pub path: String,
+	/// Query parameters without the API key
+	pub params: Vec<(String, String)>,
+}
+
+impl Endpoint {
+	/// Request to the endpoint `path` with the `params`, same as the request structs passed to the corresponding [Client]
+	/// function
+	pub fn new(
+		name: impl Into<Cow<'static, str>>,
+		path: impl Into<String>,
+		params: impl Serialize,
+	) -> Result<Self, serde_urlencoded::ser::Error> {
+		let query = serde_urlencoded::to_string(params)?;
+		Ok(Self {
+			name: name.into(),
+			path: path.into(),
+			params: url::form_urlencoded::parse(query.as_bytes()).into_owned().collect(),
+		})
+	}
+
+	pub fn request_key(&self) -> RequestKey {
+		RequestKey::new(&self.path, &self.params).expect("String pairs are always serializable")
+	}
+}
+
+impl<C> Service<Endpoint> for Client<C>
+where
+	C: HttpClientAdapter + Clone + 'static,
+{
+	type Response = Vec<u8>;
+	type Error = Error<C::Error>;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+	/// Always ready, the concurrency limit of [Client::with_rate_limits()] is applied when the request is sent
+	#[inline]
+	fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, endpoint: Endpoint) -> Self::Future {
+		let client = self.clone();
+		Box::pin(async move {
+			let path = format!("/{}", endpoint.path.trim_start_matches('/'));
+			client.perform_request_raw(&endpoint.name, &path, &endpoint.params).await
+		})
+	}
+}
@@ -0,0 +1,12 @@
+use crate::response;
+
+/// Current state of a site for the dashboard refresh, returned by
+/// [Client::site_snapshot()](crate::Client::site_snapshot)
+#[derive(Clone, Debug, PartialEq)]
+pub struct SiteSnapshot {
+	pub overview: response::SiteOverview,
+	pub power_flow: response::SiteCurrentPowerFlow,
+	/// `None` if the site has no storage, see [SiteCurrentPowerFlow::storage](response::SiteCurrentPowerFlow::storage)
+	pub storage: Option<response::SiteStorageData>,
+	pub env_benefits: response::SiteEnvBenefits,
+}
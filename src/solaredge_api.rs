@@ -0,0 +1,349 @@
+use http_adapter::HttpClientAdapter;
+
+use crate::api::request;
+use crate::{response, Client, Error, SerialNumber, SiteId};
+
+/// Endpoints of the SolarEdge API, implemented by [Client] and by the fakes in the downstream tests
+///
+/// Write the application code against `&dyn SolarEdgeApi<Error = E>` or a generic `A: SolarEdgeApi` to be able to substitute
+/// the client without the HTTP mocks. The trait is object-safe, so the arguments are the concrete [SiteId] and
+/// [SerialNumber] instead of the `impl Into<..>` of the [Client] functions. Only the functions mapping to a single API
+/// endpoint are included, the helpers composed of several requests, like [Client::equipment_data_range()], and the
+/// client configuration stay on the [Client].
+///
+/// # Example
+/// ```
+/// # // Dummy implementation for doctests only, do not use as reference, use `http-adapter-reqwest` crate instead
+/// # mod http_adapter_reqwest {
+/// #    #[derive(Default)]
+/// #    pub struct ReqwestAdapter;
+/// #    #[async_trait::async_trait(?Send)]
+/// #    impl http_adapter::HttpClientAdapter for ReqwestAdapter {
+/// #       type Error = String;
+/// #       async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> { Ok(http_adapter::Response::new(vec![])) }
+/// #    }
+/// # }
+/// use solaredge::{Error, SiteId, SolarEdgeApi};
+///
+/// async fn site_name<E>(api: &dyn SolarEdgeApi<Error = E>, site_id: SiteId) -> Result<String, Error<E>> {
+///    Ok(api.site_details(site_id).await?.name)
+/// }
+///
+/// async fn run() -> Result<(), Box<dyn std::error::Error>> {
+///    let client = solaredge::Client::<http_adapter_reqwest::ReqwestAdapter>::new("API_KEY");
+///    let name = site_name(&client, SiteId(1)).await?;
+///    Ok(())
+/// }
+/// ```
+#[async_trait::async_trait(?Send)]
+pub trait SolarEdgeApi {
+	/// Error of the underlying HTTP client, see [Error::HttpRequest]
+	type Error;
+
+	/// See [Client::version_current()]
+	async fn version_current(&self) -> Result<String, Error<Self::Error>>;
+
+	/// See [Client::version_supported()]
+	async fn version_supported(&self) -> Result<Vec<response::VersionSpec>, Error<Self::Error>>;
+
+	/// See [Client::sites_list()]
+	async fn sites_list(&self, params: &request::SitesList<'_>) -> Result<Vec<response::Site>, Error<Self::Error>>;
+
+	/// See [Client::site_details()]
+	async fn site_details(&self, site_id: SiteId) -> Result<response::Site, Error<Self::Error>>;
+
+	/// See [Client::site_data_period()]
+	async fn site_data_period(&self, site_id: SiteId) -> Result<response::DataPeriod, Error<Self::Error>>;
+
+	/// See [Client::site_data_period_bulk()]
+	async fn site_data_period_bulk(&self, site_ids: &[SiteId]) -> Result<Vec<response::DataPeriodBulk>, Error<Self::Error>>;
+
+	/// See [Client::site_energy()]
+	async fn site_energy(&self, site_id: SiteId, params: &request::SiteEnergy)
+		-> Result<response::SiteEnergy, Error<Self::Error>>;
+
+	/// See [Client::site_energy_bulk()]
+	async fn site_energy_bulk(
+		&self,
+		site_ids: &[SiteId],
+		params: &request::SiteEnergy,
+	) -> Result<response::SiteEnergyBulkList, Error<Self::Error>>;
+
+	/// See [Client::site_time_frame_energy()]
+	async fn site_time_frame_energy(
+		&self,
+		site_id: SiteId,
+		params: &request::SiteTotalEnergy,
+	) -> Result<response::SiteTimeframeEnergy, Error<Self::Error>>;
+
+	/// See [Client::site_time_frame_energy_bulk()]
+	async fn site_time_frame_energy_bulk(
+		&self,
+		site_ids: &[SiteId],
+		params: &request::SiteTotalEnergy,
+	) -> Result<Vec<response::SiteTimeframeEnergyBulk>, Error<Self::Error>>;
+
+	/// See [Client::site_power()]
+	async fn site_power(
+		&self,
+		site_id: SiteId,
+		params: &request::DateTimeRange,
+	) -> Result<response::SitePower, Error<Self::Error>>;
+
+	/// See [Client::site_power_bulk()]
+	async fn site_power_bulk(
+		&self,
+		site_ids: &[SiteId],
+		params: &request::DateTimeRange,
+	) -> Result<response::SitePowerValueList, Error<Self::Error>>;
+
+	/// See [Client::site_overview()]
+	async fn site_overview(&self, site_id: SiteId) -> Result<response::SiteOverview, Error<Self::Error>>;
+
+	/// See [Client::site_power_details()]
+	async fn site_power_details(
+		&self,
+		site_id: SiteId,
+		params: &request::SitePowerDetails<'_>,
+	) -> Result<response::SiteMetersDetails, Error<Self::Error>>;
+
+	/// See [Client::site_energy_details()]
+	async fn site_energy_details(
+		&self,
+		site_id: SiteId,
+		params: &request::MetersDateTimeRange<'_>,
+	) -> Result<response::SiteMetersDetails, Error<Self::Error>>;
+
+	/// See [Client::site_current_power_flow()]
+	async fn site_current_power_flow(&self, site_id: SiteId) -> Result<response::SiteCurrentPowerFlow, Error<Self::Error>>;
+
+	/// See [Client::site_storage_data()]
+	async fn site_storage_data(
+		&self,
+		site_id: SiteId,
+		params: &request::SiteStorageData<'_>,
+	) -> Result<response::SiteStorageData, Error<Self::Error>>;
+
+	/// See [Client::site_env_benefits()]
+	async fn site_env_benefits(
+		&self,
+		site_id: SiteId,
+		params: &request::SiteEnvBenefits,
+	) -> Result<response::SiteEnvBenefits, Error<Self::Error>>;
+
+	/// See [Client::site_image()]
+	async fn site_image(
+		&self,
+		site_id: SiteId,
+		name: &str,
+		params: &request::SiteImage<'_>,
+	) -> Result<response::ImageResult, Error<Self::Error>>;
+
+	/// See [Client::site_inventory()]
+	async fn site_inventory(&self, site_id: SiteId) -> Result<response::SiteInventory, Error<Self::Error>>;
+
+	/// See [Client::site_meters()]
+	async fn site_meters(
+		&self,
+		site_id: SiteId,
+		params: &request::MetersDateTimeRange<'_>,
+	) -> Result<response::SiteMeters, Error<Self::Error>>;
+
+	/// See [Client::equipment_list()]
+	async fn equipment_list(&self, site_id: SiteId) -> Result<Vec<response::Equipment>, Error<Self::Error>>;
+
+	/// See [Client::equipment_data()]
+	async fn equipment_data(
+		&self,
+		site_id: SiteId,
+		serial_number: SerialNumber,
+		params: &request::DateTimeRange,
+	) -> Result<Vec<response::EquipmentTelemetry>, Error<Self::Error>>;
+
+	/// See [Client::equipment_changelog()]
+	async fn equipment_changelog(
+		&self,
+		site_id: SiteId,
+		serial_number: SerialNumber,
+	) -> Result<Vec<response::EquipmentChange>, Error<Self::Error>>;
+
+	/// See [Client::accounts_list()]
+	async fn accounts_list(&self, params: &request::AccountsList<'_>) -> Result<Vec<response::Account>, Error<Self::Error>>;
+
+	/// See [Client::equipment_sensors()]
+	async fn equipment_sensors(&self, site_id: SiteId) -> Result<Vec<response::GatewaySensors>, Error<Self::Error>>;
+
+	/// See [Client::site_sensor_data()]
+	async fn site_sensor_data(
+		&self,
+		site_id: SiteId,
+		params: &request::SiteSensorData,
+	) -> Result<Vec<response::GatewaySensorData>, Error<Self::Error>>;
+}
+
+#[async_trait::async_trait(?Send)]
+impl<C: HttpClientAdapter> SolarEdgeApi for Client<C> {
+	type Error = C::Error;
+
+	async fn version_current(&self) -> Result<String, Error<C::Error>> {
+		Client::version_current(self).await
+	}
+
+	async fn version_supported(&self) -> Result<Vec<response::VersionSpec>, Error<C::Error>> {
+		Client::version_supported(self).await
+	}
+
+	async fn sites_list(&self, params: &request::SitesList<'_>) -> Result<Vec<response::Site>, Error<C::Error>> {
+		Client::sites_list(self, params).await
+	}
+
+	async fn site_details(&self, site_id: SiteId) -> Result<response::Site, Error<C::Error>> {
+		Client::site_details(self, site_id).await
+	}
+
+	async fn site_data_period(&self, site_id: SiteId) -> Result<response::DataPeriod, Error<C::Error>> {
+		Client::site_data_period(self, site_id).await
+	}
+
+	async fn site_data_period_bulk(&self, site_ids: &[SiteId]) -> Result<Vec<response::DataPeriodBulk>, Error<C::Error>> {
+		Client::site_data_period_bulk(self, site_ids).await
+	}
+
+	async fn site_energy(&self, site_id: SiteId, params: &request::SiteEnergy) -> Result<response::SiteEnergy, Error<C::Error>> {
+		Client::site_energy(self, site_id, params).await
+	}
+
+	async fn site_energy_bulk(
+		&self,
+		site_ids: &[SiteId],
+		params: &request::SiteEnergy,
+	) -> Result<response::SiteEnergyBulkList, Error<C::Error>> {
+		Client::site_energy_bulk(self, site_ids, params).await
+	}
+
+	async fn site_time_frame_energy(
+		&self,
+		site_id: SiteId,
+		params: &request::SiteTotalEnergy,
+	) -> Result<response::SiteTimeframeEnergy, Error<C::Error>> {
+		Client::site_time_frame_energy(self, site_id, params).await
+	}
+
+	async fn site_time_frame_energy_bulk(
+		&self,
+		site_ids: &[SiteId],
+		params: &request::SiteTotalEnergy,
+	) -> Result<Vec<response::SiteTimeframeEnergyBulk>, Error<C::Error>> {
+		Client::site_time_frame_energy_bulk(self, site_ids, params).await
+	}
+
+	async fn site_power(&self, site_id: SiteId, params: &request::DateTimeRange) -> Result<response::SitePower, Error<C::Error>> {
+		Client::site_power(self, site_id, params).await
+	}
+
+	async fn site_power_bulk(
+		&self,
+		site_ids: &[SiteId],
+		params: &request::DateTimeRange,
+	) -> Result<response::SitePowerValueList, Error<C::Error>> {
+		Client::site_power_bulk(self, site_ids, params).await
+	}
+
+	async fn site_overview(&self, site_id: SiteId) -> Result<response::SiteOverview, Error<C::Error>> {
+		Client::site_overview(self, site_id).await
+	}
+
+	async fn site_power_details(
+		&self,
+		site_id: SiteId,
+		params: &request::SitePowerDetails<'_>,
+	) -> Result<response::SiteMetersDetails, Error<C::Error>> {
+		Client::site_power_details(self, site_id, params).await
+	}
+
+	async fn site_energy_details(
+		&self,
+		site_id: SiteId,
+		params: &request::MetersDateTimeRange<'_>,
+	) -> Result<response::SiteMetersDetails, Error<C::Error>> {
+		Client::site_energy_details(self, site_id, params).await
+	}
+
+	async fn site_current_power_flow(&self, site_id: SiteId) -> Result<response::SiteCurrentPowerFlow, Error<C::Error>> {
+		Client::site_current_power_flow(self, site_id).await
+	}
+
+	async fn site_storage_data(
+		&self,
+		site_id: SiteId,
+		params: &request::SiteStorageData<'_>,
+	) -> Result<response::SiteStorageData, Error<C::Error>> {
+		Client::site_storage_data(self, site_id, params).await
+	}
+
+	async fn site_env_benefits(
+		&self,
+		site_id: SiteId,
+		params: &request::SiteEnvBenefits,
+	) -> Result<response::SiteEnvBenefits, Error<C::Error>> {
+		Client::site_env_benefits(self, site_id, params).await
+	}
+
+	async fn site_image(
+		&self,
+		site_id: SiteId,
+		name: &str,
+		params: &request::SiteImage<'_>,
+	) -> Result<response::ImageResult, Error<C::Error>> {
+		Client::site_image(self, site_id, name, params).await
+	}
+
+	async fn site_inventory(&self, site_id: SiteId) -> Result<response::SiteInventory, Error<C::Error>> {
+		Client::site_inventory(self, site_id).await
+	}
+
+	async fn site_meters(
+		&self,
+		site_id: SiteId,
+		params: &request::MetersDateTimeRange<'_>,
+	) -> Result<response::SiteMeters, Error<C::Error>> {
+		Client::site_meters(self, site_id, params).await
+	}
+
+	async fn equipment_list(&self, site_id: SiteId) -> Result<Vec<response::Equipment>, Error<C::Error>> {
+		Client::equipment_list(self, site_id).await
+	}
+
+	async fn equipment_data(
+		&self,
+		site_id: SiteId,
+		serial_number: SerialNumber,
+		params: &request::DateTimeRange,
+	) -> Result<Vec<response::EquipmentTelemetry>, Error<C::Error>> {
+		Client::equipment_data(self, site_id, serial_number, params).await
+	}
+
+	async fn equipment_changelog(
+		&self,
+		site_id: SiteId,
+		serial_number: SerialNumber,
+	) -> Result<Vec<response::EquipmentChange>, Error<C::Error>> {
+		Client::equipment_changelog(self, site_id, serial_number).await
+	}
+
+	async fn accounts_list(&self, params: &request::AccountsList<'_>) -> Result<Vec<response::Account>, Error<C::Error>> {
+		Client::accounts_list(self, params).await
+	}
+
+	async fn equipment_sensors(&self, site_id: SiteId) -> Result<Vec<response::GatewaySensors>, Error<C::Error>> {
+		Client::equipment_sensors(self, site_id).await
+	}
+
+	async fn site_sensor_data(
+		&self,
+		site_id: SiteId,
+		params: &request::SiteSensorData,
+	) -> Result<Vec<response::GatewaySensorData>, Error<C::Error>> {
+		Client::site_sensor_data(self, site_id, params).await
+	}
+}
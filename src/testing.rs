@@ -0,0 +1,333 @@
+//! Fake API for the downstream tests, enabled with the `testing` feature
+//!
+//! Write the code under test against the [SolarEdgeApi] trait and pass the [MockClient] with the canned responses instead
+//! of the [Client](crate::Client).
+//!
+//! # Example
+//! ```
+//! use http_adapter::http::StatusCode;
+//! use solaredge::testing::MockClient;
+//! use solaredge::{Error, SiteId, SolarEdgeApi};
+//!
+//! async fn run() {
+//!    let mock = MockClient::<String>::new()
+//!       .with_response("version_current", r#""1.0.0""#)
+//!       .with_error_once("site_overview", || Error::Api(StatusCode::INTERNAL_SERVER_ERROR, vec![]));
+//!    assert_eq!("1.0.0", mock.version_current().await.unwrap());
+//!    assert!(mock.site_overview(SiteId(1)).await.is_err());
+//!    assert_eq!(vec!["version_current", "site_overview"], mock.calls());
+//! }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::de::DeserializeOwned;
+
+use crate::api::request;
+use crate::json::{Backend, JsonBackend};
+use crate::{response, Error, SerialNumber, SiteId, SolarEdgeApi};
+
+/// [SolarEdgeApi] implementation returning the canned responses configured per endpoint
+///
+/// The endpoint is identified by the name of the [SolarEdgeApi] function, e.g. `site_details`, the request parameters are
+/// ignored. The response is the JSON of the type returned by the function, i.e. the API response without the top-level
+/// wrapper object, e.g. the contents of the `details` object for `site_details`. It's parsed on every call, so the
+/// malformed response results in [Error::Json] like with the real API. The responses set with
+/// [MockClient::with_response_once()] and [MockClient::with_error_once()] are returned first in the order they were added,
+/// then the one set with [MockClient::with_response()] or [MockClient::with_error()] is returned for all following calls.
+///
+/// # Panics
+/// Calling the endpoint without a configured response panics to fail the test.
+pub struct MockClient<E> {
+	replies: Mutex<HashMap<&'static str, Replies<E>>>,
+	calls: Mutex<Vec<&'static str>>,
+}
+
+type ErrorFactory<E> = Box<dyn Fn() -> Error<E> + Send + Sync>;
+
+enum Reply<E> {
+	Json(String),
+	Error(ErrorFactory<E>),
+}
+
+struct Replies<E> {
+	once: VecDeque<Reply<E>>,
+	always: Option<Reply<E>>,
+}
+
+impl<E> Reply<E> {
+	fn get<T: DeserializeOwned>(&self) -> Result<T, Error<E>> {
+		match self {
+			Reply::Json(json) => Backend::from_slice(json.as_bytes()).map_err(Error::Json),
+			Reply::Error(error) => Err(error()),
+		}
+	}
+}
+
+impl<E> Default for Replies<E> {
+	fn default() -> Self {
+		Self {
+			once: VecDeque::new(),
+			always: None,
+		}
+	}
+}
+
+impl<E> Default for MockClient<E> {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<E> fmt::Debug for MockClient<E> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut endpoints = self.lock_replies().keys().copied().collect::<Vec<_>>();
+		endpoints.sort_unstable();
+		f.debug_struct("MockClient")
+			.field("endpoints", &endpoints)
+			.field("calls", &self.calls())
+			.finish()
+	}
+}
+
+impl<E> MockClient<E> {
+	pub fn new() -> Self {
+		Self {
+			replies: Mutex::new(HashMap::new()),
+			calls: Mutex::new(vec![]),
+		}
+	}
+
+	/// Returns the `json` response for all calls to the `endpoint`
+	pub fn with_response(self, endpoint: &'static str, json: impl Into<String>) -> Self {
+		self.lock_replies().entry(endpoint).or_default().always = Some(Reply::Json(json.into()));
+		self
+	}
+
+	/// Returns the `json` response for the next call to the `endpoint`
+	pub fn with_response_once(self, endpoint: &'static str, json: impl Into<String>) -> Self {
+		self
+			.lock_replies()
+			.entry(endpoint)
+			.or_default()
+			.once
+			.push_back(Reply::Json(json.into()));
+		self
+	}
+
+	/// Fails all calls to the `endpoint` with the error returned by `error`
+	pub fn with_error(self, endpoint: &'static str, error: impl Fn() -> Error<E> + Send + Sync + 'static) -> Self {
+		self.lock_replies().entry(endpoint).or_default().always = Some(Reply::Error(Box::new(error)));
+		self
+	}
+
+	/// Fails the next call to the `endpoint` with the error returned by `error`
+	pub fn with_error_once(self, endpoint: &'static str, error: impl Fn() -> Error<E> + Send + Sync + 'static) -> Self {
+		self
+			.lock_replies()
+			.entry(endpoint)
+			.or_default()
+			.once
+			.push_back(Reply::Error(Box::new(error)));
+		self
+	}
+
+	/// Endpoints called so far in the order of the calls
+	pub fn calls(&self) -> Vec<&'static str> {
+		self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+	}
+
+	fn reply<T: DeserializeOwned>(&self, endpoint: &'static str) -> Result<T, Error<E>> {
+		self.calls.lock().unwrap_or_else(|e| e.into_inner()).push(endpoint);
+		let mut replies = self.lock_replies();
+		let replies = replies.get_mut(endpoint);
+		match replies {
+			Some(replies) => match replies.once.pop_front() {
+				Some(reply) => reply.get(),
+				None => replies
+					.always
+					.as_ref()
+					.map_or_else(|| Self::unconfigured(endpoint), Reply::get),
+			},
+			None => Self::unconfigured(endpoint),
+		}
+	}
+
+	fn unconfigured(endpoint: &str) -> ! {
+		panic!("No response is configured for the endpoint: {endpoint}")
+	}
+
+	fn lock_replies(&self) -> MutexGuard<'_, HashMap<&'static str, Replies<E>>> {
+		self.replies.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl<E> SolarEdgeApi for MockClient<E> {
+	type Error = E;
+
+	async fn version_current(&self) -> Result<String, Error<E>> {
+		self.reply("version_current")
+	}
+
+	async fn version_supported(&self) -> Result<Vec<response::VersionSpec>, Error<E>> {
+		self.reply("version_supported")
+	}
+
+	async fn sites_list(&self, _params: &request::SitesList<'_>) -> Result<Vec<response::Site>, Error<E>> {
+		self.reply("sites_list")
+	}
+
+	async fn site_details(&self, _site_id: SiteId) -> Result<response::Site, Error<E>> {
+		self.reply("site_details")
+	}
+
+	async fn site_data_period(&self, _site_id: SiteId) -> Result<response::DataPeriod, Error<E>> {
+		self.reply("site_data_period")
+	}
+
+	async fn site_data_period_bulk(&self, _site_ids: &[SiteId]) -> Result<Vec<response::DataPeriodBulk>, Error<E>> {
+		self.reply("site_data_period_bulk")
+	}
+
+	async fn site_energy(&self, _site_id: SiteId, _params: &request::SiteEnergy) -> Result<response::SiteEnergy, Error<E>> {
+		self.reply("site_energy")
+	}
+
+	async fn site_energy_bulk(
+		&self,
+		_site_ids: &[SiteId],
+		_params: &request::SiteEnergy,
+	) -> Result<response::SiteEnergyBulkList, Error<E>> {
+		self.reply("site_energy_bulk")
+	}
+
+	async fn site_time_frame_energy(
+		&self,
+		_site_id: SiteId,
+		_params: &request::SiteTotalEnergy,
+	) -> Result<response::SiteTimeframeEnergy, Error<E>> {
+		self.reply("site_time_frame_energy")
+	}
+
+	async fn site_time_frame_energy_bulk(
+		&self,
+		_site_ids: &[SiteId],
+		_params: &request::SiteTotalEnergy,
+	) -> Result<Vec<response::SiteTimeframeEnergyBulk>, Error<E>> {
+		self.reply("site_time_frame_energy_bulk")
+	}
+
+	async fn site_power(&self, _site_id: SiteId, _params: &request::DateTimeRange) -> Result<response::SitePower, Error<E>> {
+		self.reply("site_power")
+	}
+
+	async fn site_power_bulk(
+		&self,
+		_site_ids: &[SiteId],
+		_params: &request::DateTimeRange,
+	) -> Result<response::SitePowerValueList, Error<E>> {
+		self.reply("site_power_bulk")
+	}
+
+	async fn site_overview(&self, _site_id: SiteId) -> Result<response::SiteOverview, Error<E>> {
+		self.reply("site_overview")
+	}
+
+	async fn site_power_details(
+		&self,
+		_site_id: SiteId,
+		_params: &request::SitePowerDetails<'_>,
+	) -> Result<response::SiteMetersDetails, Error<E>> {
+		self.reply("site_power_details")
+	}
+
+	async fn site_energy_details(
+		&self,
+		_site_id: SiteId,
+		_params: &request::MetersDateTimeRange<'_>,
+	) -> Result<response::SiteMetersDetails, Error<E>> {
+		self.reply("site_energy_details")
+	}
+
+	async fn site_current_power_flow(&self, _site_id: SiteId) -> Result<response::SiteCurrentPowerFlow, Error<E>> {
+		self.reply("site_current_power_flow")
+	}
+
+	async fn site_storage_data(
+		&self,
+		_site_id: SiteId,
+		_params: &request::SiteStorageData<'_>,
+	) -> Result<response::SiteStorageData, Error<E>> {
+		self.reply("site_storage_data")
+	}
+
+	async fn site_env_benefits(
+		&self,
+		_site_id: SiteId,
+		_params: &request::SiteEnvBenefits,
+	) -> Result<response::SiteEnvBenefits, Error<E>> {
+		self.reply("site_env_benefits")
+	}
+
+	async fn site_image(
+		&self,
+		_site_id: SiteId,
+		_name: &str,
+		_params: &request::SiteImage<'_>,
+	) -> Result<response::ImageResult, Error<E>> {
+		self.reply("site_image")
+	}
+
+	async fn site_inventory(&self, _site_id: SiteId) -> Result<response::SiteInventory, Error<E>> {
+		self.reply("site_inventory")
+	}
+
+	async fn site_meters(
+		&self,
+		_site_id: SiteId,
+		_params: &request::MetersDateTimeRange<'_>,
+	) -> Result<response::SiteMeters, Error<E>> {
+		self.reply("site_meters")
+	}
+
+	async fn equipment_list(&self, _site_id: SiteId) -> Result<Vec<response::Equipment>, Error<E>> {
+		self.reply("equipment_list")
+	}
+
+	async fn equipment_data(
+		&self,
+		_site_id: SiteId,
+		_serial_number: SerialNumber,
+		_params: &request::DateTimeRange,
+	) -> Result<Vec<response::EquipmentTelemetry>, Error<E>> {
+		self.reply("equipment_data")
+	}
+
+	async fn equipment_changelog(
+		&self,
+		_site_id: SiteId,
+		_serial_number: SerialNumber,
+	) -> Result<Vec<response::EquipmentChange>, Error<E>> {
+		self.reply("equipment_changelog")
+	}
+
+	async fn accounts_list(&self, _params: &request::AccountsList<'_>) -> Result<Vec<response::Account>, Error<E>> {
+		self.reply("accounts_list")
+	}
+
+	async fn equipment_sensors(&self, _site_id: SiteId) -> Result<Vec<response::GatewaySensors>, Error<E>> {
+		self.reply("equipment_sensors")
+	}
+
+	async fn site_sensor_data(
+		&self,
+		_site_id: SiteId,
+		_params: &request::SiteSensorData,
+	) -> Result<Vec<response::GatewaySensorData>, Error<E>> {
+		self.reply("site_sensor_data")
+	}
+}
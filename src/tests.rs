@@ -1,11 +1,26 @@
+#[cfg(feature = "chrono")]
 use chrono::{NaiveDate, NaiveTime};
 use http_adapter_reqwest::ReqwestAdapter;
 
+#[cfg(feature = "analytics")]
+use crate::analytics;
+use crate::diagnostics::{detect_drift, DriftFinding};
+#[cfg(feature = "chrono")]
+use crate::series::SortSeries;
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+use crate::PowerFlowStatus;
 use crate::{
-	Client, DateTimeRange, MetersDateTimeRange, SiteEnergy, SiteEnvBenefits, SitePowerDetails, SiteStatus, SiteStorageData,
-	SiteTotalEnergy, SitesList, SortOrder, SystemUnits, TimeUnit,
+	response, AccountsList, ApiErrorBody, AuditEntry, Client, EnvSecret, Environment, Error, ErrorClass, FileSecret,
+	RateLimitExceeded, RateLimits, RetryPolicy, SecretError, SecretProvider, SiteEnvBenefits, SiteId, SiteSortBy, SiteStatus,
+	SitesList, SortOrder,
+};
+#[cfg(feature = "chrono")]
+use crate::{
+	series, DateTimeRange, MeterType, MetersDateTimeRange, PowerUnit, SiteEnergy, SitePowerDetails, SiteProfile, SiteSensorData,
+	SiteStorageData, SiteTotalEnergy, SystemUnits, TimeUnit,
 };
 
+#[cfg(feature = "chrono")]
 #[tokio::test]
 async fn it_works() {
 	env_logger::init();
@@ -154,3 +169,3606 @@ async fn it_works() {
 		}
 	}
 }
+
+#[tokio::test]
+async fn replay_environment() {
+	let dir = std::env::temp_dir().join(format!("solaredge-replay-{}", std::process::id()));
+	std::fs::create_dir_all(dir.join("version")).unwrap();
+	std::fs::write(dir.join("version/current.json"), r#"{"version":{"release":"1.0.0"}}"#).unwrap();
+	let env = format!("replay:{}", dir.display()).parse::<Environment>().unwrap();
+	assert_eq!(Environment::Replay(dir.clone()), env);
+	let c = Client::new_with_environment(ReqwestAdapter::default(), "", env);
+	assert_eq!("1.0.0", c.version_current().await.unwrap());
+	assert!(matches!(c.version_supported().await, Err(Error::Replay(..))));
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+fn fixtures_client() -> Client<ReqwestAdapter> {
+	let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+	Client::new_with_environment(ReqwestAdapter::default(), "", Environment::Replay(dir)).with_count_check(true)
+}
+
+/// Serves the fixtures like [Environment::Replay] but also records the requested URLs
+#[derive(Clone, Default)]
+struct RecordingAdapter {
+	urls: std::sync::Arc<std::sync::Mutex<Vec<url::Url>>>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl http_adapter::HttpClientAdapter for RecordingAdapter {
+	type Error = std::io::Error;
+
+	async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+		let url = url::Url::parse(&request.uri().to_string()).unwrap();
+		let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+			.join("tests/fixtures")
+			.join(url.path().trim_start_matches('/'));
+		self.urls.lock().unwrap().push(url);
+		Ok(http_adapter::Response::new(std::fs::read(fixture)?))
+	}
+}
+
+/// Fails the first `failures` requests with the `status`, then serves the fixtures
+struct FlakyAdapter {
+	failures: std::sync::atomic::AtomicUsize,
+	status: http_adapter::http::StatusCode,
+}
+
+#[async_trait::async_trait(?Send)]
+impl http_adapter::HttpClientAdapter for FlakyAdapter {
+	type Error = std::io::Error;
+
+	async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+		use std::sync::atomic::Ordering;
+
+		if self
+			.failures
+			.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |failures| failures.checked_sub(1))
+			.is_ok()
+		{
+			let res = http_adapter::Response::builder()
+				.status(self.status)
+				.header(http_adapter::http::header::RETRY_AFTER, "7")
+				.body(self.status.to_string().into_bytes())
+				.unwrap();
+			return Ok(res);
+		}
+		RecordingAdapter::default().execute(request).await
+	}
+}
+
+/// Serves the same body for every request
+#[derive(Clone)]
+struct BodyAdapter(String);
+
+#[async_trait::async_trait(?Send)]
+impl http_adapter::HttpClientAdapter for BodyAdapter {
+	type Error = std::io::Error;
+
+	async fn execute(&self, _request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+		Ok(http_adapter::Response::new(self.0.as_bytes().to_vec()))
+	}
+}
+
+/// Runs every endpoint against the recorded fixtures, with `strict` feature enabled it also detects new fields
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn fixture_sweep() {
+	let c = fixtures_client();
+	let site_id = 1;
+	let site_ids = [1, 2];
+	let date_range = SiteEnergy {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 12).unwrap(),
+		time_unit: Some(TimeUnit::Day),
+	};
+	let total_range = SiteTotalEnergy {
+		start_date: date_range.start_date,
+		end_date: date_range.end_date,
+	};
+	let time_range = DateTimeRange {
+		start_time: date_range.start_date.and_time(NaiveTime::MIN),
+		end_time: date_range.end_date.and_time(NaiveTime::MIN),
+	};
+	let meters_range = MetersDateTimeRange {
+		start_time: time_range.start_time,
+		end_time: time_range.end_time,
+		time_unit: None,
+		meters: None,
+	};
+	c.version_current().await.unwrap();
+	c.version_supported().await.unwrap();
+	c.accounts_list(&AccountsList::default()).await.unwrap();
+	c.sites_list(&SitesList::default()).await.unwrap();
+	c.sites_list_all(&SitesList::default()).try_collect().await.unwrap();
+	c.accounts_list_all(&AccountsList::default()).try_collect().await.unwrap();
+	let site = c.site_details(site_id).await.unwrap();
+	c.follow_details(&site.uris).await.unwrap();
+	c.site_data_period(site_id).await.unwrap();
+	c.follow_data_period(&site.uris).await.unwrap();
+	c.site_data_period_bulk(&site_ids).await.unwrap();
+	c.site_energy(site_id, &date_range).await.unwrap();
+	c.site_energy_bulk(&site_ids, &date_range).await.unwrap();
+	c.site_time_frame_energy(site_id, &total_range).await.unwrap();
+	c.site_time_frame_energy_bulk(&site_ids, &total_range).await.unwrap();
+	c.site_power(site_id, &time_range).await.unwrap();
+	c.site_power_bulk(&site_ids, &time_range).await.unwrap();
+	c.site_overview(site_id).await.unwrap();
+	c.follow_overview(&site.uris).await.unwrap();
+	c.site_power_details(
+		site_id,
+		&SitePowerDetails {
+			start_time: time_range.start_time,
+			end_time: time_range.end_time,
+			meters: None,
+		},
+	)
+	.await
+	.unwrap();
+	c.site_energy_details(site_id, &meters_range).await.unwrap();
+	c.site_current_power_flow(site_id).await.unwrap();
+	c.site_storage_data(
+		site_id,
+		&SiteStorageData {
+			start_time: time_range.start_time,
+			end_time: time_range.end_time,
+			serials: None,
+		},
+	)
+	.await
+	.unwrap();
+	c.site_env_benefits(site_id, &SiteEnvBenefits { system_units: None })
+		.await
+		.unwrap();
+	c.site_inventory(site_id).await.unwrap();
+	c.site_meters(site_id, &meters_range).await.unwrap();
+	c.equipment_list(site_id).await.unwrap();
+	c.equipment_data(site_id, "12345678", &time_range).await.unwrap();
+	c.equipment_changelog(site_id, "12345678").await.unwrap();
+	c.equipment_sensors(site_id).await.unwrap();
+	c.site_sensor_data(
+		site_id,
+		&SiteSensorData {
+			start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+			end_date: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+		},
+	)
+	.await
+	.unwrap();
+}
+
+#[test]
+fn drift_detection() {
+	let body = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site/1/details.json")).unwrap();
+	assert!(detect_drift::<response::SiteDetailsTop>(&body).unwrap().is_clean());
+
+	let body = br#"{"details": {"id": 1, "name": null, "extra": true}}"#;
+	let report = detect_drift::<response::SiteDetailsTop>(body).unwrap();
+	assert!(!report.parsed);
+	assert_eq!(
+		vec![
+			DriftFinding::UnknownField {
+				path: "$.details.extra".to_string()
+			},
+			DriftFinding::UnexpectedNull {
+				path: "$.details.name".to_string()
+			},
+		],
+		report.findings
+	);
+
+	let body = br#"{"version": {"release": 1}}"#;
+	let report = detect_drift::<response::VersionCurrentTop>(body).unwrap();
+	assert!(matches!(&report.findings[..], [DriftFinding::Mismatch { path, .. }] if path == "$.version.release"));
+}
+
+#[test]
+fn count_mismatch() {
+	use crate::diagnostics::{CountMismatch, Counted};
+
+	let consistent = response::List {
+		count: 3,
+		list: vec![1, 2, 3],
+	};
+	assert_eq!(None, consistent.count_mismatch());
+	assert_eq!(None, consistent.page_count_mismatch(0, 100));
+
+	let truncated = response::List {
+		count: 3,
+		list: vec![1, 2],
+	};
+	assert_eq!(Some(CountMismatch { reported: 3, actual: 2 }), truncated.count_mismatch());
+	assert_eq!(
+		Some(CountMismatch { reported: 3, actual: 2 }),
+		truncated.page_count_mismatch(0, 100)
+	);
+
+	// the count of the paged lists is the total across all pages
+	let page = response::List {
+		count: 250,
+		list: (0..100).collect(),
+	};
+	assert!(page.count_mismatch().is_some());
+	assert_eq!(None, page.page_count_mismatch(0, 100));
+	assert_eq!(None, page.page_count_mismatch(100, 100));
+	let last_page = response::List {
+		count: 250,
+		list: (0..50).collect(),
+	};
+	assert_eq!(None, last_page.page_count_mismatch(200, 100));
+	assert_eq!(
+		Some(CountMismatch {
+			reported: 100,
+			actual: 50
+		}),
+		last_page.page_count_mismatch(100, 100)
+	);
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn telemetry_dedup() {
+	let c = fixtures_client();
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let mut telemetries = c.equipment_data(1, "12345678", &range).await.unwrap();
+	let mut chunk = c.equipment_data(1, "12345678", &range).await.unwrap();
+	chunk[1].total_active_power = 0.;
+	telemetries.extend(chunk);
+	assert_eq!(2, series::dedup_by_timestamp(&mut telemetries));
+	assert_eq!(2, telemetries.len());
+	assert_eq!(3000., telemetries[0].total_active_power);
+	assert_eq!(0., telemetries[1].total_active_power);
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn series_sorting() {
+	let c = fixtures_client();
+	let mut details = c
+		.site_power_details(
+			1,
+			&SitePowerDetails {
+				start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+				end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+				meters: None,
+			},
+		)
+		.await
+		.unwrap();
+	assert!(details.is_series_monotonic());
+	details.meters[1].values.reverse();
+	assert!(!details.is_series_monotonic());
+	assert_eq!(Some(1), series::find_non_monotonic(&details.meters[1].values));
+	details.sort_series();
+	assert!(details.is_series_monotonic());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn power_unit_normalization() {
+	let c = fixtures_client();
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let mut power = c.site_power(1, &range).await.unwrap();
+	assert!(power.normalize_unit(PowerUnit::Kilowatt));
+	assert_eq!("kW", power.unit);
+	assert_eq!(Some(3.1), power.values[0].value);
+	assert_eq!(None, power.values[2].value);
+	let mut bulk = c.site_power_bulk(&[1, 2], &range).await.unwrap();
+	bulk.unit = "kW".to_string();
+	assert!(bulk.normalize_unit(PowerUnit::Watt));
+	assert_eq!(
+		Some(3_100_000.),
+		bulk.site_energy_list[1].power_data_value_series.values[0].value
+	);
+	bulk.unit = "hp".to_string();
+	assert!(!bulk.normalize_unit(PowerUnit::Watt));
+	bulk.unit = "mW".to_string();
+	assert!(!bulk.normalize_unit(PowerUnit::Watt));
+	bulk.unit = "GWh".to_string();
+	assert!(bulk.normalize_unit(PowerUnit::Megawatt));
+	assert_eq!("MWh", bulk.unit);
+	assert_eq!(
+		Some(3_100_000_000.),
+		bulk.site_energy_list[1].power_data_value_series.values[0].value
+	);
+}
+
+#[test]
+fn power_unit_parsing() {
+	use crate::PowerUnit;
+
+	for (s, unit) in [
+		("W", PowerUnit::Watt),
+		("kW", PowerUnit::Kilowatt),
+		("MW", PowerUnit::Megawatt),
+		("GW", PowerUnit::Gigawatt),
+		("Wh", PowerUnit::Watt),
+		("kWh", PowerUnit::Kilowatt),
+		("MWh", PowerUnit::Megawatt),
+		("GWh", PowerUnit::Gigawatt),
+	] {
+		assert_eq!(Some(unit), PowerUnit::from_api(s), "{s}");
+	}
+	for s in ["mW", "mw", "KW", "kw", "w", "MWH", ""] {
+		assert_eq!(None, PowerUnit::from_api(s), "{s}");
+	}
+	assert_eq!(1_000., PowerUnit::Gigawatt.factor_to(PowerUnit::Megawatt));
+	assert_eq!("GW", PowerUnit::Gigawatt.to_string());
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn meter_enrichment() {
+	let c = fixtures_client();
+	let range = MetersDateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+		time_unit: None,
+		meters: None,
+	};
+	let inventory = c.site_inventory(1).await.unwrap();
+	let meters = c.site_meters(1, &range).await.unwrap();
+	let enriched = analytics::meters::enrich_site_meters(&inventory, &meters);
+	assert_eq!(Some("MTR123"), enriched[0].serial_number);
+	assert_eq!(Some("WattNode"), enriched[0].manufacturer());
+	assert_eq!(Some("physical"), enriched[0].form());
+	let details = c.site_energy_details(1, &range).await.unwrap();
+	let enriched = analytics::meters::enrich_meter_details(&inventory, &details);
+	assert_eq!(Some("Production Meter"), enriched[0].name());
+	assert_eq!("Purchased", enriched[1].meter_type);
+	assert!(enriched[1].metadata.is_none());
+}
+
+#[cfg(feature = "analytics")]
+#[tokio::test]
+async fn account_tree() {
+	let c = fixtures_client();
+	let accounts = c.accounts_list(&AccountsList::default()).await.unwrap();
+	let tree = analytics::accounts::AccountTree::new(&accounts);
+	assert_eq!(vec![10], tree.roots().iter().map(|a| a.id).collect::<Vec<_>>());
+	assert_eq!(vec![11, 12], tree.children(10).iter().map(|a| a.id).collect::<Vec<_>>());
+	assert_eq!(
+		vec![11, 13, 12],
+		tree.descendants(10).iter().map(|a| a.id).collect::<Vec<_>>()
+	);
+	assert_eq!(Some(11), tree.parent(13).map(|a| a.id));
+	assert!(tree.descendants(13).is_empty());
+
+	let sites = c.sites_list(&SitesList::default()).await.unwrap();
+	let groups = analytics::accounts::group_sites_by_account(&sites, &accounts);
+	assert_eq!(1, groups.len());
+	assert_eq!(Some("Installer"), groups[0].account.map(|a| a.name.as_str()));
+	assert_eq!(14., groups[0].total_peak_power());
+	assert_eq!(2, groups[0].active_site_count());
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn fleet_yield() {
+	let c = fixtures_client();
+	let sites = c.sites_list(&SitesList::default()).await.unwrap();
+	let range = SiteTotalEnergy {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 12).unwrap(),
+	};
+	let bulk = c.site_time_frame_energy_bulk(&[1, 2], &range).await.unwrap();
+	let fleet = analytics::fleet::FleetYield::from_time_frame_energy_bulk(&sites, &bulk);
+	assert_eq!(91000., fleet.total_energy);
+	assert_eq!(14., fleet.total_peak_power);
+	assert_eq!(6500., fleet.specific_yield);
+	let underperformers = fleet.underperformers(0.1);
+	assert_eq!(vec![1], underperformers.iter().map(|s| s.site_id).collect::<Vec<_>>());
+}
+
+#[cfg(feature = "analytics")]
+#[tokio::test]
+async fn changelog_timeline() {
+	let c = fixtures_client();
+	let equipment = c.equipment_list(1).await.unwrap();
+	let mut changelogs = vec![];
+	for device in &equipment {
+		changelogs.push((device, c.equipment_changelog(1, &device.serial_number).await.unwrap()));
+	}
+	changelogs[0].1.reverse();
+	let timeline = analytics::equipment::merge_changelogs(changelogs.iter().map(|(device, changes)| (*device, changes)));
+	assert_eq!(3, timeline.len());
+	assert_eq!("Inverter 1", timeline[0].device.name);
+	assert_eq!("SE5000-16A-IL", timeline[0].change.part_number);
+	assert!(timeline[0].change.date < timeline[1].change.date);
+	assert_eq!("Inverter 2", timeline[2].device.name);
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn curtailment_detection() {
+	let c = fixtures_client();
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let mut telemetries = c.equipment_data(1, "12345678", &range).await.unwrap();
+	assert!(analytics::telemetry::detect_curtailment(&telemetries).is_empty());
+	telemetries[0].power_limit = 50.;
+	let periods = analytics::telemetry::detect_curtailment(&telemetries);
+	assert_eq!(1, periods.len());
+	assert_eq!(telemetries[0].date, periods[0].start);
+	assert_eq!(telemetries[1].date, periods[0].end);
+	assert_eq!(1, periods[0].samples);
+	// 3000 W curtailed for 5 minutes
+	assert_eq!(250., periods[0].estimated_curtailed_energy);
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn telemetry_anomalies() {
+	use analytics::telemetry::{scan_anomalies, AnomalyKind, AnomalyThresholds};
+
+	let c = fixtures_client();
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let mut telemetries = c.equipment_data(1, "12345678", &range).await.unwrap();
+	let thresholds = AnomalyThresholds::default();
+	assert!(scan_anomalies(&telemetries, &thresholds).is_empty());
+	telemetries[0].ground_fault_resistance = Some(100.);
+	telemetries[1].temperature = 80.;
+	let anomalies = scan_anomalies(&telemetries, &thresholds);
+	assert_eq!(
+		vec![
+			AnomalyKind::LowGroundFaultResistance { resistance: 100. },
+			AnomalyKind::HighTemperature { temperature: 80. },
+			AnomalyKind::TemperatureJump {
+				previous: 45.5,
+				temperature: 80.
+			},
+		],
+		anomalies.iter().map(|a| a.kind).collect::<Vec<_>>()
+	);
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn backup_events() {
+	let c = fixtures_client();
+	let start = NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN);
+	let mut samples = vec![];
+	for i in 0..4 {
+		let mut flow = c.site_current_power_flow(1).await.unwrap();
+		if i == 1 || i == 2 {
+			flow.grid.as_mut().unwrap().status = PowerFlowStatus::Disabled;
+			let storage = flow.storage.as_mut().unwrap();
+			storage.status = PowerFlowStatus::Discharging;
+			storage.current_power = 1.2;
+			storage.charge_level = Some(80. - i as f64);
+		}
+		samples.push((start + chrono::Duration::minutes(15 * i), flow));
+	}
+	assert!(!analytics::power_flow::is_grid_down(&samples[0].1));
+	let events = analytics::power_flow::detect_backup_events(&samples);
+	assert_eq!(1, events.len());
+	assert_eq!(chrono::Duration::minutes(30), events[0].duration());
+	assert_eq!(2, events[0].samples);
+	assert_eq!(600., events[0].storage_energy);
+	assert_eq!(600., events[0].load_energy);
+	assert_eq!(Some(78.), events[0].min_charge_level);
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[test]
+fn consumption_baseline() {
+	let start = NaiveDate::from_ymd_opt(2021, 8, 2).unwrap().and_time(NaiveTime::MIN);
+	let sample = |i: i64| response::SiteDateValue {
+		date: start + chrono::Duration::hours(i),
+		value: Some(
+			if i % 24 < 8 {
+				300.
+			} else {
+				1000.
+			} + (i % 2) as f64 * 100.,
+		),
+	};
+	let history = (0..2 * analytics::consumption::HOURS_IN_WEEK as i64)
+		.map(sample)
+		.collect::<Vec<_>>();
+	let baseline = analytics::consumption::ConsumptionBaseline::new(&history);
+	assert_eq!(0, analytics::consumption::hour_of_week(&start));
+	let slot = baseline.slot(&start).unwrap();
+	assert_eq!(2, slot.samples);
+	assert_eq!(300., slot.mean);
+	assert_eq!(0., slot.std_dev);
+
+	let mut series = (0..48).map(sample).collect::<Vec<_>>();
+	series[3].value = Some(2500.);
+	let anomalies = baseline.detect_anomalies(&series, 3., 2);
+	assert_eq!(1, anomalies.len());
+	assert_eq!(start + chrono::Duration::hours(3), anomalies[0].item.date);
+	assert_eq!(400., anomalies[0].expected);
+	assert_eq!(2100., anomalies[0].deviation);
+	assert!(baseline.detect_anomalies(&series, 3., 3).is_empty());
+
+	for item in &mut series[10..16] {
+		item.value = Some(1234.);
+	}
+	let stuck = analytics::consumption::find_stuck_periods(&series, 4, false);
+	assert_eq!(1, stuck.len());
+	assert_eq!(start + chrono::Duration::hours(10), stuck[0].start);
+	assert_eq!(start + chrono::Duration::hours(15), stuck[0].end);
+	assert_eq!(6, stuck[0].samples);
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn location_benchmark() {
+	let c = fixtures_client();
+	let sites = c.sites_list(&SitesList::default()).await.unwrap();
+	let groups = analytics::benchmark::group_sites_by_city(&sites);
+	assert_eq!(1, groups.len());
+	assert_eq!("de/berlin", groups[0].key);
+	assert_eq!(2, groups[0].sites.len());
+	let range = SiteEnergy {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 12).unwrap(),
+		time_unit: Some(TimeUnit::Day),
+	};
+	let bulk = c.site_energy_bulk(&[1, 2], &range).await.unwrap();
+	assert!(analytics::benchmark::benchmark_daily(&groups, &bulk, 3, 0.1).is_empty());
+	let deviations = analytics::benchmark::benchmark_daily(&groups, &bulk, 2, 0.1);
+	assert_eq!(4, deviations.len());
+	assert_eq!(1, deviations[0].site_id);
+	assert_eq!(NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(), deviations[0].date);
+	assert!(deviations[0].deviation < -0.1);
+	assert!(deviations[1].deviation > 0.1);
+	assert!(analytics::benchmark::benchmark_daily(&groups, &bulk, 2, 0.5).is_empty());
+}
+
+#[cfg(feature = "analytics")]
+#[tokio::test]
+async fn site_coordinates() {
+	let c = fixtures_client();
+	let sites = c.sites_list(&SitesList::default()).await.unwrap();
+	assert_eq!(Some((52.52, 13.405)), sites[0].location.coordinates());
+	let distance = sites[0].location.distance_km(&sites[1].location).unwrap();
+	assert!((distance - 27.2).abs() < 0.1, "{distance}");
+	let groups = analytics::benchmark::group_sites_by_distance(&sites, 10.);
+	assert_eq!(
+		vec!["near/1", "near/2"],
+		groups.iter().map(|g| g.key.as_str()).collect::<Vec<_>>()
+	);
+	let groups = analytics::benchmark::group_sites_by_distance(&sites, 50.);
+	assert_eq!(1, groups.len());
+	assert_eq!(2, groups[0].sites.len());
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn geojson_export() {
+	let c = fixtures_client();
+	let mut sites = c.sites_list(&SitesList::default()).await.unwrap();
+	let range = SiteTotalEnergy {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 12).unwrap(),
+	};
+	let bulk = c.site_time_frame_energy_bulk(&[1, 2], &range).await.unwrap();
+	let fleet = analytics::fleet::FleetYield::from_time_frame_energy_bulk(&sites, &bulk);
+	sites[1].location.lat = None;
+	let geojson = analytics::geojson::sites_to_geojson(&sites, Some(&fleet));
+	assert_eq!("FeatureCollection", geojson["type"]);
+	let features = geojson["features"].as_array().unwrap();
+	assert_eq!(1, features.len());
+	assert_eq!(serde_json::json!([13.405, 52.52]), features[0]["geometry"]["coordinates"]);
+	assert_eq!(1, features[0]["properties"]["id"]);
+	assert_eq!(9.8, features[0]["properties"]["peakPower"]);
+	assert!(features[0]["properties"]["specificYield"].is_number());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn site_profiles() {
+	let c = fixtures_client();
+	let sites = c.sites_list(&SitesList::default()).await.unwrap();
+	let profile = SiteProfile {
+		time_unit: Some(TimeUnit::Hour),
+		meters: Some(vec![MeterType::Consumption, MeterType::Production]),
+		..SiteProfile::from_site(&sites[0])
+	};
+	assert_eq!(Some("Europe/Berlin"), profile.time_zone.as_deref());
+	let adapter = RecordingAdapter::default();
+	let urls = adapter.urls.clone();
+	let c = Client::new_with_client(adapter, "").with_site_profile(1, profile);
+	assert!(c.site_profile(1).is_some());
+	assert!(c.site_profile(2).is_none());
+	let range = MetersDateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+		time_unit: None,
+		meters: None,
+	};
+	c.site_energy_details(1, &range).await.unwrap();
+	let range = MetersDateTimeRange {
+		time_unit: Some(TimeUnit::Day),
+		..range
+	};
+	c.site_energy_details(1, &range).await.unwrap();
+	let energy = SiteEnergy {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 12).unwrap(),
+		time_unit: None,
+	};
+	c.site_energy(1, &energy).await.unwrap();
+	let queries = urls
+		.lock()
+		.unwrap()
+		.iter()
+		.map(|url| url.query().unwrap().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			"startTime=2021-08-10+00%3A00%3A00&endTime=2021-08-11+00%3A00%3A00&timeUnit=HOUR&meters=Consumption%2CProduction&api_key=",
+			"startTime=2021-08-10+00%3A00%3A00&endTime=2021-08-11+00%3A00%3A00&timeUnit=DAY&meters=Consumption%2CProduction&api_key=",
+			"startDate=2021-08-10&endDate=2021-08-12&timeUnit=HOUR&api_key=",
+		],
+		queries
+	);
+
+	// the period is checked against the limit of the time unit from the profile
+	let profile = SiteProfile {
+		time_unit: Some(TimeUnit::QuarterOfAnHour),
+		..SiteProfile::default()
+	};
+	let c = c.with_site_profile(1, profile);
+	let energy = SiteEnergy {
+		end_date: NaiveDate::from_ymd_opt(2021, 10, 1).unwrap(),
+		..energy
+	};
+	assert!(matches!(
+		c.site_energy(1, &energy).await,
+		Err(Error::InvalidRequest(crate::InvalidRequest::PeriodTooLong { .. }))
+	));
+	let range = MetersDateTimeRange {
+		end_time: NaiveDate::from_ymd_opt(2021, 10, 1).unwrap().and_time(NaiveTime::MIN),
+		time_unit: None,
+		..range
+	};
+	assert!(matches!(
+		c.site_energy_details(1, &range).await,
+		Err(Error::InvalidRequest(crate::InvalidRequest::PeriodTooLong { .. }))
+	));
+	assert!(matches!(
+		c.site_meters(1, &range).await,
+		Err(Error::InvalidRequest(crate::InvalidRequest::PeriodTooLong { .. }))
+	));
+	// the time unit of the request overrides the profile
+	let energy = SiteEnergy {
+		time_unit: Some(TimeUnit::Day),
+		..energy
+	};
+	c.site_energy(1, &energy).await.unwrap();
+	assert_eq!(4, urls.lock().unwrap().len());
+}
+
+#[test]
+fn number_formatting() {
+	use crate::format::{format_energy, format_kwh, format_power, NumberFormat};
+
+	assert_eq!("1,234,567.89", NumberFormat::EN.format(1234567.891, 2));
+	assert_eq!("1.234.567,9", NumberFormat::DE.format(1234567.891, 1));
+	assert_eq!("1\u{202F}235", NumberFormat::FR.format(1234.6, 0));
+	assert_eq!("-1234.50", NumberFormat::PLAIN.format(-1234.5, 2));
+	assert_eq!("0.00", NumberFormat::EN.format(-0.001, 2));
+	assert_eq!("999", NumberFormat::EN.format(999., 0));
+	assert_eq!("NaN", NumberFormat::EN.format(f64::NAN, 2));
+	assert_eq!("350.0 W", format_power(350., 1, &NumberFormat::EN));
+	assert_eq!("4,20 kW", format_power(4200., 2, &NumberFormat::DE));
+	assert_eq!("-2.5 MW", format_power(-2_500_000., 1, &NumberFormat::EN));
+	assert_eq!("1.20 GWh", format_energy(1.2e9, 2, &NumberFormat::EN));
+	assert_eq!("1,234.5 kWh", format_kwh(1_234_500., 1, &NumberFormat::EN));
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn meter_balance() {
+	use analytics::meters::{reconstruct_missing_meters, validate_meter_balance, MeterRelation, Tolerance};
+
+	let c = fixtures_client();
+	let range = MetersDateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+		time_unit: None,
+		meters: None,
+	};
+	let mut details = c.site_energy_details(1, &range).await.unwrap();
+	assert!(reconstruct_missing_meters(&mut details).is_empty());
+	let feed_in = details.meters[0]
+		.values
+		.iter()
+		.map(|item| response::SiteDateValue {
+			date: item.date,
+			value: item.value.map(|value| value / 4.),
+		})
+		.collect();
+	details.meters.push(response::SiteMeterValue {
+		typ: "FeedIn".to_string(),
+		values: feed_in,
+	});
+	assert_eq!(
+		vec![MeterType::SelfConsumption, MeterType::Consumption],
+		reconstruct_missing_meters(&mut details)
+	);
+	let consumption = &details.meters.last().unwrap().values;
+	assert_eq!(Some(42000.), consumption[0].value);
+	assert_eq!(None, consumption[2].value);
+	assert_eq!(Some(18000.), details.meters[3].values[0].value);
+
+	let tolerance = Tolerance {
+		absolute: 10.,
+		relative: 0.01,
+	};
+	assert!(validate_meter_balance(&details, tolerance).is_empty());
+	details.meters[4].values[1].value = Some(30000.);
+	let violations = validate_meter_balance(&details, tolerance);
+	assert_eq!(1, violations.len());
+	assert_eq!(MeterRelation::Consumption, violations[0].relation);
+	assert_eq!(37625., violations[0].expected);
+	assert_eq!(30000., violations[0].actual);
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn site_sorting() {
+	use crate::diagnostics::find_unsorted_site;
+
+	let c = fixtures_client();
+	let details = c.site_details(1).await.unwrap();
+	assert_eq!(
+		Some(NaiveDate::from_ymd_opt(2019, 4, 20).unwrap().and_hms_opt(9, 30, 0).unwrap()),
+		details.creation_time
+	);
+	let sites = c.sites_list(&SitesList::default()).await.unwrap();
+	assert!(sites[0].creation_time.is_none());
+	assert_eq!(Some(1), find_unsorted_site(&sites, SiteSortBy::Name, SortOrder::Ascending));
+	assert_eq!(None, find_unsorted_site(&sites, SiteSortBy::Name, SortOrder::Descending));
+	assert_eq!(None, find_unsorted_site(&sites, SiteSortBy::City, SortOrder::Descending));
+	assert_eq!(
+		Some(1),
+		find_unsorted_site(&sites, SiteSortBy::PeakPower, SortOrder::Ascending)
+	);
+	assert_eq!(None, find_unsorted_site(&sites, SiteSortBy::PeakPower, SortOrder::Descending));
+}
+
+#[tokio::test]
+async fn rate_limits() {
+	let c = fixtures_client().with_rate_limits(RateLimits {
+		daily_per_key: Some(1),
+		daily_per_site: Some(2),
+		..RateLimits::default()
+	});
+	let limiter = c.rate_limiter().unwrap();
+	assert_eq!(Some(2), limiter.remaining_site_budget(1));
+	c.site_details(1).await.unwrap();
+	c.site_data_period_bulk(&[1, 2]).await.unwrap();
+	assert_eq!(Some(0), limiter.remaining_site_budget(1));
+	assert_eq!(Some(1), limiter.remaining_site_budget(2));
+	assert!(matches!(
+		c.site_overview(1).await,
+		Err(Error::BudgetExhausted(RateLimitExceeded::Site(1)))
+	));
+	assert!(matches!(
+		c.site_data_period_bulk(&[2, 1]).await,
+		Err(Error::BudgetExhausted(RateLimitExceeded::Site(1)))
+	));
+	assert_eq!(Some(1), limiter.remaining_site_budget(2));
+	c.equipment_list(2).await.ok();
+	assert_eq!(Some(0), limiter.remaining_site_budget(2));
+
+	c.sites_list(&SitesList::default()).await.unwrap();
+	assert_eq!(Some(0), limiter.remaining_key_budget());
+	assert!(matches!(
+		c.version_current().await,
+		Err(Error::BudgetExhausted(RateLimitExceeded::Key))
+	));
+	assert_eq!(0, limiter.active_requests());
+
+	let c2 = c.clone();
+	assert_eq!(Some(0), c2.rate_limiter().unwrap().remaining_key_budget());
+}
+
+#[tokio::test]
+async fn retry_policy() {
+	use std::sync::atomic::AtomicUsize;
+	use std::sync::{Arc, Mutex};
+	use std::time::Duration;
+
+	let policy = RetryPolicy {
+		jitter: 0.,
+		..RetryPolicy::default()
+	};
+	let unavailable = Error::<()>::Api(http_adapter::http::StatusCode::SERVICE_UNAVAILABLE, vec![]);
+	assert_eq!(Some(Duration::from_millis(500)), policy.delay_for(1, &unavailable));
+	assert_eq!(Some(Duration::from_millis(1000)), policy.delay_for(2, &unavailable));
+	assert_eq!(None, policy.delay_for(3, &unavailable));
+	let forbidden = Error::<()>::Api(http_adapter::http::StatusCode::FORBIDDEN, vec![]);
+	assert_eq!(None, policy.delay_for(1, &forbidden));
+	let jittered = RetryPolicy::default().delay_for(1, &unavailable).unwrap();
+	assert!(jittered > Duration::from_millis(250) && jittered <= Duration::from_millis(500));
+
+	let delays = Arc::new(Mutex::new(vec![]));
+	let sleep = {
+		let delays = delays.clone();
+		move |delay| {
+			delays.lock().unwrap().push(delay);
+			async {}
+		}
+	};
+	let c = Client::new_with_client(
+		FlakyAdapter {
+			failures: AtomicUsize::new(2),
+			status: http_adapter::http::StatusCode::SERVICE_UNAVAILABLE,
+		},
+		"",
+	)
+	.with_retry(policy.clone(), sleep.clone());
+	assert_eq!("1.0.0", c.version_current().await.unwrap());
+	assert_eq!(
+		vec![Duration::from_millis(500), Duration::from_millis(1000)],
+		*delays.lock().unwrap()
+	);
+
+	let c = Client::new_with_client(
+		FlakyAdapter {
+			failures: AtomicUsize::new(3),
+			status: http_adapter::http::StatusCode::SERVICE_UNAVAILABLE,
+		},
+		"",
+	)
+	.with_retry(policy, sleep);
+	assert!(matches!(c.version_current().await, Err(Error::Api(status, _)) if status == 503));
+}
+
+#[tokio::test]
+async fn site_list_filtering() {
+	let c = fixtures_client();
+	let all_sites = c.sites_list(&SitesList::default()).await.unwrap();
+	let mut sites = c.sites_list(&SitesList::default()).await.unwrap();
+	let p = SitesList {
+		sort_property: Some(SiteSortBy::Name),
+		..Default::default()
+	};
+	p.apply(&mut sites);
+	assert_eq!(vec![2, 1], sites.iter().map(|s| s.id).collect::<Vec<_>>());
+	let p = SitesList {
+		sort_property: Some(SiteSortBy::Name),
+		sort_order: Some(SortOrder::Descending),
+		start_index: Some(1),
+		..Default::default()
+	};
+	p.apply(&mut sites);
+	assert_eq!(vec![2], sites.iter().map(|s| s.id).collect::<Vec<_>>());
+
+	let p = SitesList {
+		search_text: Some("gara"),
+		..Default::default()
+	};
+	assert_eq!(vec![false, true], all_sites.iter().map(|s| p.matches(s)).collect::<Vec<_>>());
+	let p = SitesList {
+		status: Some(&[SiteStatus::Disabled]),
+		..Default::default()
+	};
+	assert!(!all_sites.iter().any(|s| p.matches(s)));
+	let p = SitesList {
+		status: Some(&[SiteStatus::All]),
+		search_text: Some("BERLIN"),
+		..Default::default()
+	};
+	assert!(all_sites.iter().all(|s| p.matches(s)));
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn sensor_topology() {
+	let c = fixtures_client();
+	let inventory = c.site_inventory(1).await.unwrap();
+	let sensors = c.equipment_sensors(1).await.unwrap();
+	let range = SiteSensorData {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let data = c.site_sensor_data(1, &range).await.unwrap();
+	let topology = analytics::sensors::sensor_topology(&inventory.gateways, &sensors, &data);
+	assert_eq!(2, topology.len());
+	assert_eq!("Gateway 1", topology[0].name);
+	assert_eq!(Some("2.1"), topology[0].gateway.map(|g| g.firmware_version.as_str()));
+	assert_eq!(2, topology[0].sensors.len());
+	assert_eq!(Some(810.), topology[0].telemetries[0].global_horizontal_irradiance);
+	assert_eq!("Gateway 2", topology[1].name);
+	assert!(topology[1].gateway.is_none());
+	assert!(topology[1].sensors.is_empty());
+	assert_eq!(Some(3.2), topology[1].telemetries[0].wind_speed);
+}
+
+#[test]
+fn api_error_body() {
+	use http_adapter::http::StatusCode;
+
+	let err = Error::<String>::Api(StatusCode::FORBIDDEN, br#"{"String": "Invalid token"}"#.to_vec());
+	assert_eq!(
+		Some(ApiErrorBody {
+			code: None,
+			message: "Invalid token".to_string()
+		}),
+		err.api_error_body()
+	);
+	assert_eq!("Solaredge HTTP API error: 403 Forbidden, Invalid token", err.to_string());
+	let err = Error::<String>::Api(
+		StatusCode::BAD_REQUEST,
+		br#"{"error": {"code": 12, "message": "Invalid date range"}}"#.to_vec(),
+	);
+	let body = err.api_error_body().unwrap();
+	assert_eq!(Some("12"), body.code.as_deref());
+	assert_eq!("12: Invalid date range", body.to_string());
+	let err = Error::<String>::Api(StatusCode::BAD_GATEWAY, b"<html>Bad Gateway</html>".to_vec());
+	assert!(err.api_error_body().is_none());
+	assert_eq!("Solaredge HTTP API error: 502 Bad Gateway", err.to_string());
+}
+
+#[test]
+fn http_date_parsing() {
+	use std::time::{Duration, UNIX_EPOCH};
+
+	use crate::error::{parse_http_date, parse_retry_after};
+
+	assert_eq!(Some(784111777), parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"));
+	assert_eq!(Some(951782400), parse_http_date("Tue, 29 Feb 2000 00:00:00 GMT"));
+	assert_eq!(Some(253402300799), parse_http_date("Fri, 31 Dec 9999 23:59:59 GMT"));
+	// leap second
+	assert_eq!(Some(1483228800), parse_http_date("Sat, 31 Dec 2016 23:59:60 GMT"));
+	for malformed in [
+		"Sun, 06 Nov 2030 99999999999999999:00:00 GMT",
+		"Sun, 06 Nov 99999999999999 08:49:37 GMT",
+		"Sun, 06 Nov 10000 08:49:37 GMT",
+		"Sun, 06 Nov 1969 08:49:37 GMT",
+		"Sun, 06 Nov 1994 24:00:00 GMT",
+		"Sun, 06 Nov 1994 08:60:00 GMT",
+		"Sun, 06 Nov 1994 08:49:61 GMT",
+		"Sun, 06 Nov 1994 08:49:37:00 GMT",
+		"Sun, 31 Nov 1994 08:49:37 GMT",
+		"Mon, 29 Feb 2100 08:49:37 GMT",
+		"Sun, 00 Nov 1994 08:49:37 GMT",
+		"Sun, 06 Nov 1994 08:49:37 UTC",
+	] {
+		assert_eq!(None, parse_http_date(malformed), "{malformed}");
+		assert_eq!(None, parse_retry_after(malformed, UNIX_EPOCH), "{malformed}");
+	}
+	assert_eq!(
+		Some(Duration::from_secs(784111777)),
+		parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", UNIX_EPOCH)
+	);
+}
+
+#[tokio::test]
+async fn throttling() {
+	use std::sync::atomic::AtomicUsize;
+	use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+	use crate::error::parse_retry_after;
+
+	let now = UNIX_EPOCH + Duration::from_secs(784111777);
+	assert_eq!(Some(Duration::from_secs(120)), parse_retry_after("120", now));
+	assert_eq!(
+		Some(Duration::from_secs(60)),
+		parse_retry_after("Sun, 06 Nov 1994 08:50:37 GMT", now)
+	);
+	assert_eq!(Some(Duration::ZERO), parse_retry_after("Sun, 06 Nov 1994 08:49:00 GMT", now));
+	assert_eq!(None, parse_retry_after("soon", now));
+	assert!(SystemTime::now() > now);
+
+	let c = Client::new_with_client(
+		FlakyAdapter {
+			failures: AtomicUsize::new(1),
+			status: http_adapter::http::StatusCode::TOO_MANY_REQUESTS,
+		},
+		"",
+	);
+	let err = c.version_current().await.unwrap_err();
+	assert!(matches!(
+		err,
+		Error::RateLimited {
+			retry_after: Some(retry_after),
+			..
+		} if retry_after == Duration::from_secs(7)
+	));
+	assert_eq!(None, RetryPolicy::default().delay_for(1, &err));
+	let policy = RetryPolicy {
+		jitter: 0.,
+		retry_on_status: vec![http_adapter::http::StatusCode::TOO_MANY_REQUESTS],
+		..RetryPolicy::default()
+	};
+	assert_eq!(Some(Duration::from_secs(7)), policy.delay_for(1, &err));
+	assert_eq!("1.0.0", c.version_current().await.unwrap());
+}
+
+#[cfg(feature = "analytics")]
+#[tokio::test]
+async fn device_model() {
+	use analytics::devices::{inventory_devices, Device, DeviceKind};
+
+	let c = fixtures_client();
+	let inventory = c.site_inventory(1).await.unwrap();
+	let devices = inventory_devices(&inventory);
+	let kinds = devices.iter().map(Device::kind).collect::<Vec<_>>();
+	assert!(kinds.windows(2).all(|pair| pair[0] as u8 <= pair[1] as u8));
+	let sensor = devices.iter().find(|d| d.kind() == DeviceKind::Sensor).unwrap();
+	assert_eq!("SENSOR1", sensor.name());
+	assert_eq!(None, sensor.serial());
+	assert_eq!(Some("12345678"), sensor.connected_to_serial());
+	let gateway = devices.iter().find(|d| d.kind() == DeviceKind::Gateway).unwrap();
+	assert_eq!("Gateway 1", gateway.name());
+	assert_eq!(Some("2.1"), gateway.firmware_version());
+	let equipment = c.equipment_list(1).await.unwrap();
+	let device = Device::from(&equipment[0]);
+	assert_eq!(DeviceKind::Inverter, device.kind());
+	assert_eq!(Some("12345678"), device.serial());
+	assert_eq!("Inverter", device.kind().to_string());
+}
+
+/// Serves the `startIndex`/`size` slice of the recorded sites or accounts list and records the requested URLs
+#[derive(Default)]
+struct PagingAdapter {
+	urls: std::sync::Arc<std::sync::Mutex<Vec<url::Url>>>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl http_adapter::HttpClientAdapter for PagingAdapter {
+	type Error = std::io::Error;
+
+	async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+		let url = url::Url::parse(&request.uri().to_string()).unwrap();
+		let param = |name: &str| {
+			url.query_pairs()
+				.find(|(key, _)| key == name)
+				.and_then(|(_, value)| value.parse::<usize>().ok())
+		};
+		let (start_index, size) = (param("startIndex").unwrap_or(0), param("size").unwrap_or(100));
+		let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+			.join("tests/fixtures")
+			.join(url.path().trim_start_matches('/'));
+		let mut body: serde_json::Value = serde_json::from_slice(&std::fs::read(fixture)?)?;
+		let items = match url.path() {
+			"/accounts/list.json" => &mut body["accounts"]["list"],
+			_ => &mut body["sites"]["site"],
+		};
+		let items = items.as_array_mut().unwrap();
+		*items = items.iter().skip(start_index).take(size).cloned().collect();
+		self.urls.lock().unwrap().push(url);
+		Ok(http_adapter::Response::new(serde_json::to_vec(&body)?))
+	}
+}
+
+#[tokio::test]
+async fn sites_pagination() {
+	let all_sites = fixtures_client().sites_list(&SitesList::default()).await.unwrap();
+	let all_ids = all_sites.iter().map(|site| site.id).collect::<Vec<_>>();
+
+	let adapter = PagingAdapter::default();
+	let urls = adapter.urls.clone();
+	let c = Client::new_with_client(adapter, "");
+	let sites = c.sites_list_all(&SitesList::default()).try_collect().await.unwrap();
+	assert_eq!(all_ids, sites.iter().map(|site| site.id).collect::<Vec<_>>());
+	assert_eq!(1, urls.lock().unwrap().len());
+
+	urls.lock().unwrap().clear();
+	let params = SitesList {
+		size: Some(1),
+		..SitesList::default()
+	};
+	let sites = c.sites_list_all(&params).try_collect().await.unwrap();
+	assert_eq!(all_ids, sites.iter().map(|site| site.id).collect::<Vec<_>>());
+	let start_indices = urls
+		.lock()
+		.unwrap()
+		.iter()
+		.filter_map(|url| {
+			url.query_pairs()
+				.find(|(key, _)| key == "startIndex")
+				.map(|(_, value)| value.into_owned())
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(vec!["0", "1"], start_indices);
+
+	let params = SitesList {
+		size: Some(1),
+		start_index: Some(1),
+		..SitesList::default()
+	};
+	let sites = c.sites_list_all(&params).try_collect().await.unwrap();
+	assert_eq!(all_ids[1..], sites.iter().map(|site| site.id).collect::<Vec<_>>());
+
+	let c = Client::new_with_environment(ReqwestAdapter::default(), "", Environment::Replay("/nonexistent".into()));
+	assert!(matches!(
+		c.sites_list_all(&SitesList::default()).try_collect().await,
+		Err(Error::Replay(..))
+	));
+}
+
+#[tokio::test]
+async fn response_meta() {
+	let c = fixtures_client().with_api_version("1.0.0");
+	let details = c.with_meta(|c| async move { c.site_details(1).await }).await.unwrap();
+	assert_eq!(1, details.value.id);
+	assert_eq!(1, details.meta.len());
+	let meta = &details.meta[0];
+	assert_eq!("site_details", meta.endpoint);
+	assert_eq!("/site/1/details.json", meta.path);
+	assert_eq!(Some("1.0.0"), meta.api_version.as_deref());
+	assert!(meta.from_cache);
+	assert_eq!(1, meta.attempts);
+	assert_eq!(Some(meta.fetched_at), details.fetched_at());
+
+	let overview = c
+		.with_meta(|c| async move {
+			let site = c.site_details(1).await?;
+			c.follow_details(&site.uris).await
+		})
+		.await
+		.unwrap();
+	assert!(overview.meta.len() > 1);
+	assert!(overview.any_from_cache());
+	assert!(overview.meta.windows(2).all(|pair| pair[0].fetched_at <= pair[1].fetched_at));
+	// the recorder is attached only to the clone passed to the request
+	c.site_details(1).await.unwrap();
+	assert!(c
+		.with_meta(|_| async { Ok::<_, Error<()>>(()) })
+		.await
+		.unwrap()
+		.meta
+		.is_empty());
+
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_client(adapter, "");
+	let details = c.with_meta(|c| async move { c.site_details(1).await }).await.unwrap();
+	assert!(!details.any_from_cache());
+	assert_eq!(None, details.meta[0].api_version);
+}
+
+#[tokio::test]
+async fn accounts_pagination() {
+	let all_accounts = fixtures_client().accounts_list(&AccountsList::default()).await.unwrap();
+	let all_ids = all_accounts.iter().map(|account| account.id).collect::<Vec<_>>();
+
+	let adapter = PagingAdapter::default();
+	let urls = adapter.urls.clone();
+	let c = Client::new_with_client(adapter, "");
+	let params = AccountsList {
+		size: Some(3),
+		..AccountsList::default()
+	};
+	let accounts = c.accounts_list_all(&params).try_collect().await.unwrap();
+	assert_eq!(all_ids, accounts.iter().map(|account| account.id).collect::<Vec<_>>());
+	assert_eq!(2, urls.lock().unwrap().len());
+
+	let params = AccountsList {
+		size: Some(2),
+		start_index: Some(1),
+		..AccountsList::default()
+	};
+	let accounts = c.accounts_list_all(&params).try_collect().await.unwrap();
+	assert_eq!(all_ids[1..], accounts.iter().map(|account| account.id).collect::<Vec<_>>());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn request_keys() {
+	use crate::RequestKey;
+
+	let details = RequestKey::new("site/1/details.json", ()).unwrap();
+	assert_eq!("/site/1/details.json", details.as_str());
+	assert_eq!(details, RequestKey::new("/site/1/details.json", ()).unwrap());
+	// FNV-1a of the canonical form, must never change
+	assert_eq!(0x8e38dc3b67aa6768, details.stable_hash());
+	assert_eq!("8e38dc3b67aa6768", details.to_string());
+
+	let params = SiteEnergy {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 12).unwrap(),
+		time_unit: Some(TimeUnit::Day),
+	};
+	let key = RequestKey::new("/site/1/energy.json", &params).unwrap();
+	assert_eq!(
+		"/site/1/energy.json?endDate=2021-08-12&startDate=2021-08-10&timeUnit=DAY",
+		key.as_str()
+	);
+	let reordered = RequestKey::new(
+		"/site/1/energy.json",
+		[
+			("timeUnit", "DAY"),
+			("startDate", "2021-08-10"),
+			("api_key", "KEY"),
+			("endDate", "2021-08-12"),
+		],
+	)
+	.unwrap();
+	assert_eq!(key, reordered);
+	assert_eq!(key.stable_hash(), reordered.stable_hash());
+	assert_ne!(key, RequestKey::new("/site/2/energy.json", &params).unwrap());
+
+	let c = fixtures_client();
+	let energy = c.with_meta(|c| async move { c.site_energy(1, &params).await }).await.unwrap();
+	assert_eq!(key, energy.meta[0].request_key);
+}
+
+/// Serves the recorded `/sites/1,2/...` bulk fixtures for any list of site ids and records the requested URLs
+#[cfg(feature = "chrono")]
+#[derive(Default)]
+struct BulkAdapter {
+	urls: std::sync::Arc<std::sync::Mutex<Vec<url::Url>>>,
+}
+
+#[cfg(feature = "chrono")]
+#[async_trait::async_trait(?Send)]
+impl http_adapter::HttpClientAdapter for BulkAdapter {
+	type Error = std::io::Error;
+
+	async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+		let url = url::Url::parse(&request.uri().to_string()).unwrap();
+		let file = url.path().rsplit('/').next().unwrap();
+		let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+			.join("tests/fixtures/sites/1,2")
+			.join(file);
+		self.urls.lock().unwrap().push(url);
+		Ok(http_adapter::Response::new(std::fs::read(fixture)?))
+	}
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn bulk_chunking() {
+	let adapter = BulkAdapter::default();
+	let urls = adapter.urls.clone();
+	let c = Client::new_with_client(adapter, "");
+	let site_ids = (1..=250).collect::<Vec<u64>>();
+	let chunk_sizes = || {
+		urls
+			.lock()
+			.unwrap()
+			.drain(..)
+			.map(|url| url.path().split('/').nth(2).unwrap().split(',').count())
+			.collect::<Vec<_>>()
+	};
+
+	let periods = c.site_data_period_bulk(&site_ids).await.unwrap();
+	assert_eq!(vec![100, 100, 50], chunk_sizes());
+	assert_eq!(6, periods.len());
+
+	let energy = c
+		.site_energy_bulk(
+			&site_ids,
+			&SiteEnergy {
+				start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+				end_date: NaiveDate::from_ymd_opt(2021, 8, 12).unwrap(),
+				time_unit: Some(TimeUnit::Day),
+			},
+		)
+		.await
+		.unwrap();
+	assert_eq!(vec![100, 100, 50], chunk_sizes());
+	assert_eq!(6, energy.site_energy_list.len());
+	assert_eq!(6, energy.count);
+
+	let time_range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let power = c.site_power_bulk(&site_ids[..100], &time_range).await.unwrap();
+	assert_eq!(vec![100], chunk_sizes());
+	assert_eq!(2, power.site_energy_list.len());
+
+	c.site_data_period_bulk(&[SiteId(0); 0]).await.ok();
+	assert_eq!(1, chunk_sizes().len());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn bulk_chunk_units() {
+	/// Serves the bulk fixtures with the `unit` of the responses after the first one replaced
+	#[derive(Default)]
+	struct MixedUnitsAdapter {
+		inner: BulkAdapter,
+		units: std::sync::Mutex<Vec<(&'static str, &'static str)>>,
+	}
+
+	#[async_trait::async_trait(?Send)]
+	impl http_adapter::HttpClientAdapter for MixedUnitsAdapter {
+		type Error = std::io::Error;
+
+		async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+			let first = self.inner.urls.lock().unwrap().is_empty();
+			let res = self.inner.execute(request).await?;
+			if first {
+				return Ok(res);
+			}
+			let mut body = String::from_utf8(res.into_body()).unwrap();
+			for (from, to) in self.units.lock().unwrap().iter() {
+				body = body.replace(from, to);
+			}
+			Ok(http_adapter::Response::new(body.into_bytes()))
+		}
+	}
+
+	let adapter = MixedUnitsAdapter::default();
+	*adapter.units.lock().unwrap() = vec![(r#""unit": "Wh""#, r#""unit": "kWh""#), (r#""unit": "W""#, r#""unit": "kW""#)];
+	let c = Client::new_with_client(adapter, "");
+	let site_ids = (1..=150).collect::<Vec<u64>>();
+	let energy = SiteEnergy {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 12).unwrap(),
+		time_unit: Some(TimeUnit::Day),
+	};
+	let res = c.site_energy_bulk(&site_ids, &energy).await.unwrap();
+	assert_eq!("Wh", res.unit);
+	// the second chunk reported 24000 kWh
+	assert_eq!(Some(24_000.), res.site_energy_list[0].energy_values.values[0].value);
+	assert_eq!(Some(24_000_000.), res.site_energy_list[2].energy_values.values[0].value);
+
+	let time_range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let adapter = MixedUnitsAdapter::default();
+	*adapter.units.lock().unwrap() = vec![(r#""unit": "W""#, r#""unit": "kW""#)];
+	let c = Client::new_with_client(adapter, "");
+	let res = c.site_power_bulk(&site_ids, &time_range).await.unwrap();
+	assert_eq!("W", res.unit);
+	let first = res.site_energy_list[0].power_data_value_series.values[0].value.unwrap();
+	assert_eq!(
+		Some(first * 1000.),
+		res.site_energy_list[2].power_data_value_series.values[0].value
+	);
+
+	let adapter = MixedUnitsAdapter::default();
+	*adapter.units.lock().unwrap() = vec![(r#""unit": "W""#, r#""unit": "hp""#)];
+	let c = Client::new_with_client(adapter, "");
+	assert!(matches!(
+		c.site_power_bulk(&site_ids, &time_range).await,
+		Err(Error::UnitMismatch { expected, actual }) if expected == "W" && actual == "hp"
+	));
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn energy_range_splitting() {
+	assert_eq!(Some(1), TimeUnit::QuarterOfAnHour.max_energy_period_months());
+	assert_eq!(None, TimeUnit::Month.max_energy_period_months());
+
+	let adapter = RecordingAdapter::default();
+	let urls = adapter.urls.clone();
+	let c = Client::new_with_client(adapter, "");
+	let windows = || {
+		urls
+			.lock()
+			.unwrap()
+			.drain(..)
+			.map(|url| {
+				let param = |name: &str| url.query_pairs().find(|(key, _)| key == name).unwrap().1.into_owned();
+				(param("startDate"), param("endDate"))
+			})
+			.collect::<Vec<_>>()
+	};
+	let single = c
+		.site_energy(
+			1,
+			&SiteEnergy {
+				start_date: NaiveDate::from_ymd_opt(2021, 1, 15).unwrap(),
+				end_date: NaiveDate::from_ymd_opt(2021, 1, 16).unwrap(),
+				time_unit: None,
+			},
+		)
+		.await
+		.unwrap();
+	windows();
+
+	let energy = c
+		.site_energy_full_range(
+			1,
+			&SiteEnergy {
+				start_date: NaiveDate::from_ymd_opt(2021, 1, 15).unwrap(),
+				end_date: NaiveDate::from_ymd_opt(2021, 3, 20).unwrap(),
+				time_unit: Some(TimeUnit::QuarterOfAnHour),
+			},
+		)
+		.await
+		.unwrap();
+	let window = |start: &str, end: &str| (start.to_string(), end.to_string());
+	assert_eq!(
+		vec![
+			window("2021-01-15", "2021-02-14"),
+			window("2021-02-15", "2021-03-14"),
+			window("2021-03-15", "2021-03-20"),
+		],
+		windows()
+	);
+	assert_eq!(single.values.len() * 3, energy.values.len());
+
+	let params = SiteEnergy {
+		start_date: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 12, 31).unwrap(),
+		time_unit: None,
+	};
+	c.site_energy_full_range(1, &params).await.unwrap();
+	assert_eq!(
+		vec![window("2020-01-01", "2020-12-31"), window("2021-01-01", "2021-12-31")],
+		windows()
+	);
+	let params = SiteEnergy {
+		time_unit: Some(TimeUnit::Month),
+		..params
+	};
+	c.site_energy_full_range(1, &params).await.unwrap();
+	assert_eq!(vec![window("2020-01-01", "2021-12-31")], windows());
+}
+
+#[tokio::test]
+async fn batch_requests() {
+	use std::cell::Cell;
+	use std::task::Poll;
+
+	let c = fixtures_client();
+	let mut batch = c.batch();
+	let overview = batch.add(|c| c.site_overview(1));
+	let details = batch.add(|c| c.site_details(1));
+	let version = batch.add(|c| c.version_current());
+	let missing = batch.add(|c| c.site_details(404));
+	assert_eq!(4, batch.len());
+	let mut results = batch.execute().await;
+	assert_eq!(1, results.take(details).unwrap().unwrap().id);
+	results.take(overview).unwrap().unwrap();
+	assert!(!results.take(version).unwrap().unwrap().is_empty());
+	let missing = results.take(missing);
+	assert!(matches!(missing, Some(Err(Error::Replay(..)))), "{missing:?}");
+	assert!(results.take(details).is_none());
+	assert_eq!(0, results.remaining());
+
+	let in_flight = Cell::new(0);
+	let max_in_flight = Cell::new(0);
+	let mut batch = c.batch().with_concurrency(2);
+	let handles = (0..5)
+		.map(|i| {
+			let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+			batch.add(move |_| async move {
+				in_flight.set(in_flight.get() + 1);
+				max_in_flight.set(max_in_flight.get().max(in_flight.get()));
+				let mut yielded = false;
+				std::future::poll_fn(|cx| {
+					if yielded {
+						Poll::Ready(())
+					} else {
+						yielded = true;
+						cx.waker().wake_by_ref();
+						Poll::Pending
+					}
+				})
+				.await;
+				in_flight.set(in_flight.get() - 1);
+				Ok(i)
+			})
+		})
+		.collect::<Vec<_>>();
+	let mut results = batch.execute().await;
+	assert_eq!(2, max_in_flight.get());
+	for (i, handle) in handles.into_iter().enumerate() {
+		assert_eq!(i, results.take(handle).unwrap().unwrap());
+	}
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn weekly_windows() {
+	let adapter = RecordingAdapter::default();
+	let urls = adapter.urls.clone();
+	let c = Client::new_with_client(adapter, "");
+	let windows = || {
+		urls
+			.lock()
+			.unwrap()
+			.drain(..)
+			.map(|url| {
+				let param = |name: &str| url.query_pairs().find(|(key, _)| key == name).unwrap().1.into_owned();
+				(param("startTime"), param("endTime"))
+			})
+			.collect::<Vec<_>>()
+	};
+	let window = |start: &str, end: &str| (format!("{start} 00:00:00"), format!("{end} 00:00:00"));
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 1).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 16).unwrap().and_time(NaiveTime::MIN),
+	};
+	let week = DateTimeRange {
+		start_time: range.start_time,
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 8).unwrap().and_time(NaiveTime::MIN),
+	};
+	let single = c.equipment_data(1, "12345678", &week).await.unwrap();
+	windows();
+
+	let telemetries = c.equipment_data_range(1, "12345678", &range).await.unwrap();
+	let expected_windows = vec![
+		window("2021-08-01", "2021-08-08"),
+		window("2021-08-08", "2021-08-15"),
+		window("2021-08-15", "2021-08-16"),
+	];
+	assert_eq!(expected_windows, windows());
+	// every window returns the same fixture, the repeated samples are removed
+	let mut expected = single.iter().map(|telemetry| telemetry.date).collect::<Vec<_>>();
+	expected.sort();
+	expected.dedup();
+	let mut dates = telemetries.iter().map(|telemetry| telemetry.date).collect::<Vec<_>>();
+	dates.sort();
+	assert_eq!(expected, dates);
+
+	let storage_range = SiteStorageData {
+		start_time: range.start_time,
+		end_time: range.end_time,
+		serials: None,
+	};
+	let single = c
+		.site_storage_data(
+			1,
+			&SiteStorageData {
+				start_time: week.start_time,
+				end_time: week.end_time,
+				serials: None,
+			},
+		)
+		.await
+		.unwrap();
+	windows();
+	let storage = c.site_storage_data_range(1, &storage_range).await.unwrap();
+	assert_eq!(expected_windows, windows());
+	assert_eq!(single.batteries.len(), storage.battery_count);
+	for (single, battery) in single.batteries.iter().zip(&storage.batteries) {
+		assert_eq!(single.serial_number, battery.serial_number);
+		assert_eq!(battery.telemetries.len(), battery.telemetry_count);
+		assert!(battery.telemetries.len() <= single.telemetries.len());
+	}
+
+	let short = DateTimeRange {
+		start_time: range.start_time,
+		end_time: range.start_time,
+	};
+	c.equipment_data_range(1, "12345678", &short).await.unwrap();
+	assert_eq!(1, windows().len());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn request_validation() {
+	use crate::{InvalidRequest, MaxPeriod, Validate};
+
+	let date = |month, day| NaiveDate::from_ymd_opt(2021, month, day).unwrap();
+	let energy = |start_date, end_date, time_unit| SiteEnergy {
+		start_date,
+		end_date,
+		time_unit,
+	};
+	assert_eq!(
+		Ok(()),
+		energy(date(1, 15), date(2, 14), Some(TimeUnit::QuarterOfAnHour)).validate()
+	);
+	assert_eq!(
+		Err(InvalidRequest::PeriodTooLong {
+			max: MaxPeriod::Months(1)
+		}),
+		energy(date(1, 15), date(2, 15), Some(TimeUnit::Hour)).validate()
+	);
+	assert_eq!(Ok(()), energy(date(1, 15), date(2, 15), None).validate());
+	assert_eq!(Ok(()), energy(date(1, 1), date(12, 31), Some(TimeUnit::Month)).validate());
+	assert_eq!(
+		Err(InvalidRequest::StartAfterEnd),
+		energy(date(1, 2), date(1, 1), Some(TimeUnit::Year)).validate()
+	);
+
+	let range = |start: NaiveDate, end: NaiveDate| DateTimeRange {
+		start_time: start.and_time(NaiveTime::MIN),
+		end_time: end.and_time(NaiveTime::MIN),
+	};
+	assert_eq!(Ok(()), range(date(8, 1), date(8, 8)).validate_max_period(MaxPeriod::Days(7)));
+	assert_eq!(Ok(()), range(date(8, 1), date(9, 1)).validate());
+	assert_eq!(
+		"period is longer than 7 days",
+		range(date(8, 1), date(8, 9))
+			.validate_max_period(MaxPeriod::Days(7))
+			.unwrap_err()
+			.to_string()
+	);
+	let sites = SitesList {
+		size: Some(101),
+		..SitesList::default()
+	};
+	assert_eq!(Err(InvalidRequest::PageSizeTooLarge { size: 101 }), sites.validate());
+
+	// nothing is sent, so the rate limiter is not charged
+	let c = fixtures_client().with_rate_limits(RateLimits::default());
+	assert!(matches!(
+		c.sites_list(&sites).await,
+		Err(Error::InvalidRequest(InvalidRequest::PageSizeTooLarge { size: 101 }))
+	));
+	assert!(matches!(
+		c.equipment_data(1, "12345678", &range(date(8, 1), date(8, 9))).await,
+		Err(Error::InvalidRequest(InvalidRequest::PeriodTooLong { .. }))
+	));
+	assert!(matches!(
+		c.site_energy(1, &energy(date(1, 2), date(1, 1), None)).await,
+		Err(Error::InvalidRequest(InvalidRequest::StartAfterEnd))
+	));
+	let limiter = c.rate_limiter().unwrap();
+	assert_eq!(Some(300), limiter.remaining_key_budget());
+	assert_eq!(Some(300), limiter.remaining_site_budget(1));
+}
+
+#[tokio::test]
+async fn site_tasks() {
+	let c = Client::new_with_client(RecordingAdapter::default(), "");
+	let local = tokio::task::LocalSet::new();
+	let mut results = local
+		.run_until(async {
+			let mut tasks = tokio::task::JoinSet::new();
+			for task in c.site_tasks(&[1, 404], |c, site_id| async move { c.site_overview(site_id).await }) {
+				tasks.spawn_local(task);
+			}
+			drop(c);
+			let mut results = vec![];
+			while let Some(res) = tasks.join_next().await {
+				results.push(res.unwrap());
+			}
+			results
+		})
+		.await;
+	results.sort_by_key(|res| match res {
+		Ok((site_id, _)) => *site_id,
+		Err(e) => e.site_id,
+	});
+	assert!(matches!(results[0], Ok((SiteId(1), _))));
+	let Err(e) = &results[1] else {
+		panic!("Site 404 must fail");
+	};
+	assert_eq!(404, e.site_id);
+	assert!(matches!(e.error, Error::HttpRequest(_)));
+	assert!(e.to_string().starts_with("Site 404: HTTP request error"));
+	assert!(std::error::Error::source(e).is_some());
+}
+
+#[tokio::test]
+async fn rate_limiter_persistence() {
+	use crate::RateLimiterUsage;
+
+	let limits = RateLimits {
+		daily_per_key: Some(3),
+		..RateLimits::default()
+	};
+	let c = fixtures_client().with_rate_limits(limits);
+	c.version_current().await.unwrap();
+	c.site_details(1).await.unwrap();
+	let usage = c.rate_limiter().unwrap().usage();
+	assert_eq!(1, usage.key_used);
+	assert_eq!(Some(&1), usage.site_used.get(&1));
+	let saved = serde_json::to_string(&usage).unwrap();
+
+	let restarted = fixtures_client().with_rate_limits(limits);
+	let limiter = restarted.rate_limiter().unwrap();
+	assert!(limiter.restore(&serde_json::from_str(&saved).unwrap()));
+	assert_eq!(Some(2), limiter.remaining_key_budget());
+	assert_eq!(Some(299), limiter.remaining_site_budget(1));
+	assert_eq!(usage, limiter.usage());
+
+	let yesterday = RateLimiterUsage {
+		day: usage.day - 1,
+		key_used: 3,
+		..RateLimiterUsage::default()
+	};
+	assert!(!limiter.restore(&yesterday));
+	assert_eq!(Some(2), limiter.remaining_key_budget());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn id_newtypes() {
+	use crate::SerialNumber;
+
+	let c = fixtures_client();
+	let site = c.site_details(SiteId(1)).await.unwrap();
+	assert_eq!(SiteId(1), site.id);
+	assert_eq!(1, site.id);
+	assert_eq!("1", site.id.to_string());
+	assert_eq!(site.id, c.site_details(1).await.unwrap().id);
+	let inventory = c.site_inventory(site.id).await.unwrap();
+	let inverter = &inventory.inverters[0];
+	assert_eq!(SerialNumber::from("12345678"), inverter.sn);
+	assert_eq!("12345678", inverter.sn);
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	c.equipment_data(site.id, &inverter.sn, &range).await.unwrap();
+	assert_eq!("1", serde_json::to_string(&site.id).unwrap());
+	assert_eq!(SiteId(7), serde_json::from_str::<SiteId>("7").unwrap());
+	assert_eq!(7, u64::from(SiteId(7)));
+}
+
+#[tokio::test]
+async fn site_handle() {
+	let c = fixtures_client();
+	let site = c.site(1);
+	assert_eq!(1, site.id());
+	assert_eq!(c.site_details(1).await.unwrap(), site.details().await.unwrap());
+	assert_eq!(c.site_overview(1).await.unwrap(), site.overview().await.unwrap());
+	assert_eq!(c.site_current_power_flow(1).await.unwrap(), site.power_flow().await.unwrap());
+	assert_eq!(c.site_inventory(1).await.unwrap(), site.inventory().await.unwrap());
+	assert!(c.site(404).details().await.is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn equipment_handle() {
+	let c = fixtures_client();
+	let inverter = c.equipment(1, "12345678");
+	assert_eq!(1, inverter.site_id());
+	assert_eq!("12345678", inverter.serial_number());
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 1).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 2).unwrap().and_time(NaiveTime::MIN),
+	};
+	assert_eq!(
+		c.equipment_data(1, "12345678", &range).await.unwrap(),
+		inverter.data(&range).await.unwrap()
+	);
+	assert_eq!(
+		c.equipment_changelog(1, "12345678").await.unwrap(),
+		inverter.changelog().await.unwrap()
+	);
+	let same = c.site(1).equipment("12345678");
+	assert_eq!(inverter.serial_number(), same.serial_number());
+	assert_eq!(1, same.site().id());
+}
+
+#[tokio::test]
+async fn secret_provider() {
+	/// Rotates to the next key on refresh
+	struct RotatingKey(std::sync::Mutex<Vec<&'static str>>);
+
+	impl SecretProvider for RotatingKey {
+		fn api_key(&self) -> Result<String, SecretError> {
+			Ok(self.0.lock().unwrap().last().unwrap().to_string())
+		}
+
+		fn refresh(&self) -> bool {
+			self.0.lock().unwrap().pop();
+			true
+		}
+	}
+
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_secret_provider(
+		adapter.clone(),
+		|| Ok::<_, SecretError>("from_callback".to_string()),
+		Environment::Production,
+	);
+	c.site_details(1).await.unwrap();
+	assert_eq!(
+		Some("from_callback".into()),
+		adapter.urls.lock().unwrap()[0]
+			.query_pairs()
+			.find(|(key, _)| key == "api_key")
+			.map(|(_, value)| value)
+	);
+
+	let flaky = FlakyAdapter {
+		failures: 1.into(),
+		status: http_adapter::http::StatusCode::FORBIDDEN,
+	};
+	let c = Client::new_with_secret_provider(
+		flaky,
+		RotatingKey(std::sync::Mutex::new(vec!["new", "old"])),
+		Environment::Production,
+	);
+	c.site_details(1).await.unwrap();
+	let flaky = FlakyAdapter {
+		failures: 2.into(),
+		status: http_adapter::http::StatusCode::FORBIDDEN,
+	};
+	let c = Client::new_with_secret_provider(
+		flaky,
+		RotatingKey(std::sync::Mutex::new(vec!["new", "old"])),
+		Environment::Production,
+	);
+	assert!(matches!(
+		c.site_details(1).await,
+		Err(Error::Api(http_adapter::http::StatusCode::FORBIDDEN, _))
+	));
+
+	let path = std::env::temp_dir().join(format!("solaredge_api_key_{}", std::process::id()));
+	std::fs::write(&path, "from_file\n").unwrap();
+	assert_eq!("from_file", FileSecret::new(&path).api_key().unwrap());
+	std::fs::remove_file(&path).unwrap();
+	assert!(FileSecret::new(&path).api_key().is_err());
+
+	let c = Client::new_with_secret_provider(
+		RecordingAdapter::default(),
+		EnvSecret::new("SOLAREDGE_TEST_MISSING_API_KEY"),
+		Environment::Production,
+	);
+	let e = c.site_details(1).await.unwrap_err();
+	assert!(matches!(e, Error::Secret(_)));
+	assert!(e.to_string().starts_with("API key error: "));
+}
+
+#[tokio::test]
+async fn raw_requests() {
+	let c = fixtures_client();
+	let fixture = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site/1/overview.json")).unwrap();
+	assert_eq!(fixture, c.fetch_raw("/site/1/overview.json", ()).await.unwrap());
+	assert_eq!(
+		fixture,
+		c.fetch_raw("site/1/overview.json", [("systemUnits", "Metrics")])
+			.await
+			.unwrap()
+	);
+	assert!(matches!(
+		c.fetch_raw("/site/404/overview.json", ()).await,
+		Err(Error::Replay(..))
+	));
+
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "KEY");
+	c.fetch_raw("/site/1/overview.json", [("systemUnits", "Metrics")])
+		.await
+		.unwrap();
+	let url = adapter.urls.lock().unwrap()[0].clone();
+	assert_eq!("/site/1/overview.json", url.path());
+	assert_eq!(Some("systemUnits=Metrics&api_key=KEY"), url.query());
+}
+
+#[test]
+fn api_key_redaction() {
+	let e = Error::HttpRequest(
+		"error sending request for url (https://monitoringapi.solaredge.com/site/1/details.json?api_key=SECRET)".to_string(),
+	);
+	assert_eq!(
+		"HTTP request error: error sending request for url (https://monitoringapi.solaredge.com/site/1/details.json?api_key=<hidden>)",
+		e.to_string()
+	);
+	let debug = format!("{e:?}");
+	assert!(!debug.contains("SECRET"));
+	assert!(debug.starts_with("HttpRequest(\"error sending request"));
+	let e = Error::HttpRequest("?a=1&api_key=SECRET&b=2 api_key=OTHER".to_string());
+	assert_eq!(
+		"HTTP request error: ?a=1&api_key=<hidden>&b=2 api_key=<hidden>",
+		e.to_string()
+	);
+	assert!(!format!("{:?}", Client::new_with_client(ReqwestAdapter::default(), "SECRET")).contains("SECRET"));
+}
+
+#[tokio::test]
+async fn audit_sink() {
+	let entries = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+	let c = Client::new_with_client(RecordingAdapter::default(), "SECRET").with_audit_sink({
+		let entries = std::sync::Arc::clone(&entries);
+		move |entry: &AuditEntry| {
+			entries.lock().unwrap().push((
+				entry.endpoint.to_string(),
+				entry.request_key.as_str().to_string(),
+				entry.attempts,
+				entry.response.map(<[u8]>::to_vec),
+			))
+		}
+	});
+	c.site_details(1).await.unwrap();
+	c.site_details(404).await.unwrap_err();
+	let entries = entries.lock().unwrap();
+	assert_eq!(2, entries.len());
+	let fixture = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site/1/details.json")).unwrap();
+	assert_eq!(
+		(
+			"site_details".to_string(),
+			"/site/1/details.json".to_string(),
+			1,
+			Some(fixture)
+		),
+		entries[0]
+	);
+	assert_eq!(
+		("site_details".to_string(), "/site/404/details.json".to_string(), 1, None),
+		entries[1]
+	);
+}
+
+#[tokio::test]
+async fn raw_responses() {
+	let c = Client::new_with_client(RecordingAdapter::default(), "");
+	let details = c.with_raw(|c| async move { c.site_details(1).await }).await;
+	assert_eq!(1, details.value.unwrap().id);
+	let fixture = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site/1/details.json")).unwrap();
+	assert_eq!(1, details.raw.len());
+	assert_eq!("site_details", details.raw[0].endpoint);
+	assert_eq!("/site/1/details.json", details.raw[0].path);
+	assert_eq!(fixture, details.raw[0].body);
+	assert_eq!(Some(1), details.raw[0].json().unwrap()["details"]["id"].as_u64());
+	// recorder is not shared with the original client
+	c.site_details(1).await.unwrap();
+	assert_eq!(1, details.raw.len());
+
+	let c = Client::new_with_client(BodyAdapter(r#"{"details":{"id":"not a number"}}"#.into()), "");
+	let details = c.with_raw(|c| async move { c.site_details(1).await }).await;
+	assert!(matches!(details.value, Err(Error::Json(_))));
+	assert_eq!(r#"{"details":{"id":"not a number"}}"#, details.raw[0].body);
+}
+
+#[tokio::test]
+async fn error_report() {
+	assert!(fixtures_client().error_report().is_none());
+	let c = fixtures_client().with_error_report(std::time::Duration::from_secs(3600));
+	c.site_details(1).await.unwrap();
+	assert_eq!(Some(0), c.error_report().map(|report| report.total()));
+	c.site_details(404).await.unwrap_err();
+	c.clone().site_overview(404).await.unwrap_err();
+	c.site_overview(404).await.unwrap_err();
+	let report = c.error_report().unwrap();
+	assert_eq!(3, report.total());
+	assert_eq!(Some(&3), report.by_class().get(&ErrorClass::Replay));
+	assert_eq!(Some(&2), report.by_endpoint().get("site_overview"));
+	assert_eq!(
+		"3 failures in 3600s, site_details: replay 1, site_overview: replay 2",
+		report.to_string()
+	);
+
+	let c = Client::new_with_client(
+		FlakyAdapter {
+			failures: 1.into(),
+			status: http_adapter::http::StatusCode::SERVICE_UNAVAILABLE,
+		},
+		"",
+	)
+	.with_error_report(std::time::Duration::from_secs(60));
+	c.site_details(1).await.unwrap_err();
+	c.site_details(1).await.unwrap();
+	let report = c.error_report().unwrap();
+	assert_eq!(1, report.total());
+	assert_eq!(Some(&1), report.by_class().get(&ErrorClass::ServerError));
+}
+
+#[tokio::test]
+async fn lenient_lists() {
+	let mut inventory: serde_json::Value = serde_json::from_slice(
+		&std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site/1/inventory.json")).unwrap(),
+	)
+	.unwrap();
+	let inverters = inventory["Inventory"]["inverters"].as_array_mut().unwrap();
+	let mut broken = inverters[0].clone();
+	broken["connectedOptimizers"] = "many".into();
+	inverters.insert(0, broken);
+	let c = Client::new_with_client(BodyAdapter(inventory.to_string()), "");
+
+	assert!(matches!(c.site_inventory(1).await, Err(Error::Json(_))));
+	let res = c.with_lenient(|c| async move { c.site_inventory(1).await }).await.unwrap();
+	assert_eq!(1, res.value.inverters.len());
+	assert_eq!("12345678", res.value.inverters[0].sn);
+	assert_eq!(1, res.value.meters.len());
+	assert_eq!(1, res.skipped.len());
+	assert_eq!(0, res.skipped[0].0);
+	assert!(res.skipped[0].1.to_string().contains("many"));
+	// lenient mode doesn't leak to the original client
+	assert!(matches!(c.site_inventory(1).await, Err(Error::Json(_))));
+
+	let c = fixtures_client();
+	let res = c.with_lenient(|c| async move { c.site_inventory(1).await }).await.unwrap();
+	assert!(res.skipped.is_empty());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn data_availability() {
+	use response::DataAvailability;
+
+	let c = fixtures_client();
+	let period = c.site_data_period(1).await.unwrap();
+	assert!(period.availability().is_available());
+
+	let c = Client::new_with_client(BodyAdapter(r#"{"dataPeriod":{"startDate":null,"endDate":null}}"#.into()), "");
+	let period = c.site_data_period(1).await.unwrap();
+	assert_eq!(DataAvailability::NotTransmitting, period.availability());
+	assert_eq!(None, period.availability().available());
+
+	let range = SiteEnergy {
+		start_date: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap(),
+		end_date: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap(),
+		time_unit: Some(TimeUnit::Day),
+	};
+	let c = Client::new_with_client(
+		BodyAdapter(
+			r#"{"energy":{"timeUnit":"DAY","unit":"Wh","values":[{"date":"2021-08-10 00:00:00","value":null},{"date":"2021-08-11 00:00:00","value":null}]}}"#.into(),
+		),
+		"",
+	);
+	let energy = c.site_energy(1, &range).await.unwrap();
+	assert_eq!(DataAvailability::NoData, energy.availability());
+	let c = Client::new_with_client(
+		BodyAdapter(
+			r#"{"energy":{"timeUnit":"DAY","unit":"Wh","values":[{"date":"2021-08-10 00:00:00","value":null},{"date":"2021-08-11 00:00:00","value":0.0}]}}"#.into(),
+		),
+		"",
+	);
+	let energy = c.site_energy(1, &range).await.unwrap();
+	assert_eq!(Some(2), energy.availability().map(<[_]>::len).available());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn calendar_bounds() {
+	use chrono::{FixedOffset, TimeZone, Weekday};
+
+	use crate::calendar::{site_today, DateBounds};
+
+	let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+	// Wednesday
+	let week = DateBounds::week(date(2, 28), Weekday::Mon);
+	assert_eq!((date(2, 26), date(3, 3)), (week.first, week.last));
+	let week = DateBounds::week(date(2, 28), Weekday::Sun);
+	assert_eq!((date(2, 25), date(3, 2)), (week.first, week.last));
+	assert_eq!(week, DateBounds::week(date(2, 25), Weekday::Sun));
+	assert_eq!(7, week.days());
+	assert_eq!((date(2, 18), date(2, 24)), (week.previous().first, week.previous().last));
+
+	let month = DateBounds::month(date(2, 28));
+	assert_eq!((date(2, 1), date(2, 29)), (month.first, month.last));
+	assert_eq!(29, month.days());
+	assert!(month.contains(date(2, 29)) && !month.contains(date(3, 1)));
+	let year = DateBounds::year(date(2, 28));
+	assert_eq!((date(1, 1), date(12, 31)), (year.first, year.last));
+
+	let range = DateBounds::day(date(3, 1)).date_time_range();
+	assert_eq!(date(3, 1).and_time(NaiveTime::MIN), range.start_time);
+	assert_eq!(date(3, 1).and_hms_opt(23, 59, 59).unwrap(), range.end_time);
+	let energy = month.site_energy(Some(TimeUnit::Day));
+	assert_eq!((date(2, 1), date(2, 29)), (energy.start_date, energy.end_date));
+
+	// the date at the site differs from UTC one around midnight
+	let east = FixedOffset::east_opt(14 * 3600).unwrap();
+	let west = FixedOffset::west_opt(12 * 3600).unwrap();
+	assert!(site_today(&east) > site_today(&west));
+	assert_eq!(
+		date(3, 1),
+		east
+			.from_utc_datetime(&date(2, 29).and_hms_opt(12, 0, 0).unwrap())
+			.date_naive()
+	);
+}
+
+#[cfg(feature = "serialize")]
+#[tokio::test]
+async fn serialize_responses() {
+	let c = fixtures_client();
+	let overview = c.site_overview(1).await.unwrap();
+	let json = serde_json::to_string(&overview).unwrap();
+	let restored: response::SiteOverview = serde_json::from_str(&json).unwrap();
+	assert_eq!(overview, restored);
+
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 1).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 2).unwrap().and_time(NaiveTime::MIN),
+	};
+	let telemetries = c.equipment_data(1, "12345678", &range).await.unwrap();
+	let json = serde_json::to_value(&telemetries).unwrap();
+	assert_eq!(
+		serde_json::Value::String("12345678".into()),
+		serde_json::to_value(&c.site_inventory(1).await.unwrap().inverters[0]).unwrap()["SN"]
+	);
+	let restored: Vec<response::EquipmentTelemetry> = serde_json::from_value(json).unwrap();
+	assert_eq!(telemetries, restored);
+}
+
+#[tokio::test]
+async fn response_equality() {
+	let c = fixtures_client();
+	let inventory = c.site_inventory(1).await.unwrap();
+	let mut changed = inventory.clone();
+	assert_eq!(inventory, changed);
+	changed.inverters[0].connected_optimizers += 1;
+	assert_ne!(inventory, changed);
+	let sites = c.sites_list(&SitesList::default()).await.unwrap();
+	assert_eq!(sites, sites.clone());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn dst_conversion() {
+	use chrono::{FixedOffset, LocalResult, NaiveDateTime, TimeZone};
+
+	use crate::series::{local_to_utc, series_to_utc, DstError, DstPolicy, DstTransition};
+
+	/// Central European time with the transitions of 2021 only
+	#[derive(Clone, Copy, Debug)]
+	struct Cet;
+
+	impl TimeZone for Cet {
+		type Offset = FixedOffset;
+
+		fn from_offset(_offset: &FixedOffset) -> Self {
+			Cet
+		}
+
+		fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+			self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+		}
+
+		fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+			let offsets = [7200, 3600]
+				.into_iter()
+				.map(|secs| FixedOffset::east_opt(secs).unwrap())
+				.filter(|offset| self.offset_from_utc_datetime(&(*local - *offset)) == *offset)
+				.collect::<Vec<_>>();
+			match offsets[..] {
+				[] => LocalResult::None,
+				[offset] => LocalResult::Single(offset),
+				[earliest, latest] => LocalResult::Ambiguous(earliest, latest),
+				_ => unreachable!(),
+			}
+		}
+
+		fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+			self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+		}
+
+		fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+			let spring = NaiveDate::from_ymd_opt(2021, 3, 28).unwrap().and_hms_opt(1, 0, 0).unwrap();
+			let autumn = NaiveDate::from_ymd_opt(2021, 10, 31).unwrap().and_hms_opt(1, 0, 0).unwrap();
+			FixedOffset::east_opt(if (spring..autumn).contains(utc) {
+				7200
+			} else {
+				3600
+			})
+			.unwrap()
+		}
+	}
+
+	let local = |m, d, h, min| NaiveDate::from_ymd_opt(2021, m, d).unwrap().and_hms_opt(h, min, 0).unwrap();
+	let utc = |m, d, h, min| chrono::Utc.from_utc_datetime(&local(m, d, h, min));
+
+	assert_eq!(
+		Ok(Some(utc(6, 1, 10, 0))),
+		local_to_utc(&local(6, 1, 12, 0), &Cet, DstPolicy::Error)
+	);
+	// fold
+	let fold = local(10, 31, 2, 30);
+	assert_eq!(Ok(Some(utc(10, 31, 0, 30))), local_to_utc(&fold, &Cet, DstPolicy::Earliest));
+	assert_eq!(Ok(Some(utc(10, 31, 1, 30))), local_to_utc(&fold, &Cet, DstPolicy::Latest));
+	assert_eq!(Ok(None), local_to_utc(&fold, &Cet, DstPolicy::Skip));
+	assert_eq!(
+		Err(DstError {
+			local: fold,
+			transition: DstTransition::Fold
+		}),
+		local_to_utc(&fold, &Cet, DstPolicy::Error)
+	);
+	// gap
+	let gap = local(3, 28, 2, 30);
+	assert_eq!(Ok(Some(utc(3, 28, 0, 30))), local_to_utc(&gap, &Cet, DstPolicy::Earliest));
+	assert_eq!(Ok(Some(utc(3, 28, 1, 30))), local_to_utc(&gap, &Cet, DstPolicy::Latest));
+	let e = local_to_utc(&gap, &Cet, DstPolicy::Error).unwrap_err();
+	assert_eq!(DstTransition::Gap, e.transition);
+	assert_eq!(
+		"Local time 2021-03-28 02:30:00 doesn't exist because of the DST transition",
+		e.to_string()
+	);
+
+	let series = [1, 2, 3]
+		.map(|h| response::SiteDateValue {
+			date: local(3, 28, h, 0),
+			value: Some(f64::from(h)),
+		})
+		.to_vec();
+	let converted = series_to_utc(&series, &Cet, DstPolicy::Skip).unwrap();
+	assert_eq!(
+		vec![(utc(3, 28, 0, 0), Some(1.)), (utc(3, 28, 1, 0), Some(3.))],
+		converted.into_iter().map(|(utc, item)| (utc, item.value)).collect::<Vec<_>>()
+	);
+	assert!(series_to_utc(&series, &Cet, DstPolicy::Error).is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn progress() {
+	use std::sync::{Arc, Mutex};
+	use std::time::Duration;
+
+	use crate::Progress;
+
+	let reported = Arc::new(Mutex::new(vec![]));
+	let c = Client::new_with_client(RecordingAdapter::default(), "").with_progress({
+		let reported = Arc::clone(&reported);
+		move |progress: &Progress| {
+			assert_eq!(progress.done == progress.total, progress.is_complete());
+			reported.lock().unwrap().push((
+				progress.operation.to_string(),
+				progress.done,
+				progress.total,
+				progress.fraction(),
+			));
+		}
+	});
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 1).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 16).unwrap().and_time(NaiveTime::MIN),
+	};
+	c.equipment_data_range(1, "12345678", &range).await.unwrap();
+	let expected = (1..=3)
+		.map(|done| ("equipment_data_range".to_string(), done, 3, done as f64 / 3.))
+		.collect::<Vec<_>>();
+	assert_eq!(expected, *reported.lock().unwrap());
+
+	let progress = Progress {
+		operation: "site_energy_bulk",
+		done: 0,
+		total: 4,
+		elapsed: Duration::ZERO,
+	};
+	assert_eq!(None, progress.eta());
+	let progress = Progress {
+		done: 1,
+		elapsed: Duration::from_secs(2),
+		..progress
+	};
+	assert_eq!(Some(Duration::from_secs(6)), progress.eta());
+	assert_eq!(0.25, progress.fraction());
+}
+
+#[tokio::test]
+async fn base_url() {
+	let adapter = RecordingAdapter::default();
+	let base_url = url::Url::parse("http://localhost:8080/solaredge").unwrap();
+	let c = Client::new_with_client(adapter.clone(), "KEY").with_base_url(base_url.clone());
+	assert_eq!(&Environment::Custom(base_url), c.environment());
+	// no such fixture
+	assert!(c.site_details(1).await.is_err());
+	let c = c.with_base_url(url::Url::parse("http://localhost:8080/").unwrap());
+	c.site_details(1).await.unwrap();
+	let urls = adapter
+		.urls
+		.lock()
+		.unwrap()
+		.iter()
+		.map(|url| format!("{}{}", url.origin().ascii_serialization(), url.path()))
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			"http://localhost:8080/solaredge/site/1/details.json",
+			"http://localhost:8080/site/1/details.json"
+		],
+		urls
+	);
+}
+
+#[tokio::test]
+async fn solaredge_api_trait() {
+	use crate::{SerialNumber, SiteId, SolarEdgeApi};
+
+	async fn site_name<E>(api: &dyn SolarEdgeApi<Error = E>, site_id: SiteId) -> Result<String, Error<E>> {
+		Ok(api.site_details(site_id).await?.name)
+	}
+
+	let c = fixtures_client();
+	assert_eq!(c.site_details(1).await.unwrap().name, site_name(&c, SiteId(1)).await.unwrap());
+	let api: &dyn SolarEdgeApi<Error = _> = &c;
+	assert_eq!(
+		c.equipment_changelog(1, "12345678").await.unwrap(),
+		api.equipment_changelog(SiteId(1), SerialNumber::from("12345678"))
+			.await
+			.unwrap()
+	);
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn mock_client() {
+	use http_adapter::http::StatusCode;
+
+	use crate::testing::MockClient;
+	use crate::{SiteId, SolarEdgeApi};
+
+	let fixture = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site/1/details.json")).unwrap();
+	let details = serde_json::from_str::<serde_json::Value>(&fixture).unwrap()["details"].to_string();
+	let expected = fixtures_client().site_details(1).await.unwrap();
+	let mock = MockClient::<String>::new()
+		.with_response("site_details", details)
+		.with_error_once("site_details", || Error::Api(StatusCode::SERVICE_UNAVAILABLE, vec![]))
+		.with_response_once("version_current", r#""1.0.0""#)
+		.with_response("version_current", r#""2.0.0""#)
+		.with_response("equipment_list", "{}");
+	let api: &dyn SolarEdgeApi<Error = String> = &mock;
+	assert!(matches!(
+		api.site_details(SiteId(1)).await,
+		Err(Error::Api(StatusCode::SERVICE_UNAVAILABLE, _))
+	));
+	assert_eq!(expected, api.site_details(SiteId(1)).await.unwrap());
+	assert_eq!(expected, api.site_details(SiteId(2)).await.unwrap());
+	assert_eq!("1.0.0", api.version_current().await.unwrap());
+	assert_eq!("2.0.0", api.version_current().await.unwrap());
+	assert!(matches!(api.equipment_list(SiteId(1)).await, Err(Error::Json(_))));
+	assert_eq!(
+		vec![
+			"site_details",
+			"site_details",
+			"site_details",
+			"version_current",
+			"version_current",
+			"equipment_list"
+		],
+		mock.calls()
+	);
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+#[should_panic(expected = "No response is configured for the endpoint: site_overview")]
+async fn mock_client_unconfigured() {
+	use crate::testing::MockClient;
+	use crate::{SiteId, SolarEdgeApi};
+
+	let _ = MockClient::<String>::new().site_overview(SiteId(1)).await;
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn request_plan() {
+	use std::time::Duration;
+
+	use crate::{RateLimitExceeded, RateLimiter, RateLimits, RequestPlan, SiteEnergy, SiteId, TimeUnit};
+
+	let date = |m, d| NaiveDate::from_ymd_opt(2021, m, d).unwrap();
+	let mut plan = RequestPlan::new();
+	plan
+		.key_requests(1)
+		.bulk_requests(&(1..=150).collect::<Vec<u64>>(), 2)
+		.equipment_data_range(
+			1,
+			&DateTimeRange {
+				start_time: date(8, 1).and_time(NaiveTime::MIN),
+				end_time: date(8, 16).and_time(NaiveTime::MIN),
+			},
+		)
+		.site_energy_full_range(
+			2,
+			&SiteEnergy {
+				start_date: date(1, 1),
+				end_date: date(12, 31),
+				time_unit: Some(TimeUnit::QuarterOfAnHour),
+			},
+		)
+		.polling(3, Duration::from_secs(5 * 60), 1);
+	assert_eq!(1 + 2 * 2 + 3 + 12 + 288, plan.total_requests());
+	assert_eq!(1, plan.key_requests);
+	assert_eq!(Some(&(2 + 3)), plan.site_requests.get(&SiteId(1)));
+	assert_eq!(Some(&(2 + 12)), plan.site_requests.get(&SiteId(2)));
+	assert_eq!(Some(&(2 + 288)), plan.site_requests.get(&SiteId(3)));
+	assert_eq!(Some(&2), plan.site_requests.get(&SiteId(150)));
+
+	assert_eq!(Ok(()), plan.check(&RateLimits::default()));
+	assert_eq!(Some(1), plan.days_required(&RateLimits::default()));
+	let limits = RateLimits {
+		daily_per_site: Some(100),
+		..RateLimits::default()
+	};
+	assert_eq!(Err(RateLimitExceeded::Site(3)), plan.check(&limits));
+	assert_eq!(Some(3), plan.days_required(&limits));
+	let limits = RateLimits {
+		daily_per_key: Some(0),
+		..RateLimits::default()
+	};
+	assert_eq!(Err(RateLimitExceeded::Key), plan.check(&limits));
+	assert_eq!(None, plan.days_required(&limits));
+
+	let limiter = RateLimiter::new(RateLimits::default());
+	assert_eq!(Ok(()), plan.check_remaining(&limiter));
+	limiter.restore(&crate::RateLimiterUsage {
+		site_used: [(2, 290)].into_iter().collect(),
+		..limiter.usage()
+	});
+	assert_eq!(Err(RateLimitExceeded::Site(2)), plan.check_remaining(&limiter));
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn cassette_adapter() {
+	use crate::{CassetteAdapter, CassetteError, CassetteMode};
+
+	let dir = std::env::temp_dir().join(format!("solaredge-cassette-{}", std::process::id()));
+	let inner = RecordingAdapter::default();
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 1).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 8).unwrap().and_time(NaiveTime::MIN),
+	};
+
+	let c = Client::new_with_client(CassetteAdapter::new(inner.clone(), &dir, CassetteMode::Auto), "SECRET");
+	let details = c.site_details(1).await.unwrap();
+	let telemetries = c.equipment_data(1, "12345678", &range).await.unwrap();
+	assert_eq!(details, c.site_details(1).await.unwrap());
+	assert_eq!(telemetries, c.equipment_data(1, "12345678", &range).await.unwrap());
+	assert_eq!(2, inner.urls.lock().unwrap().len());
+	assert!(dir.join("site/1/details.json").is_file());
+	let recordings = std::fs::read_dir(dir.join("equipment/1/12345678"))
+		.unwrap()
+		.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(1, recordings.len());
+	assert!(recordings[0].starts_with("data.") && recordings[0].ends_with(".json"));
+	assert!(
+		!std::fs::read_to_string(dir.join("equipment/1/12345678").join(&recordings[0]))
+			.unwrap()
+			.contains("SECRET")
+	);
+
+	// another API key replays the same recordings
+	let c = Client::new_with_client(CassetteAdapter::new(inner.clone(), &dir, CassetteMode::Replay), "OTHER");
+	assert_eq!(details, c.site_details(1).await.unwrap());
+	assert!(matches!(
+		c.site_overview(1).await,
+		Err(Error::HttpRequest(CassetteError::Io(_, e))) if e.kind() == std::io::ErrorKind::NotFound
+	));
+
+	let c = Client::new_with_client(CassetteAdapter::new(inner.clone(), &dir, CassetteMode::Record), "SECRET");
+	c.site_details(1).await.unwrap();
+	assert_eq!(3, inner.urls.lock().unwrap().len());
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Fixtures of the site 2 capture the API quirks that broke the parsing before
+#[tokio::test]
+async fn fixture_edge_cases() {
+	let c = fixtures_client();
+	let details = c.site_details(2).await.unwrap();
+	assert!(!details.public_settings.is_public);
+
+	let inventory = c.site_inventory(2).await.unwrap();
+	let physical_meter = &inventory.meters[0];
+	assert_eq!(Some("WattNode"), physical_meter.manufacturer.as_deref());
+	assert_eq!(Some("31"), physical_meter.firmware_version.as_deref());
+	let virtual_meter = inventory.meters.last().unwrap();
+	assert_eq!("Consumption Meter", virtual_meter.name);
+	assert_eq!(None, virtual_meter.manufacturer);
+	assert_eq!(None, virtual_meter.firmware_version);
+	assert_eq!("", virtual_meter.connected_solaredge_device_sn.as_str());
+	assert_eq!(
+		c.site_inventory(1).await.unwrap().meters[..],
+		inventory.meters[..inventory.meters.len() - 1]
+	);
+	assert_eq!(9800., inventory.batteries[0].nameplate_capacity);
+}
+
+#[test]
+fn numeric_strings() {
+	let values = serde_json::from_str::<Vec<response::SiteDateValue>>(
+		r#"[
+			{"date": "2021-08-01 00:00:00", "value": 1},
+			{"date": "2021-08-01 00:15:00", "value": " 2.5"},
+			{"date": "2021-08-01 00:30:00", "value": null},
+			{"date": "2021-08-01 00:45:00"}
+		]"#,
+	)
+	.unwrap();
+	assert_eq!(
+		vec![Some(1.), Some(2.5), None, None],
+		values.iter().map(|value| value.value).collect::<Vec<_>>()
+	);
+	let storage = serde_json::from_str::<response::SiteStorageData>(r#"{"batteryCount": "0", "batteries": []}"#).unwrap();
+	assert_eq!(0, storage.battery_count);
+	let e = serde_json::from_str::<response::SiteStorageData>(r#"{"batteryCount": "-1", "batteries": []}"#).unwrap_err();
+	assert!(e.to_string().starts_with("Number parse error, input: -1"));
+	assert!(serde_json::from_str::<response::SiteStorageData>(r#"{"batteryCount": -1, "batteries": []}"#).is_err());
+
+	let periods = serde_json::from_str::<Vec<response::DataPeriodBulk>>(
+		r#"[
+			{"siteId": "12345", "dataPeriod": {"startDate": null, "endDate": null}},
+			{"siteId": 12346, "dataPeriod": {"startDate": null, "endDate": null}}
+		]"#,
+	)
+	.unwrap();
+	assert_eq!(SiteId(12345), periods[0].site_id);
+	assert_eq!(SiteId(12346), periods[1].site_id);
+	assert!(serde_json::from_str::<SiteId>(r#""site""#).is_err());
+	assert_eq!("12345", serde_json::to_string(&SiteId(12345)).unwrap());
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn tower_service() {
+	use tower_service::Service;
+
+	use crate::Endpoint;
+
+	let adapter = RecordingAdapter::default();
+	let mut c = Client::new_with_client(adapter.clone(), "KEY").with_error_report(std::time::Duration::from_secs(60));
+	let endpoint = Endpoint::new("site_overview", "site/1/overview.json", [("systemUnits", "Metrics")]).unwrap();
+	assert_eq!("/site/1/overview.json?systemUnits=Metrics", endpoint.request_key().as_str());
+	std::future::poll_fn(|cx| c.poll_ready(cx)).await.unwrap();
+	let body = c.call(endpoint).await.unwrap();
+	assert_eq!(
+		std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site/1/overview.json")).unwrap(),
+		body
+	);
+	let url = adapter.urls.lock().unwrap()[0].clone();
+	assert_eq!(
+		"/site/1/overview.json?systemUnits=Metrics&api_key=KEY",
+		&url[url::Position::BeforePath..]
+	);
+
+	let missing = Endpoint::new("site_missing", "/site/1/missing.json", ()).unwrap();
+	assert!(c.call(missing).await.is_err());
+	assert_eq!(Some(1), c.error_report().unwrap().by_endpoint().get("site_missing").copied());
+}
+
+#[test]
+fn empty_strings() {
+	let location = serde_json::from_str::<response::Location>(
+		r#"{"country": "Germany", "state": " ", "city": "Berlin", "address": "", "address2": "", "zip": "10115",
+		"timeZone": "Europe/Berlin", "countryCode": "DE"}"#,
+	)
+	.unwrap();
+	assert_eq!(None, location.state);
+	assert_eq!("", location.address);
+	let settings = serde_json::from_str::<response::PublicSettings>(r#"{"name": "Home", "isPublic": true}"#).unwrap();
+	assert_eq!(Some("Home"), settings.name.as_deref());
+	let settings = serde_json::from_str::<response::PublicSettings>(r#"{"name": "", "isPublic": true}"#).unwrap();
+	assert_eq!(None, settings.name);
+}
+
+#[tokio::test]
+async fn middleware() {
+	use std::sync::{Arc, Mutex};
+
+	use http_adapter::http::StatusCode;
+	use http_adapter::{Request, Response};
+
+	use crate::ClientMiddleware;
+
+	struct Logging(&'static str, Arc<Mutex<Vec<String>>>);
+
+	impl ClientMiddleware for Logging {
+		fn before_request(&self, endpoint: &str, request: &mut Request<Vec<u8>>) {
+			request.headers_mut().insert("x-middleware", self.0.parse().unwrap());
+			self.1.lock().unwrap().push(format!("{} before {endpoint}", self.0));
+		}
+
+		fn after_response(&self, endpoint: &str, response: &Response<Vec<u8>>) {
+			self
+				.1
+				.lock()
+				.unwrap()
+				.push(format!("{} after {endpoint} {}", self.0, response.status().as_u16()));
+		}
+	}
+
+	struct Noop;
+
+	impl ClientMiddleware for Noop {}
+
+	let log = Arc::new(Mutex::new(vec![]));
+	let adapter = FlakyAdapter {
+		failures: 1.into(),
+		status: StatusCode::INTERNAL_SERVER_ERROR,
+	};
+	let c = Client::new_with_client(adapter, "")
+		.with_middleware(Logging("first", Arc::clone(&log)))
+		.with_middleware(Noop)
+		.with_middleware(Logging("second", Arc::clone(&log)));
+	assert!(c.site_details(1).await.is_err());
+	c.site_details(1).await.unwrap();
+	assert_eq!(
+		vec![
+			"first before site_details",
+			"second before site_details",
+			"first after site_details 500",
+			"second after site_details 500",
+			"first before site_details",
+			"second before site_details",
+			"first after site_details 200",
+			"second after site_details 200",
+		],
+		*log.lock().unwrap()
+	);
+
+	log.lock().unwrap().clear();
+	fixtures_client()
+		.with_middleware(Logging("replay", Arc::clone(&log)))
+		.site_details(1)
+		.await
+		.unwrap();
+	assert!(log.lock().unwrap().is_empty());
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn meter_ids() {
+	use std::collections::HashSet;
+
+	use crate::analytics::meters::MeterId;
+
+	let c = fixtures_client();
+	let range = MetersDateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+		time_unit: None,
+		meters: None,
+	};
+	let inventory = c.site_inventory(1).await.unwrap();
+	let meters = c.site_meters(1, &range).await.unwrap();
+	let enriched = analytics::meters::enrich_site_meters(&inventory, &meters);
+	assert_eq!(MeterId::Serial("MTR123"), enriched[0].id());
+	assert_eq!(Some("MTR123"), enriched[0].id().serial());
+	let details = c.site_energy_details(1, &range).await.unwrap();
+	let enriched = analytics::meters::enrich_meter_details(&inventory, &details);
+	assert_eq!(
+		MeterId::Named {
+			name: "Production Meter",
+			meter_type: "Production"
+		},
+		enriched[0].id()
+	);
+	assert_eq!("Production Meter (Production)", enriched[0].id().to_string());
+	assert_eq!(MeterId::Type("Purchased"), enriched[1].id());
+	assert_eq!(None, enriched[1].id().serial());
+
+	let inventory = c.site_inventory(2).await.unwrap();
+	let ids = inventory.meters.iter().map(MeterId::from).collect::<HashSet<_>>();
+	assert_eq!(2, ids.len());
+	assert!(ids.contains(&MeterId::Named {
+		name: "Consumption Meter",
+		meter_type: "Consumption"
+	}));
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn params_query_string() {
+	use crate::Params;
+
+	let params = MetersDateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+		time_unit: Some(TimeUnit::Day),
+		meters: Some(&[MeterType::Production, MeterType::FeedIn]),
+	};
+	let query = params.to_query_string().unwrap();
+	assert_eq!(
+		"startTime=2021-08-10+00%3A00%3A00&endTime=2021-08-11+00%3A00%3A00&timeUnit=DAY&meters=Production%2CFeedIn",
+		query
+	);
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "KEY");
+	c.site_energy_details(1, &params).await.unwrap();
+	let url = adapter.urls.lock().unwrap()[0].clone();
+	assert_eq!(Some(format!("{query}&api_key=KEY").as_str()), url.query());
+
+	assert_eq!("", SiteEnvBenefits { system_units: None }.to_query_string().unwrap());
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn request_metrics() {
+	use std::collections::BTreeMap;
+
+	use http_adapter::http::StatusCode;
+	use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+	let recorder = DebuggingRecorder::new();
+	let snapshotter = recorder.snapshotter();
+	metrics::with_local_recorder(&recorder, || {
+		let adapter = FlakyAdapter {
+			failures: 1.into(),
+			status: StatusCode::SERVICE_UNAVAILABLE,
+		};
+		let c = Client::new_with_client(adapter, "");
+		let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+		rt.block_on(async {
+			assert!(c.site_details(1).await.is_err());
+			c.site_details(1).await.unwrap();
+			c.site_overview(1).await.unwrap();
+		});
+	});
+	let mut counters = BTreeMap::new();
+	let mut histograms = BTreeMap::new();
+	for (key, _, _, value) in snapshotter.snapshot().into_vec() {
+		let key = key.key();
+		let labels = key
+			.labels()
+			.map(|label| format!("{}={}", label.key(), label.value()))
+			.collect::<Vec<_>>();
+		let name = format!("{} {}", key.name(), labels.join(","));
+		match value {
+			DebugValue::Counter(value) => {
+				counters.insert(name, value);
+			}
+			DebugValue::Histogram(values) => {
+				histograms.insert(name, values.len());
+			}
+			DebugValue::Gauge(_) => unreachable!(),
+		}
+	}
+	let expected = BTreeMap::from([
+		(
+			"solaredge_request_errors_total endpoint=site_details,status=503".to_string(),
+			1,
+		),
+		("solaredge_requests_total endpoint=site_details".to_string(), 2),
+		("solaredge_requests_total endpoint=site_overview".to_string(), 1),
+	]);
+	assert_eq!(expected, counters);
+	let expected = BTreeMap::from([
+		("solaredge_request_duration_seconds endpoint=site_details".to_string(), 2),
+		("solaredge_request_duration_seconds endpoint=site_overview".to_string(), 1),
+	]);
+	assert_eq!(expected, histograms);
+}
+
+#[tokio::test]
+async fn path_encoding() {
+	use crate::PathEncoding;
+
+	assert_eq!("7E1234AB-C5", PathEncoding::Segment.encode("7E1234AB-C5"));
+	assert_eq!("A.B_C~D+E", PathEncoding::Segment.encode("A.B_C~D+E"));
+	assert_eq!("SN%201%2F2%233%3F%25%5C", PathEncoding::Segment.encode("SN 1/2#3?%\\"));
+	assert_eq!("%C3%9C-1", PathEncoding::Segment.encode("Ü-1"));
+	assert_eq!("7E1234AB%2DC5", PathEncoding::NonAlphanumeric.encode("7E1234AB-C5"));
+	assert_eq!("A%2EB%5FC%7ED", PathEncoding::NonAlphanumeric.encode("A.B_C~D"));
+
+	let changes = fixtures_client().equipment_changelog(1, "7E1234AB-C5").await.unwrap();
+	assert_eq!("7E1234AB-C5", changes[0].serial_number);
+
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "");
+	c.equipment_changelog(1, "7E1234AB-C5").await.unwrap();
+	let c = c.with_path_encoding(PathEncoding::NonAlphanumeric);
+	assert!(c.equipment_changelog(1, "7E1234AB-C5").await.is_err());
+	let paths = adapter
+		.urls
+		.lock()
+		.unwrap()
+		.iter()
+		.map(|url| url.path().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			"/equipment/1/7E1234AB-C5/changeLog.json",
+			"/equipment/1/7E1234AB%2DC5/changeLog.json"
+		],
+		paths
+	);
+}
+
+#[tokio::test]
+async fn quota_tracker() {
+	use std::time::{Duration, UNIX_EPOCH};
+
+	use crate::{QuotaScope, QuotaTracker, RateLimiterUsage};
+
+	let tracker = QuotaTracker::new(RateLimits {
+		daily_per_key: Some(10),
+		daily_per_site: None,
+		..RateLimits::default()
+	});
+	let c = Client::new_with_client(RecordingAdapter::default(), "").with_quota_tracker(tracker);
+	c.site_details(1).await.unwrap();
+	c.site_data_period_bulk(&[1, 2]).await.unwrap();
+	c.sites_list(&SitesList::default()).await.unwrap();
+	assert!(c.equipment_list(2).await.is_err());
+	let quota = c.clone().quota().used(QuotaScope::Site(SiteId(1)));
+	assert_eq!(2, quota);
+	assert_eq!(2, c.quota().used(QuotaScope::Site(SiteId(2))));
+	assert_eq!(0, c.quota().used(QuotaScope::Site(SiteId(3))));
+	assert_eq!(1, c.quota().used(QuotaScope::Key));
+	assert_eq!(Some(9), c.quota().remaining(QuotaScope::Key));
+	assert_eq!(None, c.quota().remaining(QuotaScope::Site(SiteId(1))));
+
+	// replayed requests are counted like by the rate limiter
+	let c = fixtures_client().with_rate_limits(RateLimits::default());
+	c.site_details(1).await.unwrap();
+	assert_eq!(1, c.quota().used(QuotaScope::Site(SiteId(1))));
+	assert_eq!(c.rate_limiter().unwrap().usage(), c.quota().usage());
+
+	let saved = serde_json::to_string(&c.quota().usage()).unwrap();
+	let tracker = QuotaTracker::default();
+	assert!(tracker.restore(&serde_json::from_str(&saved).unwrap()));
+	assert_eq!(1, tracker.used(QuotaScope::Site(SiteId(1))));
+	let yesterday = RateLimiterUsage {
+		day: tracker.usage().day - 1,
+		key_used: 5,
+		..RateLimiterUsage::default()
+	};
+	assert!(!tracker.restore(&yesterday));
+	assert_eq!(0, tracker.used(QuotaScope::Key));
+
+	// the counts are reset at midnight UTC, 2021-08-10 21:30 UTC
+	let tracker = QuotaTracker::default();
+	let evening = UNIX_EPOCH + Duration::from_secs(1628631000);
+	tracker.record_at("/sites/1,2/dataPeriod.json", evening);
+	tracker.record_at("/version/current.json", evening);
+	let later = evening + Duration::from_secs(2 * 3600);
+	assert_eq!(1, tracker.used_at(QuotaScope::Site(SiteId(1)), later));
+	assert_eq!(1, tracker.used_at(QuotaScope::Key, later));
+	assert_eq!(Some(299), tracker.remaining_at(QuotaScope::Site(SiteId(2)), later));
+	let next_day = evening + Duration::from_secs(3 * 3600);
+	assert_eq!(0, tracker.used_at(QuotaScope::Key, next_day));
+	assert_eq!(Some(300), tracker.remaining_at(QuotaScope::Site(SiteId(2)), next_day));
+}
+
+#[tokio::test]
+async fn status_filter() {
+	use crate::{InvalidRequest, StatusFilter, Validate};
+
+	let filter = StatusFilter::new(&[SiteStatus::Disabled, SiteStatus::Active, SiteStatus::Disabled]).unwrap();
+	assert_eq!(&[SiteStatus::Active, SiteStatus::Disabled], filter.as_slice());
+	assert!(filter.matches(SiteStatus::Disabled));
+	assert!(!filter.matches(SiteStatus::Pending));
+	assert_eq!(
+		StatusFilter::ALL,
+		StatusFilter::new(&[SiteStatus::All, SiteStatus::All]).unwrap()
+	);
+	assert!(StatusFilter::ALL.matches(SiteStatus::Pending));
+	assert_eq!(
+		StatusFilter::default(),
+		StatusFilter::new(&[SiteStatus::Pending, SiteStatus::Active]).unwrap()
+	);
+	assert_eq!(Err(InvalidRequest::InvalidStatusFilter), StatusFilter::new(&[]));
+	assert_eq!(
+		Err(InvalidRequest::InvalidStatusFilter),
+		StatusFilter::new(&[SiteStatus::Active, SiteStatus::All])
+	);
+
+	let mut sites = SitesList {
+		status: Some(&[SiteStatus::All, SiteStatus::Disabled]),
+		..SitesList::default()
+	};
+	assert_eq!(Err(InvalidRequest::InvalidStatusFilter), sites.validate());
+	assert!(matches!(
+		fixtures_client().sites_list(&sites).await,
+		Err(Error::InvalidRequest(InvalidRequest::InvalidStatusFilter))
+	));
+	sites.status = Some(StatusFilter::ALL.as_slice());
+	assert_eq!(2, fixtures_client().sites_list(&sites).await.unwrap().len());
+}
+
+#[tokio::test]
+async fn accounts_list_filtering() {
+	use crate::AccountSortBy;
+
+	let all_accounts = fixtures_client().accounts_list(&AccountsList::default()).await.unwrap();
+	let mut accounts = all_accounts.clone();
+	let p = AccountsList {
+		search_text: Some("REGION"),
+		sort_property: Some(AccountSortBy::Name),
+		sort_order: Some(SortOrder::Descending),
+		..AccountsList::default()
+	};
+	p.apply(&mut accounts);
+	assert_eq!(vec![12, 11], accounts.iter().map(|a| a.id).collect::<Vec<_>>());
+
+	let mut accounts = all_accounts.clone();
+	let p = AccountsList {
+		sort_property: Some(AccountSortBy::Address),
+		start_index: Some(1),
+		size: Some(2),
+		..AccountsList::default()
+	};
+	p.apply(&mut accounts);
+	assert_eq!(vec![11, 12], accounts.iter().map(|a| a.id).collect::<Vec<_>>());
+
+	let p = AccountsList {
+		search_text: Some("missing"),
+		..AccountsList::default()
+	};
+	assert!(!all_accounts.iter().any(|a| p.matches(a)));
+}
+
+#[tokio::test]
+async fn request_priority() {
+	use std::time::Duration;
+
+	use crate::RequestPriority;
+
+	/// Serves the fixtures after a delay to keep the concurrency slot busy
+	#[derive(Clone, Default)]
+	struct SlowAdapter(RecordingAdapter);
+
+	#[async_trait::async_trait(?Send)]
+	impl http_adapter::HttpClientAdapter for SlowAdapter {
+		type Error = std::io::Error;
+
+		async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+			tokio::time::sleep(Duration::from_millis(10)).await;
+			self.0.execute(request).await
+		}
+	}
+
+	let adapter = SlowAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "").with_rate_limits(RateLimits {
+		concurrent: Some(1),
+		..RateLimits::default()
+	});
+	let backfill = c.clone().with_priority(RequestPriority::Low);
+	let realtime = c.clone().with_priority(RequestPriority::High);
+	let (details, energy, overview, flow, period) = tokio::join!(
+		c.site_details(1),
+		backfill.site_env_benefits(1, &SiteEnvBenefits { system_units: None }),
+		c.site_overview(1),
+		realtime.site_current_power_flow(1),
+		backfill.site_data_period(1),
+	);
+	details.unwrap();
+	energy.unwrap();
+	overview.unwrap();
+	flow.unwrap();
+	period.unwrap();
+	let paths = adapter
+		.0
+		.urls
+		.lock()
+		.unwrap()
+		.iter()
+		.map(|url| url.path().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			"/site/1/details.json",
+			"/site/1/currentPowerFlow.json",
+			"/site/1/overview.json",
+			"/site/1/envBenefits.json",
+			"/site/1/dataPeriod.json",
+		],
+		paths
+	);
+	assert_eq!(0, c.rate_limiter().unwrap().active_requests());
+}
+
+#[tokio::test]
+async fn concurrent_fan_out() {
+	use std::cell::Cell;
+	use std::rc::Rc;
+	use std::time::Duration;
+
+	let c = Client::new_with_client(RecordingAdapter::default(), "");
+	let running = Rc::new(Cell::new(0));
+	let max_running = Rc::new(Cell::new(0));
+	let results = c
+		.for_each_site_concurrent(&[1, 404, 1, 1, 1], 2, {
+			let running = Rc::clone(&running);
+			let max_running = Rc::clone(&max_running);
+			move |c, site_id| {
+				let running = Rc::clone(&running);
+				let max_running = Rc::clone(&max_running);
+				async move {
+					running.set(running.get() + 1);
+					max_running.set(max_running.get().max(running.get()));
+					tokio::time::sleep(Duration::from_millis(5)).await;
+					let res = c.site_overview(site_id).await;
+					running.set(running.get() - 1);
+					res
+				}
+			}
+		})
+		.await;
+	assert_eq!(2, max_running.get());
+	assert_eq!(5, results.len());
+	assert!(matches!(results[0], Ok((SiteId(1), _))));
+	assert!(matches!(&results[1], Err(e) if e.site_id == 404));
+	assert!(results[2..].iter().all(|res| matches!(res, Ok((SiteId(1), _)))));
+
+	assert!(c
+		.for_each_site_concurrent(
+			&[] as &[SiteId],
+			0,
+			|c, site_id| async move { c.site_overview(site_id).await }
+		)
+		.await
+		.is_empty());
+}
+
+#[tokio::test]
+async fn site_image() {
+	use http_adapter::http::StatusCode;
+
+	use crate::response::ImageResult;
+	use crate::SiteImage;
+
+	/// Serves the image with the `ETag`, responds with HTTP 304 when the request passes its hash
+	#[derive(Clone, Default)]
+	struct ImageAdapter(RecordingAdapter);
+
+	#[async_trait::async_trait(?Send)]
+	impl http_adapter::HttpClientAdapter for ImageAdapter {
+		type Error = std::io::Error;
+
+		async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+			let url = url::Url::parse(&request.uri().to_string()).unwrap();
+			self.0.urls.lock().unwrap().push(url.clone());
+			let res = if url.query_pairs().any(|(key, value)| key == "hash" && value == "1a2b") {
+				http_adapter::Response::builder()
+					.status(StatusCode::NOT_MODIFIED)
+					.body(vec![])
+			} else {
+				http_adapter::Response::builder()
+					.header(http_adapter::http::header::ETAG, "\"1a2b\"")
+					.body(b"JPEG".to_vec())
+			};
+			Ok(res.unwrap())
+		}
+	}
+
+	let adapter = ImageAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "").with_error_report(std::time::Duration::from_secs(3600));
+	let params = SiteImage {
+		max_width: Some(640),
+		..SiteImage::default()
+	};
+	let image = c.site_image(1, "site image.jpg", &params).await.unwrap();
+	assert_eq!(ImageResult::Image(b"JPEG".to_vec(), Some("1a2b".to_string())), image);
+	let ImageResult::Image(_, hash) = image else {
+		unreachable!()
+	};
+	let params = SiteImage {
+		hash: hash.as_deref(),
+		..params
+	};
+	assert_eq!(
+		ImageResult::NotModified,
+		c.site_image(1, "site image.jpg", &params).await.unwrap()
+	);
+	assert_eq!(0, c.error_report().unwrap().total());
+	let urls = adapter.0.urls.lock().unwrap().clone();
+	assert_eq!("/site/1/siteImage/site%20image.jpg", urls[0].path());
+	assert_eq!(Some("maxWidth=640&hash=1a2b&api_key="), urls[1].query());
+
+	// other endpoints don't take 304 for an empty body
+	assert!(matches!(
+		Client::new_with_client(adapter.clone(), "")
+			.fetch_raw("/site/1/details.json", [("hash", "1a2b")])
+			.await,
+		Err(Error::Api(StatusCode::NOT_MODIFIED, _))
+	));
+}
+
+#[tokio::test]
+async fn deprecation_notice() {
+	use std::time::{Duration, UNIX_EPOCH};
+
+	use http_adapter::http::StatusCode;
+
+	/// Serves the fixtures with the deprecation headers, `/version/current.json` fails as the removed endpoint
+	#[derive(Clone, Default)]
+	struct DeprecatedAdapter(RecordingAdapter);
+
+	#[async_trait::async_trait(?Send)]
+	impl http_adapter::HttpClientAdapter for DeprecatedAdapter {
+		type Error = std::io::Error;
+
+		async fn execute(&self, request: http_adapter::Request<Vec<u8>>) -> Result<http_adapter::Response<Vec<u8>>, Self::Error> {
+			if request.uri().path() == "/version/current.json" {
+				let res = http_adapter::Response::builder()
+					.status(StatusCode::GONE)
+					.body(br#"{"String": "This endpoint is deprecated, use /version/supported"}"#.to_vec())
+					.unwrap();
+				return Ok(res);
+			}
+			let mut res = self.0.execute(request).await?;
+			let headers = res.headers_mut();
+			headers.insert("deprecation", "@1688169599".parse().unwrap());
+			headers.insert("sunset", "Sun, 30 Jun 2024 23:59:59 GMT".parse().unwrap());
+			Ok(res)
+		}
+	}
+
+	let c = Client::new_with_client(DeprecatedAdapter::default(), "");
+	let details = c.with_meta(|c| async move { c.site_details(1).await }).await.unwrap();
+	let deprecation = details.meta[0].deprecation.as_ref().unwrap();
+	assert_eq!(Some("@1688169599"), deprecation.deprecated.as_deref());
+	assert_eq!(Some(UNIX_EPOCH + Duration::from_secs(1719791999)), deprecation.sunset_at());
+	assert_eq!(None, deprecation.message);
+	assert_eq!(
+		"endpoint is deprecated, deprecation: @1688169599, sunset: Sun, 30 Jun 2024 23:59:59 GMT",
+		deprecation.to_string()
+	);
+	assert!(matches!(c.version_current().await, Err(Error::Api(StatusCode::GONE, _))));
+	let gone = http_adapter::Response::builder()
+		.status(StatusCode::GONE)
+		.body(br#"{"String": "This endpoint is deprecated"}"#.to_vec())
+		.unwrap();
+	let deprecation = crate::Deprecation::from_response(&gone).unwrap();
+	assert_eq!(Some("This endpoint is deprecated"), deprecation.message.as_deref());
+	assert_eq!(None, deprecation.sunset_at());
+	assert_eq!(
+		None,
+		crate::Deprecation::from_response(&http_adapter::Response::new(br#"{"String": "deprecated"}"#.to_vec()))
+	);
+
+	let details = fixtures_client()
+		.with_meta(|c| async move { c.site_details(1).await })
+		.await
+		.unwrap();
+	assert_eq!(None, details.meta[0].deprecation);
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn equipment_data_all() {
+	use crate::SerialNumber;
+
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 1).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+	};
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "");
+	let telemetries = c.equipment_data_all(1, &range).await.unwrap();
+	let mut serials = telemetries.keys().map(|serial| serial.as_str()).collect::<Vec<_>>();
+	serials.sort_unstable();
+	assert_eq!(vec!["12345678", "7E1234AB-C5"], serials);
+	assert_eq!(
+		c.equipment_data_range(1, "12345678", &range).await.unwrap(),
+		telemetries[&SerialNumber::from("12345678")]
+	);
+	// list and 2 weekly windows for each inverter
+	assert_eq!(5, adapter.urls.lock().unwrap().len() - 2);
+
+	assert!(c.equipment_data_all(404, &range).await.is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn post_processors() {
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let plain = fixtures_client().equipment_data(1, "12345678", &range).await.unwrap();
+	let c = fixtures_client()
+		.with_post_processor("equipment_data", |telemetries: &mut Vec<response::EquipmentTelemetry>| {
+			telemetries.reverse()
+		})
+		.with_post_processor("equipment_data", |telemetries: &mut Vec<response::EquipmentTelemetry>| {
+			telemetries.truncate(1)
+		})
+		// mismatched type is skipped
+		.with_post_processor("equipment_data", |telemetries: &mut Vec<response::SensorTelemetry>| telemetries.clear());
+	let telemetries = c.equipment_data(1, "12345678", &range).await.unwrap();
+	assert_eq!(1, telemetries.len());
+	assert_eq!(plain.last(), telemetries.first());
+
+	// range helpers get the processed response of every request
+	assert_eq!(telemetries, c.equipment_data_range(1, "12345678", &range).await.unwrap());
+
+	// other endpoints are not affected
+	let c = c.with_post_processor("site_details", |site: &mut response::Site| site.name.clear());
+	assert!(c.site_details(1).await.unwrap().name.is_empty());
+	assert!(!fixtures_client().site_details(1).await.unwrap().name.is_empty());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn api_limits() {
+	use crate::{limits, InvalidRequest, MaxPeriod, RequestPlan, Validate};
+
+	assert_eq!(
+		RateLimits {
+			daily_per_key: Some(limits::DAILY_QUOTA_PER_KEY),
+			daily_per_site: Some(limits::DAILY_QUOTA_PER_SITE),
+			concurrent: Some(limits::MAX_CONCURRENT_REQUESTS),
+		},
+		RateLimits::default()
+	);
+	assert_eq!(Some(MaxPeriod::Months(1)), limits::energy_max_period(TimeUnit::Hour));
+	assert_eq!(Some(MaxPeriod::Months(12)), limits::energy_max_period(TimeUnit::Day));
+	assert_eq!(None, limits::energy_max_period(TimeUnit::Year));
+
+	let sites = SitesList {
+		size: Some(limits::MAX_PAGE_SIZE),
+		..Default::default()
+	};
+	assert_eq!(Ok(()), sites.validate());
+	let range = SiteStorageData {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 1).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 9).unwrap().and_time(NaiveTime::MIN),
+		serials: None,
+	};
+	assert_eq!(
+		Err(InvalidRequest::PeriodTooLong {
+			max: limits::TELEMETRY_MAX_PERIOD
+		}),
+		range.validate()
+	);
+
+	let site_ids = (0..=limits::MAX_BULK_SITES).map(|site_id| site_id as u64).collect::<Vec<_>>();
+	assert_eq!(2, RequestPlan::new().bulk_requests(&site_ids, 1).total_requests());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn site_snapshot() {
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "");
+	let snapshot = c.site_snapshot(1).await.unwrap();
+	assert_eq!(c.site_overview(1).await.unwrap(), snapshot.overview);
+	assert!(snapshot.power_flow.storage.is_some());
+	assert_eq!(1, snapshot.storage.unwrap().battery_count);
+	let storage_url = adapter
+		.urls
+		.lock()
+		.unwrap()
+		.iter()
+		.find(|url| url.path() == "/site/1/storageData.json")
+		.cloned()
+		.unwrap();
+	let query = storage_url.query_pairs().collect::<std::collections::HashMap<_, _>>();
+	assert_eq!("2021-08-12 09:15:00", query["startTime"]);
+	assert_eq!("2021-08-12 10:15:00", query["endTime"]);
+
+	// no storage
+	let snapshot = c.site_snapshot(2).await.unwrap();
+	assert!(snapshot.storage.is_none());
+	assert!(!adapter
+		.urls
+		.lock()
+		.unwrap()
+		.iter()
+		.any(|url| url.path() == "/site/2/storageData.json"));
+
+	assert!(c.site_snapshot(404).await.is_err());
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn weather_join() {
+	use crate::analytics::weather::{join_weather, unexplained_dips, WeatherError, WeatherProvider, WeatherSample};
+	use crate::response::{Location, SiteDateValue};
+
+	struct Hourly;
+
+	#[async_trait::async_trait(?Send)]
+	impl WeatherProvider for Hourly {
+		async fn weather(&self, _location: &Location, range: &DateTimeRange) -> Result<Vec<WeatherSample>, WeatherError> {
+			let irradiance = [0., 200., 400., 400.];
+			Ok(irradiance
+				.iter()
+				.enumerate()
+				.map(|(i, &irradiance)| {
+					let start = range.start_time + chrono::Duration::hours(i as i64);
+					WeatherSample {
+						start,
+						end: start + chrono::Duration::hours(1),
+						temperature: Some(20.),
+						irradiance: Some(irradiance),
+					}
+				})
+				.collect())
+		}
+	}
+
+	let c = fixtures_client();
+	let site = c.site_details(1).await.unwrap();
+	let start = NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_hms_opt(6, 0, 0).unwrap();
+	let range = DateTimeRange {
+		start_time: start,
+		end_time: start + chrono::Duration::hours(4),
+	};
+	let weather = Hourly.weather(&site.location, &range).await.unwrap();
+	let production = [(0, 10.), (70, 1000.), (140, 2000.), (190, 800.), (230, 2100.), (300, 5000.)]
+		.into_iter()
+		.map(|(minutes, value)| SiteDateValue {
+			date: start + chrono::Duration::minutes(minutes),
+			value: Some(value),
+		})
+		.collect::<Vec<_>>();
+	let joined = join_weather(&production, &weather);
+	assert_eq!(6, joined.len());
+	assert_eq!(Some(&weather[1]), joined[1].weather);
+	assert_eq!(Some(&weather[3]), joined[4].weather);
+	// after the last weather sample
+	assert!(joined[5].weather.is_none());
+	assert_eq!(None, joined[0].yield_per_irradiance(50.));
+	assert_eq!(Some(5.), joined[1].yield_per_irradiance(50.));
+
+	let dips = unexplained_dips(&joined, 50., 0.5);
+	assert_eq!(1, dips.len());
+	assert_eq!(production[3].date, dips[0].item.date);
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn virtual_plant() {
+	use crate::analytics::virtual_plant::{PlantPowerFlow, VirtualPlant};
+
+	let c = fixtures_client();
+	let params = SitePowerDetails {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+		meters: None,
+	};
+	let details = c.site_power_details(1, &params).await.unwrap();
+	let mut plant = VirtualPlant::new([1, 2]);
+	assert!(plant.contains(2));
+	assert_eq!(None, plant.combine_meters([(3, &details)]));
+	let combined = plant.combine_meters([(1, &details), (2, &details), (3, &details)]).unwrap();
+	assert_eq!(details.unit, combined.unit);
+	assert_eq!(details.meters.len(), combined.meters.len());
+	for meter in &details.meters {
+		let combined = combined.meters.iter().find(|combined| combined.typ == meter.typ).unwrap();
+		for (item, combined) in meter.values.iter().zip(&combined.values) {
+			assert_eq!(item.date, combined.date);
+			assert_eq!(item.value.map(|value| value * 2.), combined.value);
+		}
+	}
+
+	let storage = c
+		.site_storage_data(
+			1,
+			&SiteStorageData {
+				start_time: params.start_time,
+				end_time: params.end_time,
+				serials: None,
+			},
+		)
+		.await
+		.unwrap();
+	let power = plant.combine_storage_power([(1, &storage), (2, &storage)]);
+	let battery = &storage.batteries[0].telemetries;
+	assert_eq!(battery.len(), power.len());
+	assert_eq!(Some(f64::from(battery[0].power) * 2.), power[0].value);
+
+	let flow_1 = c.site_current_power_flow(1).await.unwrap();
+	let flow_2 = c.site_current_power_flow(2).await.unwrap();
+	assert_eq!(
+		PlantPowerFlow {
+			sites: 2,
+			pv: 3400.,
+			load: 2400.,
+			grid: -1000.,
+			storage: 0.,
+			charge_level: Some(85.),
+		},
+		plant.combine_power_flows([(1, &flow_1), (2, &flow_2), (3, &flow_1)])
+	);
+	assert!(plant.remove(2));
+	assert_eq!(1, plant.combine_power_flows([(1, &flow_1), (2, &flow_2)]).sites);
+}
+
+#[tokio::test]
+async fn response_cache() {
+	use std::sync::Arc;
+	use std::time::{Duration, SystemTime};
+
+	use crate::{CacheStore, EndpointClass, MemoryCacheStore, QuotaScope, RequestKey, ResponseCache, SiteId};
+
+	// every endpoint name passed by the client
+	for (endpoint, class) in [
+		("version_current", EndpointClass::Static),
+		("version_supported", EndpointClass::Static),
+		("sites_list", EndpointClass::Static),
+		("sites_list_all", EndpointClass::Static),
+		("site_details", EndpointClass::Static),
+		("site_image", EndpointClass::Static),
+		("site_data_period", EndpointClass::Static),
+		("site_data_period_bulk", EndpointClass::Static),
+		("follow_details", EndpointClass::Static),
+		("follow_data_period", EndpointClass::Static),
+		("site_inventory", EndpointClass::Static),
+		("equipment_list", EndpointClass::Static),
+		("equipment_changelog", EndpointClass::Static),
+		("equipment_sensors", EndpointClass::Static),
+		("accounts_list", EndpointClass::Static),
+		("accounts_list_all", EndpointClass::Static),
+		("site_overview", EndpointClass::Realtime),
+		("follow_overview", EndpointClass::Realtime),
+		("site_current_power_flow", EndpointClass::Realtime),
+		("site_energy", EndpointClass::Historical),
+		("site_energy_bulk", EndpointClass::Historical),
+		("site_time_frame_energy", EndpointClass::Historical),
+		("site_time_frame_energy_bulk", EndpointClass::Historical),
+		("site_power", EndpointClass::Historical),
+		("site_power_bulk", EndpointClass::Historical),
+		("site_power_details", EndpointClass::Historical),
+		("site_energy_details", EndpointClass::Historical),
+		("site_env_benefits", EndpointClass::Historical),
+		("site_meters", EndpointClass::Historical),
+		("site_storage_data", EndpointClass::Historical),
+		("site_sensor_data", EndpointClass::Historical),
+		("equipment_data", EndpointClass::Historical),
+		("fetch_raw", EndpointClass::Historical),
+	] {
+		assert_eq!(class, EndpointClass::of(endpoint), "{endpoint}");
+	}
+
+	let store = Arc::new(MemoryCacheStore::new());
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "").with_cache(ResponseCache::new().with_store(Arc::clone(&store)));
+	let details = c.site_details(1).await.unwrap();
+	let cached = c.clone().with_meta(|c| async move { c.site_details(1).await }).await.unwrap();
+	assert_eq!(details, cached.value);
+	assert!(cached.meta[0].from_cache);
+	assert_eq!(0, cached.meta[0].attempts);
+	c.site_overview(1).await.unwrap();
+	c.site_overview(1).await.unwrap();
+	assert_eq!(3, adapter.urls.lock().unwrap().len());
+	assert_eq!(3, c.quota().used(QuotaScope::Site(SiteId(1))));
+	assert_eq!(1, store.len());
+
+	// accounts sharing the store don't see each other's responses
+	let other = Client::new_with_client(adapter.clone(), "OTHER").with_cache(ResponseCache::new().with_store(Arc::clone(&store)));
+	let other_details = other.with_meta(|c| async move { c.site_details(1).await }).await.unwrap();
+	assert!(!other_details.meta[0].from_cache);
+	assert_eq!(4, adapter.urls.lock().unwrap().len());
+	assert_eq!(2, store.len());
+	assert!(
+		c.clone()
+			.with_meta(|c| async move { c.site_details(1).await })
+			.await
+			.unwrap()
+			.meta[0]
+			.from_cache
+	);
+	assert!(
+		other
+			.with_meta(|c| async move { c.site_details(1).await })
+			.await
+			.unwrap()
+			.meta[0]
+			.from_cache
+	);
+	assert_eq!(4, adapter.urls.lock().unwrap().len());
+
+	c.cache().unwrap().invalidate(EndpointClass::Static);
+	assert!(store.is_empty());
+	c.site_details(1).await.unwrap();
+	assert_eq!(5, adapter.urls.lock().unwrap().len());
+
+	// disabled class is neither stored nor served
+	let key = RequestKey::new("/site/1/details.json", ()).unwrap();
+	let cache = ResponseCache::new()
+		.with_store(Arc::clone(&store))
+		.with_ttl(EndpointClass::Static, Duration::ZERO);
+	assert!(cache.get("site_details", &key).is_none());
+	cache.clear();
+	cache.insert("site_details", &key, b"details");
+	assert!(store.is_empty());
+
+	// TTL past the representable time doesn't overflow the expiry
+	let cache = ResponseCache::new()
+		.with_store(Arc::clone(&store))
+		.with_ttl(EndpointClass::Static, Duration::MAX);
+	cache.insert("site_details", &key, b"details");
+	assert_eq!(Some(b"details".to_vec()), cache.get("site_details", &key));
+	cache.clear();
+
+	let now = SystemTime::now();
+	store.put(&key, EndpointClass::Realtime, b"overview", now + Duration::from_secs(60));
+	assert_eq!(Some(b"overview".to_vec()), store.get(&key));
+	store.put(&key, EndpointClass::Realtime, b"overview", now - Duration::from_secs(1));
+	assert_eq!(None, store.get(&key));
+	assert!(store.is_empty());
+}
+
+#[tokio::test]
+async fn prefetch() {
+	use crate::{EndpointKind, ResponseCache, SiteId};
+
+	let endpoints = [
+		EndpointKind::SiteDetails,
+		EndpointKind::SiteInventory,
+		EndpointKind::SiteOverview,
+	];
+	let adapter = RecordingAdapter::default();
+	let c = Client::new_with_client(adapter.clone(), "");
+	assert!(c.prefetch(&[1, 2], &endpoints).await.is_empty());
+	assert!(adapter.urls.lock().unwrap().is_empty());
+
+	// only the cached classes are requested
+	let c = c.with_cache(ResponseCache::new());
+	assert!(c.prefetch(&[1, 2], &endpoints).await.is_empty());
+	assert_eq!(4, adapter.urls.lock().unwrap().len());
+	c.site_inventory(2).await.unwrap();
+	c.site_details(1).await.unwrap();
+	assert_eq!(4, adapter.urls.lock().unwrap().len());
+
+	let failures = c.prefetch(&[1, 3], &[EndpointKind::SiteDetails]).await;
+	assert_eq!(5, adapter.urls.lock().unwrap().len());
+	assert_eq!(1, failures.len());
+	assert_eq!(SiteId(3), failures[0].site_id);
+}
+
+#[cfg(all(feature = "analytics", feature = "chrono"))]
+#[tokio::test]
+async fn inverter_contributions() {
+	use crate::analytics::equipment::inverter_contributions;
+	use crate::SerialNumber;
+
+	let c = fixtures_client();
+	let range = DateTimeRange {
+		start_time: NaiveDate::from_ymd_opt(2021, 8, 10).unwrap().and_time(NaiveTime::MIN),
+		end_time: NaiveDate::from_ymd_opt(2021, 8, 11).unwrap().and_time(NaiveTime::MIN),
+	};
+	let mut telemetries = c.equipment_data_all(1, &range).await.unwrap();
+	let second = telemetries.get_mut(&SerialNumber::from("7E1234AB-C5")).unwrap();
+	second.reverse();
+	second[0].total_energy += 500.;
+	// counter reset
+	second.push(second[1].clone());
+	second[2].total_energy = 0.;
+	second[2].date += chrono::Duration::minutes(10);
+
+	let contributions = inverter_contributions(&telemetries, Some(1500.));
+	assert_eq!(2, contributions.len());
+	assert_eq!("12345678", contributions[0].serial_number.as_str());
+	assert_eq!(250., contributions[0].energy);
+	assert_eq!(750., contributions[1].energy);
+	assert_eq!(0.25, contributions[0].share);
+	assert_eq!(Some(1125.), contributions[1].site_energy);
+
+	let serial_number = SerialNumber::from("1");
+	let empty = Vec::<response::EquipmentTelemetry>::new();
+	let contributions = inverter_contributions([(&serial_number, &empty)], None);
+	assert_eq!(0., contributions[0].share);
+	assert_eq!(None, contributions[0].site_energy);
+}
+
+#[cfg(not(feature = "chrono"))]
+mod string_dates {
+	use crate::api::{Date, DateTime};
+
+	#[test]
+	fn date_validation() {
+		assert_eq!("2024-02-29", Date::parse("2024-02-29").unwrap().as_str());
+		assert!(Date::parse("2000-02-29").is_ok());
+		assert!(Date::parse("2023-02-29").is_err());
+		assert!(Date::parse("1900-02-29").is_err());
+		assert!(Date::parse("2021-01-31").is_ok());
+		assert!(Date::parse("2021-04-30").is_ok());
+		assert!(Date::parse("2021-04-31").is_err());
+		assert!(Date::parse("2021-12-31").is_ok());
+		assert!(Date::parse("2021-13-01").is_err());
+		assert!(Date::parse("2021-00-10").is_err());
+		assert!(Date::parse("2021-08-00").is_err());
+		assert!(Date::parse("2021-8-10").is_err());
+		assert!(Date::parse("2021/08/10").is_err());
+		assert!(Date::parse("2021-08-1a").is_err());
+		assert!(Date::parse("").is_err());
+	}
+
+	#[test]
+	fn date_time_validation() {
+		assert_eq!(
+			"2021-08-10 13:05:09",
+			DateTime::parse("2021-08-10 13:05:09").unwrap().as_str()
+		);
+		assert_eq!("2021-08-10 00:00:00", DateTime::parse("2021-08-10").unwrap().as_str());
+		assert_eq!("2021-08-10", DateTime::parse("2021-08-10 23:59:59").unwrap().date().as_str());
+		assert!(DateTime::parse("2021-08-10 24:00:00").is_err());
+		assert!(DateTime::parse("2021-08-10 23:60:00").is_err());
+		assert!(DateTime::parse("2021-08-10 23:59:60").is_err());
+		assert!(DateTime::parse("2021-08-10T13:05:09").is_err());
+		assert!(DateTime::parse("2021-08-10 13:05").is_err());
+		assert!(DateTime::parse("2023-02-29 00:00:00").is_err());
+		// multibyte character at the split position
+		assert!(DateTime::parse("2021-08-1\u{e9}00:00:00").is_err());
+		assert!(DateTime::parse("2021-08-10 00:00:\u{e9}").is_err());
+	}
+
+	#[test]
+	fn ordering() {
+		let dates = ["2021-08-10", "2021-08-09", "2020-12-31"]
+			.into_iter()
+			.map(|s| Date::parse(s).unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(dates[2], *dates.iter().min().unwrap());
+		assert_eq!(dates[0], *dates.iter().max().unwrap());
+		assert!(DateTime::parse("2021-08-10 09:00:00").unwrap() < DateTime::parse("2021-08-10 10:00:00").unwrap());
+		assert!(DateTime::parse("2021-08-09 23:59:59").unwrap() < DateTime::parse("2021-08-10").unwrap());
+	}
+}